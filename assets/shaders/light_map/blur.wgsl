@@ -1,5 +1,5 @@
 @group(0) @binding(0)
-var tiles_texture: texture_storage_2d<r8uint, read>;
+var tiles_texture: texture_storage_2d<rgba8uint, read>;
 
 @group(0) @binding(1)
 var light_texture: texture_storage_2d<rgba8unorm, read_write>;
@@ -73,13 +73,10 @@ fn bottom_to_top(@builtin(global_invocation_id) invocation_id: vec3<u32>) {
 }
 
 fn get_decay(pos: vec2<u32>) -> f32 {
-    let tile = textureLoad(tiles_texture, pos / u32(#SUBDIVISION)).r;
+    // The absorption goes from 0 for air to 255 for a solid block
+    let absorption = f32(textureLoad(tiles_texture, pos / u32(#SUBDIVISION)).g) / 255.;
 
-    if tile == 1u {
-        return DECAY_THROUGH_SOLID;
-    } else {
-        return DECAY_THROUGH_AIR;
-    }
+    return mix(DECAY_THROUGH_AIR, DECAY_THROUGH_SOLID, absorption);
 }
 
 fn blur(
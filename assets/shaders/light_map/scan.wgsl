@@ -1,5 +1,5 @@
 @group(0) @binding(0)
-var tiles_texture: texture_storage_2d<r8uint, read>;
+var tiles_texture: texture_storage_2d<rgba8uint, read>;
 
 @group(0) @binding(1)
 var light_texture: texture_storage_2d<rgba8unorm, write>;
@@ -13,16 +13,27 @@ var<uniform> sky_color: vec4<f32>;
 @compute @workgroup_size(16, 16, 1)
 fn scan(@builtin(global_invocation_id) invocation_id: vec3<u32>) {
     let pos = min + invocation_id.xy;
+    let tile = textureLoad(tiles_texture, pos / u32(#SUBDIVISION));
 
     var light = vec3(0.);
 
-    if pos.y < (u32(#WORLD_UNDERGROUND_LEVEL) * u32(#SUBDIVISION)) {
-        let tile = textureLoad(tiles_texture, pos / u32(#SUBDIVISION)).r;
-
-        if tile == 0u {
-            light = sky_color.rgb;
-        }
+    if pos.y < (u32(#WORLD_UNDERGROUND_LEVEL) * u32(#SUBDIVISION)) && tile.r == 0u {
+        light = sky_color.rgb;
     }
+
+    // Glowing tiles light themselves, the blur passes spread the light around
+    light = max(light, unpack_glow(tile));
     
     textureStore(light_texture, pos, vec4(light, 1.));
+}
+
+// The glow color is packed as RGB565 into the blue and alpha channels
+fn unpack_glow(tile: vec4<u32>) -> vec3<f32> {
+    let packed = (tile.b << 8u) | tile.a;
+
+    return vec3(
+        f32(packed >> 11u) / 31.,
+        f32((packed >> 5u) & 63u) / 63.,
+        f32(packed & 31u) / 31.,
+    );
 }
\ No newline at end of file
@@ -0,0 +1,234 @@
+use std::{error::Error, path::Path, process::ExitCode, collections::BTreeMap, time::{SystemTime, UNIX_EPOCH}};
+
+use game::{load_items, world::{WorldData, WorldSize, generator, registry::load_tiles, world_file::WORLD_FILE_EXTENSION}};
+
+const USAGE: &str = "\
+Usage:
+    worldgen generate [--size tiny|medium|large] [--seed <seed>] [--out <file.world|file.wld|file.png>] [--draw-layers]
+    worldgen info <file.world|file.wld>
+    worldgen diff <first.world|first.wld> <second.world|second.wld>";
+
+fn main() -> Result<ExitCode, Box<dyn Error>> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    let Some((command, args)) = args.split_first() else {
+        eprintln!("{}", USAGE);
+        return Ok(ExitCode::FAILURE);
+    };
+
+    load_tiles()?;
+    load_items()?;
+
+    match command.as_str() {
+        "generate" => generate(args),
+        "info" => info(args),
+        "diff" => diff(args),
+        "help" | "--help" | "-h" => {
+            println!("{}", USAGE);
+            Ok(ExitCode::SUCCESS)
+        },
+        _ => {
+            eprintln!("Unknown command \"{}\"\n\n{}", command, USAGE);
+            Ok(ExitCode::FAILURE)
+        }
+    }
+}
+
+fn generate(args: &[String]) -> Result<ExitCode, Box<dyn Error>> {
+    let mut world_size = WorldSize::Tiny;
+    let mut seed: Option<u32> = None;
+    let mut out: Option<String> = None;
+    let mut draw_layers = false;
+
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--size" => world_size = expect_value(&mut args, arg)?.parse()?,
+            "--seed" => seed = Some(expect_value(&mut args, arg)?.parse()?),
+            "--out" => out = Some(expect_value(&mut args, arg)?.to_owned()),
+            "--draw-layers" => draw_layers = true,
+            _ => return Err(format!("Unknown argument \"{}\"", arg).into())
+        }
+    }
+
+    let seed = seed.unwrap_or_else(|| {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as u32
+    });
+
+    let out = out.unwrap_or_else(|| format!("{}.{}", seed, WORLD_FILE_EXTENSION));
+
+    println!("The seed of the world is {}", seed);
+
+    let world_data = generator::generate_world(seed, world_size);
+
+    match extension(&out) {
+        Some("wld") => world_data.save_as_terraria_world(&out)?,
+        Some("png") => world_data.save_as_image(&out, draw_layers)?,
+        Some(WORLD_FILE_EXTENSION) => world_data.save(&out)?,
+        _ => return Err(format!("Unknown output format of \"{}\"", out).into())
+    }
+
+    println!("Saved to {}", out);
+
+    Ok(ExitCode::SUCCESS)
+}
+
+fn info(args: &[String]) -> Result<ExitCode, Box<dyn Error>> {
+    let [path] = args else {
+        return Err("Expected exactly one world file".into());
+    };
+
+    let world_data = load_world(path)?;
+
+    println!("Size: {}x{}", world_data.width(), world_data.height());
+    println!("Playable area: {}x{} at ({}, {})",
+        world_data.playable_width(), world_data.playable_height(),
+        world_data.playable_area.min.x, world_data.playable_area.min.y
+    );
+    println!("Surface layer: {}", world_data.layer.surface);
+    println!("Underground layer: {}", world_data.layer.underground);
+    println!("Cavern layer: {}", world_data.layer.cavern);
+    println!("Spawn point: ({}, {})", world_data.spawn_point.x, world_data.spawn_point.y);
+    println!("Checksum: {:016x}", world_data.checksum());
+
+    println!("Biomes:");
+    for (range, biome) in world_data.biome_ranges() {
+        println!("    {:?}: {}..{}", biome, range.start, range.end);
+    }
+
+    let mut blocks: BTreeMap<&str, usize> = BTreeMap::new();
+    for block in world_data.blocks.iter().flatten() {
+        *blocks.entry(block.name()).or_default() += 1;
+    }
+
+    let mut walls: BTreeMap<String, usize> = BTreeMap::new();
+    for wall in world_data.walls.iter().flatten() {
+        *walls.entry(format!("{:?}", wall.wall_type)).or_default() += 1;
+    }
+
+    let mut liquids: BTreeMap<String, usize> = BTreeMap::new();
+    for liquid in world_data.liquids.iter().flatten() {
+        *liquids.entry(format!("{:?}", liquid.liquid_type)).or_default() += 1;
+    }
+
+    println!("Blocks:");
+    for (name, count) in blocks {
+        println!("    {}: {}", name, count);
+    }
+
+    println!("Walls:");
+    for (name, count) in walls {
+        println!("    {}: {}", name, count);
+    }
+
+    println!("Liquids:");
+    for (name, count) in liquids {
+        println!("    {}: {}", name, count);
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+fn diff(args: &[String]) -> Result<ExitCode, Box<dyn Error>> {
+    const MAX_REPORTED_TILES: usize = 10;
+
+    let [first, second] = args else {
+        return Err("Expected exactly two world files".into());
+    };
+
+    let first = load_world(first)?;
+    let second = load_world(second)?;
+
+    let Some(differing_tiles) = first.differing_tiles(&second) else {
+        println!("The worlds have different sizes: {}x{} and {}x{}",
+            first.width(), first.height(),
+            second.width(), second.height()
+        );
+        return Ok(ExitCode::FAILURE);
+    };
+
+    let mut differences = 0;
+
+    for tile_pos in differing_tiles {
+        if differences < MAX_REPORTED_TILES {
+            println!("Tiles differ at ({}, {})", tile_pos.x, tile_pos.y);
+        }
+
+        differences += 1;
+    }
+
+    if first.biomes != second.biomes {
+        println!("Biomes differ");
+        differences += 1;
+    }
+
+    if first.spawn_point != second.spawn_point {
+        println!("Spawn points differ");
+        differences += 1;
+    }
+
+    if differences == 0 {
+        println!("The worlds are identical");
+        Ok(ExitCode::SUCCESS)
+    } else {
+        println!("Found {} differences", differences);
+        Ok(ExitCode::FAILURE)
+    }
+}
+
+fn load_world(path: &str) -> Result<WorldData, Box<dyn Error>> {
+    match extension(path) {
+        Some("wld") => {
+            let (world_data, skipped) = WorldData::load_terraria_world(path)?;
+
+            if !skipped.is_empty() {
+                println!("Skipped unsupported parts of the world:");
+                for (id, count) in skipped.blocks {
+                    println!("    Block {}: {}", id, count);
+                }
+                for (id, count) in skipped.walls {
+                    println!("    Wall {}: {}", id, count);
+                }
+                if skipped.tree_frames > 0 {
+                    println!("    Unknown tree frames: {}", skipped.tree_frames);
+                }
+                if skipped.furniture_frames > 0 {
+                    println!("    Unknown furniture frames: {}", skipped.furniture_frames);
+                }
+                if skipped.honey > 0 {
+                    println!("    Honey: {}", skipped.honey);
+                }
+                if skipped.chests > 0 {
+                    println!("    Chests without a chest tile: {}", skipped.chests);
+                }
+                for (id, count) in skipped.chest_items {
+                    println!("    Chest item {}: {}", id, count);
+                }
+                if skipped.signs > 0 {
+                    println!("    Signs: {}", skipped.signs);
+                }
+                if skipped.npcs > 0 {
+                    println!("    NPCs: {}", skipped.npcs);
+                }
+            }
+
+            Ok(world_data)
+        },
+        Some(WORLD_FILE_EXTENSION) => Ok(WorldData::load(path)?),
+        _ => Err(format!("Unknown world format of \"{}\"", path).into())
+    }
+}
+
+fn expect_value<'a>(args: &mut impl Iterator<Item = &'a String>, arg: &str) -> Result<&'a str, String> {
+    args.next()
+        .map(|value| value.as_str())
+        .ok_or_else(|| format!("Expected a value after \"{}\"", arg))
+}
+
+fn extension(path: &str) -> Option<&str> {
+    Path::new(path).extension().and_then(|extension| extension.to_str())
+}
@@ -13,15 +13,56 @@ pub(crate) const fn get_tile_start_index(block: BlockType) -> TextureAtlasPos {
         BlockType::Dirt => TextureAtlasPos::ZERO,
         BlockType::Stone => TextureAtlasPos::new(0, 15),
         BlockType::Grass => TextureAtlasPos::new(0, 30),
-        BlockType::Tree(_) => TextureAtlasPos::ZERO,
+        BlockType::Tree(_) | BlockType::Furniture(_) => TextureAtlasPos::ZERO,
         BlockType::Wood => TextureAtlasPos::new(0, 52),
+        BlockType::CopperOre => TextureAtlasPos::new(0, 67),
+        BlockType::IronOre => TextureAtlasPos::new(0, 82),
+        BlockType::SilverOre => TextureAtlasPos::new(0, 97),
+        BlockType::GoldOre => TextureAtlasPos::new(0, 112),
+        BlockType::Sand => TextureAtlasPos::new(0, 127),
+        BlockType::Snow => TextureAtlasPos::new(0, 142),
+        BlockType::Mud => TextureAtlasPos::new(0, 157),
+        BlockType::JungleGrass => TextureAtlasPos::new(0, 172),
     }
 }
 
+/// Row of the first shaped block in the tiles texture. The rows of the shaped blocks are generated when the texture is loaded.
+pub(crate) const SHAPED_TILES_START_ROW: u32 = 194;
+
+/// Blocks which can be shaped with a hammer in the order of their rows in the shaped part of the tiles texture
+pub(crate) const SHAPEABLE_BLOCKS: [BlockType; 12] = [
+    BlockType::Dirt, BlockType::Stone, BlockType::Grass, BlockType::Wood,
+    BlockType::CopperOre, BlockType::IronOre, BlockType::SilverOre, BlockType::GoldOre,
+    BlockType::Sand, BlockType::Snow, BlockType::Mud, BlockType::JungleGrass,
+];
+
+/// Start of the row of the shaped frames of the block. Each shape takes three columns, one per variant.
+pub(crate) const fn get_shaped_tile_start_index(block: BlockType) -> TextureAtlasPos {
+    let row = match block {
+        BlockType::Dirt => 0,
+        BlockType::Stone => 1,
+        BlockType::Grass => 2,
+        BlockType::Wood => 3,
+        BlockType::CopperOre => 4,
+        BlockType::IronOre => 5,
+        BlockType::SilverOre => 6,
+        BlockType::GoldOre => 7,
+        BlockType::Sand => 8,
+        BlockType::Snow => 9,
+        BlockType::Mud => 10,
+        BlockType::JungleGrass => 11,
+        BlockType::Tree(_) | BlockType::Furniture(_) => 0,
+    };
+
+    TextureAtlasPos::new(0, SHAPED_TILES_START_ROW + row)
+}
+
 pub(crate) const fn get_wall_start_index(wall: WallType) -> TextureAtlasPos {
     match wall {
         WallType::Stone => TextureAtlasPos::ZERO,
         WallType::Dirt => TextureAtlasPos::new(0, 5),
+        WallType::Snow => TextureAtlasPos::new(0, 10),
+        WallType::Mud => TextureAtlasPos::new(0, 15),
     }
 }
 
@@ -19,4 +19,42 @@ pub(crate) fn move_towards(current: f32, target: f32, max_delta: f32) -> f32 {
         return target;
     }
     current + (target - current).signum() * max_delta
-}
\ No newline at end of file
+}
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+/// 64-bit FNV-1a hash. Unlike the std hashers it gives the same result on every platform and run.
+#[derive(Clone, Copy)]
+pub(crate) struct Fnv1aHasher(u64);
+
+impl Default for Fnv1aHasher {
+    fn default() -> Self {
+        Self(FNV_OFFSET_BASIS)
+    }
+}
+
+impl Fnv1aHasher {
+    pub(crate) fn update(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 ^= byte as u64;
+            self.0 = self.0.wrapping_mul(FNV_PRIME);
+        }
+    }
+
+    #[inline(always)]
+    pub(crate) const fn finish(self) -> u64 {
+        self.0
+    }
+}
+
+impl std::io::Write for Fnv1aHasher {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
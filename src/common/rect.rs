@@ -51,6 +51,37 @@ impl FRect {
         point.x > self.left() && point.x < self.right() && point.y > self.bottom() && point.y < self.top()
     }
 
+    /// Whether the segment from `start` to `end` crosses the rectangle, it's clipped against each side in turn
+    pub(crate) fn intersects_segment(&self, start: Vec2, end: Vec2) -> bool {
+        let delta = end - start;
+        let mut t_min = 0f32;
+        let mut t_max = 1f32;
+
+        for (p, q) in [
+            (-delta.x, start.x - self.left()),
+            (delta.x, self.right() - start.x),
+            (-delta.y, start.y - self.bottom()),
+            (delta.y, self.top() - start.y),
+        ] {
+            // The segment is parallel to the side, it misses the rectangle when it lies outside of it
+            if p == 0. {
+                if q < 0. { return false; }
+                continue;
+            }
+
+            let t = q / p;
+            if p < 0. {
+                t_min = t_min.max(t);
+            } else {
+                t_max = t_max.min(t);
+            }
+
+            if t_min > t_max { return false; }
+        }
+
+        true
+    }
+
     #[inline]
     pub(crate) fn top(&self) -> f32 {
         self.centery + self.height / 2.
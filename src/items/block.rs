@@ -1,19 +1,15 @@
-use crate::world::block::BlockType;
+use serde::Deserialize;
 
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
 pub(crate) enum ItemBlock {
     Dirt,
     Stone,
     Wood,
+    CopperOre,
+    IronOre,
+    SilverOre,
+    GoldOre,
+    Sand,
+    Snow,
+    Mud,
 }
-
-impl From<BlockType> for ItemBlock {
-    fn from(block_type: BlockType) -> Self {
-        match block_type {
-            BlockType::Dirt | BlockType::Grass => Self::Dirt,
-            BlockType::Stone => Self::Stone,
-            BlockType::Tree(_) => Self::Wood,
-            BlockType::Wood => Self::Wood,
-        }
-    }
-}
\ No newline at end of file
@@ -0,0 +1,167 @@
+use std::ops::Range;
+
+use super::{ItemStack, Item, Stack};
+
+/// Number of slots in a chest
+pub(crate) const CHEST_SLOT_COUNT: usize = 40;
+
+/// Storage made of item slots, like the player inventory or a chest
+pub(crate) trait SlotContainer {
+    fn slots(&self) -> &[Option<ItemStack>];
+
+    fn slots_mut(&mut self) -> &mut [Option<ItemStack>];
+
+    #[inline]
+    fn slot_count(&self) -> usize {
+        self.slots().len()
+    }
+
+    #[inline]
+    fn get_slot(&self, index: usize) -> Option<ItemStack> {
+        self.slots()[index]
+    }
+
+    #[inline]
+    fn set_slot(&mut self, index: usize, item_stack: Option<ItemStack>) {
+        self.slots_mut()[index] = item_stack;
+    }
+
+    #[inline]
+    fn take_slot(&mut self, index: usize) -> Option<ItemStack> {
+        self.slots_mut()[index].take()
+    }
+
+    /// Takes up to `stack` items out of the slot
+    fn split_slot(&mut self, index: usize, stack: Stack) -> Option<ItemStack> {
+        let slot = &mut self.slots_mut()[index];
+        let item_stack = (*slot)?;
+
+        if item_stack.stack > stack {
+            *slot = Some(item_stack.with_stack(item_stack.stack - stack));
+            Some(item_stack.with_stack(stack))
+        } else {
+            slot.take()
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.slots().iter().all(Option::is_none)
+    }
+
+    fn contains(&self, item: Item) -> bool {
+        self.slots().iter().flatten().any(|item_stack| item_stack.item == item)
+    }
+
+    /// Returns the total amount of the item in all slots
+    fn count_item(&self, item: Item) -> u32 {
+        self.slots()
+            .iter()
+            .flatten()
+            .filter(|item_stack| item_stack.item == item)
+            .map(|item_stack| item_stack.stack as u32)
+            .sum()
+    }
+
+    /// Returns the amount of the item that still fits into the container
+    fn free_space(&self, item: Item) -> u32 {
+        self.slots()
+            .iter()
+            .map(|slot| match slot {
+                None => item.max_stack() as u32,
+                Some(item_stack) if item_stack.item == item => {
+                    item_stack.item.max_stack().saturating_sub(item_stack.stack) as u32
+                },
+                Some(_) => 0
+            })
+            .sum()
+    }
+
+    /// Fills the stacks of the same item first and then the empty slots.
+    /// Returns the amount of items added to the container.
+    #[inline]
+    fn add_item_stack(&mut self, new_item: ItemStack) -> Stack {
+        let slots = 0..self.slot_count();
+        self.add_item_stack_in(new_item, slots)
+    }
+
+    /// Same as [`SlotContainer::add_item_stack`], but only the `slots` are filled
+    fn add_item_stack_in(&mut self, new_item: ItemStack, slots: Range<usize>) -> Stack {
+        let max_stack = new_item.item.max_stack();
+        let mut remaining = new_item.stack;
+
+        for item_stack in self.slots_mut()[slots.clone()].iter_mut().flatten() {
+            if remaining == 0 { break; }
+            if item_stack.item != new_item.item { continue; }
+
+            let added = max_stack.saturating_sub(item_stack.stack).min(remaining);
+            item_stack.stack += added;
+            remaining -= added;
+        }
+
+        for slot in self.slots_mut()[slots].iter_mut().filter(|slot| slot.is_none()) {
+            if remaining == 0 { break; }
+
+            let added = remaining.min(max_stack);
+            *slot = Some(new_item.with_stack(added));
+            remaining -= added;
+        }
+
+        new_item.stack - remaining
+    }
+
+    /// Removes all items from the slots and returns them
+    fn take_items(&mut self) -> Vec<ItemStack> {
+        self.slots_mut().iter_mut().filter_map(Option::take).collect()
+    }
+}
+
+/// Moves the items in the `slots` of `from` which pass the `filter` into `to`.
+/// The filter gets the index of the slot and its item.
+/// The items which don't fit stay where they are.
+/// Returns `true` if anything was moved.
+pub(crate) fn transfer_items(
+    from: &mut impl SlotContainer,
+    slots: Range<usize>,
+    to: &mut impl SlotContainer,
+    filter: impl Fn(usize, &ItemStack) -> bool
+) -> bool {
+    let mut moved = false;
+
+    for index in slots {
+        let Some(item_stack) = from.get_slot(index) else { continue; };
+        if !filter(index, &item_stack) { continue; }
+
+        let added = to.add_item_stack(item_stack);
+        if added == 0 { continue; }
+
+        let remaining = item_stack.stack - added;
+        from.set_slot(index, (remaining > 0).then(|| item_stack.with_stack(remaining)));
+        moved = true;
+    }
+
+    moved
+}
+
+/// The items stored in a placed chest
+#[derive(Clone, Copy)]
+pub(crate) struct ChestContents {
+    pub(crate) slots: [Option<ItemStack>; CHEST_SLOT_COUNT],
+}
+
+impl Default for ChestContents {
+    fn default() -> Self {
+        Self { slots: [None; CHEST_SLOT_COUNT] }
+    }
+}
+
+impl SlotContainer for ChestContents {
+    #[inline]
+    fn slots(&self) -> &[Option<ItemStack>] {
+        &self.slots
+    }
+
+    #[inline]
+    fn slots_mut(&mut self) -> &mut [Option<ItemStack>] {
+        &mut self.slots
+    }
+}
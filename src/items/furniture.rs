@@ -0,0 +1,10 @@
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub(crate) enum ItemFurniture {
+    Torch,
+    Workbench,
+    Chest,
+    Door,
+    Lantern,
+}
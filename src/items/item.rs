@@ -1,59 +1,90 @@
-use super::{ItemTool, ItemSeed, ItemBlock, ItemWall};
+use serde::{Deserialize, Serialize, Serializer, Deserializer, de};
+
+use super::{ItemBehaviour, ItemDefinition, WeaponType, registry::item_definitions};
 
 pub(crate) type Stack = u16;
 
-#[derive(Clone, Copy, PartialEq)]
-pub(crate) enum Item {
-    Tool(ItemTool),
-    Block(ItemBlock),
-    Seed(ItemSeed),
-    Wall(ItemWall)
-}
+/// An id of an item in the item registry
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) struct Item(u16);
 
 impl Item {
-    pub(crate) const fn consumable(&self) -> bool {
-        match self {
-            Item::Tool(_) => false,
-            _ => true
-        }
+    pub(crate) const MAX_COUNT: usize = u16::MAX as usize;
+
+    /// Returns all items in the order they are defined
+    pub(crate) fn all() -> impl Iterator<Item = Item> {
+        (0..item_definitions().len()).map(|index| Item(index as u16))
     }
 
-    pub(crate) const fn max_stack(&self) -> Stack {
-        match self {
-            Item::Tool(_) => 1,
-            _ => 9999
-        }
+    pub(crate) fn from_id(id: &str) -> Option<Item> {
+        Self::all().find(|item| item.definition().id == id)
+    }
+
+    pub(crate) fn from_terraria_id(id: i32) -> Option<Item> {
+        Self::all().find(|item| item.definition().terraria_id == Some(id))
+    }
+
+    #[inline]
+    pub(crate) fn definition(&self) -> &'static ItemDefinition {
+        &item_definitions()[self.0 as usize]
     }
 
-    pub(crate) const fn swing_cooldown(&self) -> u32 {
-        match self {
-            Item::Tool(tool) => tool.swing_cooldown(),
-            Item::Block(_) | Item::Seed(_) | Item::Wall(_) => 15,
+    #[inline(always)]
+    pub(crate) const fn index(&self) -> usize {
+        self.0 as usize
+    }
+
+    #[inline(always)]
+    pub(crate) fn behaviour(&self) -> ItemBehaviour {
+        self.definition().behaviour
+    }
+
+    /// Whether the player swings the item when using it
+    pub(crate) fn usable(&self) -> bool {
+        self.behaviour() != ItemBehaviour::Material
+    }
+
+    pub(crate) fn consumable(&self) -> bool {
+        match self.behaviour() {
+            ItemBehaviour::Tool(_) => false,
+            ItemBehaviour::Weapon(weapon) => weapon.weapon_type == WeaponType::Throwing,
+            _ => true,
         }
     }
-}
 
-#[derive(Clone, Copy)]
-pub(crate) struct ItemStack {
-    pub item: Item,
-    pub stack: Stack
-}
+    #[inline(always)]
+    pub(crate) fn max_stack(&self) -> Stack {
+        self.definition().max_stack
+    }
 
-impl ItemStack {
-    pub(crate) const fn new_block(block: ItemBlock) -> Self {
-        ItemStack { item: Item::Block(block), stack: 1 }
+    #[inline(always)]
+    pub(crate) fn swing_cooldown(&self) -> u32 {
+        self.definition().swing_cooldown
     }
+}
 
-    pub(crate) const fn new_tool(tool: ItemTool) -> Self {
-        ItemStack { item: Item::Tool(tool), stack: 1 }
+impl Serialize for Item {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.definition().id)
     }
+}
 
-    pub(crate) const fn new_seed(seed: ItemSeed) -> Self {
-        ItemStack { item: Item::Seed(seed), stack: 1 }
+impl<'de> Deserialize<'de> for Item {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let id = String::deserialize(deserializer)?;
+        Item::from_id(&id).ok_or_else(|| de::Error::custom(format!("unknown item \"{}\"", id)))
     }
+}
 
-    pub(crate) const fn new_wall(wall: ItemWall) -> Self {
-        ItemStack { item: Item::Wall(wall), stack: 1 }
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct ItemStack {
+    pub item: Item,
+    pub stack: Stack
+}
+
+impl ItemStack {
+    pub(crate) const fn new(item: Item) -> Self {
+        ItemStack { item, stack: 1 }
     }
 
     pub(crate) fn with_stack(mut self, stack: Stack) -> Self {
@@ -66,4 +97,4 @@ impl ItemStack {
     pub(crate) fn with_max_stack(self) -> Self {
         self.with_stack(self.item.max_stack())
     }
-}
\ No newline at end of file
+}
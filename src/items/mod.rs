@@ -1,11 +1,24 @@
 mod item;
+mod registry;
 mod tool;
+mod weapon;
+mod projectile;
 mod seed;
 mod block;
 mod wall;
+mod furniture;
+mod recipe;
+mod container;
 
 pub(crate) use item::*;
+pub(crate) use registry::*;
+pub use registry::load_items;
 pub(crate) use tool::*;
+pub(crate) use weapon::*;
+pub(crate) use projectile::*;
 pub(crate) use seed::*;
 pub(crate) use block::*;
-pub(crate) use wall::*;
\ No newline at end of file
+pub(crate) use wall::*;
+pub(crate) use furniture::*;
+pub(crate) use recipe::*;
+pub(crate) use container::*;
\ No newline at end of file
@@ -0,0 +1,67 @@
+use serde::Deserialize;
+
+use crate::world::registry::LightEmission;
+
+/// What a projectile does when it flies into a solid tile
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize)]
+pub(crate) enum TileHit {
+    #[default]
+    Destroy,
+    /// The projectile stays in the tile until its lifetime ends
+    Stick,
+    /// The projectile bounces off the tile keeping this part of its speed, from 0 to 1
+    Bounce(f32),
+}
+
+/// The flight of a projectile as it's described in the data files
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub(crate) struct ProjectileDefinition {
+    /// The speed the projectile is launched with
+    pub(crate) speed: f32,
+    #[serde(default)]
+    pub(crate) gravity: f32,
+    /// The part of the velocity the projectile loses every tick, from 0 to 1
+    #[serde(default)]
+    pub(crate) drag: f32,
+    #[serde(default)]
+    pub(crate) tile_hit: TileHit,
+    /// The number of entities the projectile hits before it's destroyed
+    #[serde(default = "default_piercing")]
+    pub(crate) piercing: u32,
+    /// The time in seconds after which the projectile disappears
+    pub(crate) lifetime: f32,
+    /// The angle in radians the projectile turns by every tick. When it's zero the projectile faces the direction it flies in.
+    #[serde(default)]
+    pub(crate) spin: f32,
+    pub(crate) light: Option<LightEmission>,
+}
+
+const fn default_piercing() -> u32 { 1 }
+
+impl ProjectileDefinition {
+    pub(crate) fn validate(&self) -> Result<(), String> {
+        if self.speed <= 0. {
+            return Err("the projectile speed must be greater than zero".to_owned());
+        }
+
+        if !(0. ..1.).contains(&self.drag) {
+            return Err("the projectile drag must be between 0 and 1".to_owned());
+        }
+
+        if self.piercing == 0 {
+            return Err("the projectile piercing must be greater than zero".to_owned());
+        }
+
+        if self.lifetime <= 0. {
+            return Err("the projectile lifetime must be greater than zero".to_owned());
+        }
+
+        if let TileHit::Bounce(restitution) = self.tile_hit {
+            if !(0. ..=1.).contains(&restitution) {
+                return Err("the projectile bounce must be between 0 and 1".to_owned());
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,85 @@
+use std::{io::BufReader, fs::File, error::Error};
+
+use bevy::{prelude::{Resource, Deref}, utils::HashSet};
+use serde::Deserialize;
+
+use crate::world::furniture::FurnitureType;
+
+use super::ItemStack;
+
+/// An object which has to be near the player to craft some of the recipes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+pub(crate) enum CraftingStation {
+    Workbench,
+}
+
+impl CraftingStation {
+    pub(crate) const fn from_furniture(furniture_type: FurnitureType) -> Option<CraftingStation> {
+        match furniture_type {
+            FurnitureType::Workbench => Some(CraftingStation::Workbench),
+            _ => None
+        }
+    }
+}
+
+#[derive(Clone, Deserialize)]
+pub(crate) struct Recipe {
+    pub(crate) output: ItemStack,
+    pub(crate) ingredients: Vec<ItemStack>,
+    #[serde(default)]
+    pub(crate) station: Option<CraftingStation>,
+}
+
+impl Recipe {
+    /// Whether the station the recipe requires, if any, is one of the given stations
+    pub(crate) fn station_is_available(&self, stations: &HashSet<CraftingStation>) -> bool {
+        match self.station {
+            Some(station) => stations.contains(&station),
+            None => true
+        }
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        let stack_is_valid = |item_stack: &ItemStack| {
+            item_stack.stack > 0 && item_stack.stack <= item_stack.item.max_stack()
+        };
+
+        if !stack_is_valid(&self.output) {
+            return Err("the output has an invalid stack".to_owned());
+        }
+
+        if self.ingredients.is_empty() {
+            return Err("the recipe has no ingredients".to_owned());
+        }
+
+        for (i, ingredient) in self.ingredients.iter().enumerate() {
+            if !stack_is_valid(ingredient) {
+                return Err(format!("the ingredient #{} has an invalid stack", i));
+            }
+
+            // The amount of an item is checked against the whole inventory, so it must be listed once
+            if self.ingredients[..i].iter().any(|other| other.item == ingredient.item) {
+                return Err(format!("the ingredient #{} is listed more than once", i));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// All recipes of the game in the order they are shown in the crafting panel
+#[derive(Resource, Deref)]
+pub(crate) struct Recipes(Vec<Recipe>);
+
+pub(crate) fn load_recipes() -> Result<Recipes, Box<dyn Error>> {
+    let reader = BufReader::new(
+        File::open("./assets/data/recipes.json")?
+    );
+    let recipes: Vec<Recipe> = serde_json::from_reader(reader)?;
+
+    for (i, recipe) in recipes.iter().enumerate() {
+        recipe.validate().map_err(|err| format!("Recipe #{} is invalid: {}", i, err))?;
+    }
+
+    Ok(Recipes(recipes))
+}
@@ -0,0 +1,145 @@
+use std::{io::BufReader, fs::File, error::Error, path::Path, sync::OnceLock};
+
+use serde::Deserialize;
+
+use super::{Item, ItemTool, ItemWeapon, WeaponType, ItemBlock, ItemWall, ItemSeed, ItemFurniture, Stack};
+
+static ITEM_REGISTRY: OnceLock<Vec<ItemDefinition>> = OnceLock::new();
+
+/// What happens when the item is used
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub(crate) enum ItemBehaviour {
+    Tool(ItemTool),
+    Weapon(ItemWeapon),
+    Block(ItemBlock),
+    Wall(ItemWall),
+    Seed(ItemSeed),
+    Furniture(ItemFurniture),
+    /// The item can't be used, it's only crafted into other items
+    Material,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct ItemDefinition {
+    /// The unique name of the item which is used to refer to it in the data files and the saves
+    pub(crate) id: String,
+    /// The key of the item name in the language files
+    pub(crate) name: String,
+    /// Path to the sprite of the item inside of the assets directory
+    pub(crate) sprite: String,
+    pub(crate) max_stack: Stack,
+    /// Number of ticks the swing animation takes
+    pub(crate) swing_cooldown: u32,
+    pub(crate) behaviour: ItemBehaviour,
+    /// The net id of the item in Terraria, used to fill the chests of imported Terraria worlds
+    #[serde(default)]
+    pub(crate) terraria_id: Option<i32>,
+}
+
+impl ItemDefinition {
+    fn validate(&self) -> Result<(), String> {
+        if self.id.is_empty() {
+            return Err("the id is empty".to_owned());
+        }
+
+        // The world file stores the length of the id of a stored item in a byte
+        if self.id.len() > u8::MAX as usize {
+            return Err(format!("the id is longer than {} bytes", u8::MAX));
+        }
+
+        if self.max_stack == 0 {
+            return Err("the max stack must be greater than zero".to_owned());
+        }
+
+        if self.swing_cooldown == 0 {
+            return Err("the swing cooldown must be greater than zero".to_owned());
+        }
+
+        if !Path::new("./assets").join(&self.sprite).is_file() {
+            return Err(format!("the sprite \"{}\" doesn't exist", self.sprite));
+        }
+
+        if let ItemBehaviour::Tool(tool) = self.behaviour {
+            if tool.power() <= 0 {
+                return Err("the tool power must be greater than zero".to_owned());
+            }
+        }
+
+        if let ItemBehaviour::Weapon(weapon) = self.behaviour {
+            if weapon.damage() <= 0 {
+                return Err("the weapon damage must be greater than zero".to_owned());
+            }
+
+            if weapon.knockback() < 0. {
+                return Err("the weapon knockback must not be negative".to_owned());
+            }
+
+            if !(0. ..=1.).contains(&weapon.crit_chance()) {
+                return Err("the weapon crit chance must be between 0 and 1".to_owned());
+            }
+
+            match weapon.projectile() {
+                Some(projectile) => projectile.validate()?,
+                None if weapon.weapon_type == WeaponType::Throwing => {
+                    return Err("a throwing weapon must have a projectile".to_owned());
+                },
+                None => {}
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns all item definitions, the index of a definition is the id of its [`Item`]
+#[inline]
+pub(super) fn item_definitions() -> &'static [ItemDefinition] {
+    ITEM_REGISTRY.get().expect("The items must be loaded before they are used")
+}
+
+/// Loads the item definitions into the registry. Must be called once before any [`Item`] is used.
+///
+/// The file is read directly instead of through the asset server like the sprites, because the items are needed
+/// before the app is built: the language, the starting items and the tile drops are validated against them,
+/// and the worldgen tool reads the chests of world files without an app.
+pub fn load_items() -> Result<(), Box<dyn Error>> {
+    let reader = BufReader::new(
+        File::open("./assets/data/items.json")?
+    );
+    let definitions: Vec<ItemDefinition> = serde_json::from_reader(reader)?;
+
+    validate_items(&definitions)?;
+
+    ITEM_REGISTRY.set(definitions).map_err(|_| "The items are already loaded")?;
+
+    Ok(())
+}
+
+fn validate_items(definitions: &[ItemDefinition]) -> Result<(), String> {
+    if definitions.len() > Item::MAX_COUNT {
+        return Err(format!("There are more than {} items", Item::MAX_COUNT));
+    }
+
+    for (i, definition) in definitions.iter().enumerate() {
+        definition.validate().map_err(|err| format!("Item \"{}\" is invalid: {}", definition.id, err))?;
+
+        if definitions[..i].iter().any(|other| other.id == definition.id) {
+            return Err(format!("Item \"{}\" is defined more than once", definition.id));
+        }
+
+        // Breaking a placed tile must give back the item which placed it
+        let is_placeable = matches!(
+            definition.behaviour,
+            ItemBehaviour::Block(_) | ItemBehaviour::Wall(_) | ItemBehaviour::Seed(_) | ItemBehaviour::Furniture(_)
+        );
+        if is_placeable && definitions[..i].iter().any(|other| other.behaviour == definition.behaviour) {
+            return Err(format!("Item \"{}\" places the same tile as another item", definition.id));
+        }
+
+        if definition.terraria_id.is_some() && definitions[..i].iter().any(|other| other.terraria_id == definition.terraria_id) {
+            return Err(format!("Item \"{}\" has the same Terraria id as another item", definition.id));
+        }
+    }
+
+    Ok(())
+}
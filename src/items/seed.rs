@@ -1,6 +1,8 @@
+use serde::Deserialize;
+
 use crate::world::block::BlockType;
 
-#[derive(Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
 pub(crate) enum ItemSeed {
     Grass
 }
@@ -1,107 +1,30 @@
-#[derive(Clone, Copy, PartialEq, Eq, Hash)]
-pub(crate) enum ItemTool {
-    Pickaxe(Pickaxe),
-    Axe(Axe),
-    Hammer(Hammer)
-}
-
-impl ItemTool {
-    pub(crate) const fn power(&self) -> i32 {
-        match self {
-            Self::Pickaxe(pickaxe) => pickaxe.power(),
-            Self::Axe(axe) => axe.power(),
-            Self::Hammer(hammer) => hammer.power()
-        }
-    }
+use serde::Deserialize;
 
-    pub(crate) const fn use_cooldown(&self) -> u32 {
-        match self {
-            Self::Pickaxe(pickaxe) => pickaxe.use_cooldown(),
-            Self::Axe(axe) => axe.use_cooldown(),
-            Self::Hammer(hammer) => hammer.use_cooldown()
-        }
-    }
-
-    pub(crate) const fn swing_cooldown(&self) -> u32 {
-        match self {
-            Self::Pickaxe(pickaxe) => pickaxe.swing_cooldown(),
-            Self::Axe(axe) => axe.swing_cooldown(),
-            Self::Hammer(hammer) => hammer.swing_cooldown()
-        }
-    }
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+pub(crate) enum ToolType {
+    Pickaxe,
+    Axe,
+    Hammer
 }
 
-#[derive(Clone, Copy, PartialEq, Eq, Hash)]
-pub(crate) enum Axe {
-    CopperAxe
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+pub(crate) struct ItemTool {
+    pub(crate) tool_type: ToolType,
+    /// Damage dealt to a tile on each hit. Blocks can't be damaged by a tool with less power than they require.
+    power: i32,
+    /// Number of ticks `use_item` waits between two hits of the tool.
+    /// A tile with `hp` health takes `ceil(hp / power)` hits, so `ceil(hp / power) * use_cooldown` ticks to break.
+    use_cooldown: u32,
 }
 
-impl Axe {
-    pub(crate) const fn power(&self) -> i32 {
-        match self {
-            Self::CopperAxe => 35,
-        }
-    }
-
-    pub(crate) const fn use_cooldown(&self) -> u32 {
-        match self {
-            Self::CopperAxe => 21,
-        }
-    }
-
-    pub(crate) const fn swing_cooldown(&self) -> u32 {
-        match self {
-            Self::CopperAxe => 30,
-        }
-    }
-}
-
-#[derive(Clone, Copy, PartialEq, Eq, Hash)]
-pub(crate) enum Pickaxe {
-    CopperPickaxe
-}
-
-impl Pickaxe {
+impl ItemTool {
+    #[inline(always)]
     pub(crate) const fn power(&self) -> i32 {
-        match self {
-            Self::CopperPickaxe => 35,
-        }
+        self.power
     }
 
+    #[inline(always)]
     pub(crate) const fn use_cooldown(&self) -> u32 {
-        match self {
-            Self::CopperPickaxe => 15,
-        }
+        self.use_cooldown
     }
-
-    pub(crate) const fn swing_cooldown(&self) -> u32 {
-        match self {
-            Self::CopperPickaxe => 23,
-        }
-    }
-}
-
-#[derive(Clone, Copy, PartialEq, Eq, Hash)]
-pub(crate) enum Hammer {
-    CopperHammer
 }
-
-impl Hammer {
-    pub(crate) const fn power(&self) -> i32 {
-        match self {
-            Self::CopperHammer => 35,
-        }
-    }
-
-    pub(crate) const fn use_cooldown(&self) -> u32 {
-        match self {
-            Self::CopperHammer => 23,
-        }
-    }
-
-    pub(crate) const fn swing_cooldown(&self) -> u32 {
-        match self {
-            Self::CopperHammer => 33,
-        }
-    }
-}
\ No newline at end of file
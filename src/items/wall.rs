@@ -1,16 +1,7 @@
-use crate::world::wall::WallType;
+use serde::Deserialize;
 
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
 pub(crate) enum ItemWall {
     Dirt,
     Stone,
 }
-
-impl From<WallType> for ItemWall {
-    fn from(wall_type: WallType) -> Self {
-        match wall_type {
-            WallType::Dirt => Self::Dirt,
-            WallType::Stone => Self::Stone,
-        }
-    }
-}
\ No newline at end of file
@@ -0,0 +1,47 @@
+use serde::Deserialize;
+
+use super::ProjectileDefinition;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+pub(crate) enum WeaponType {
+    Sword,
+    /// The item is thrown as a projectile, each throw uses up one item of the stack
+    Throwing,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub(crate) struct ItemWeapon {
+    pub(crate) weapon_type: WeaponType,
+    /// Damage dealt to an entity on each hit
+    damage: i32,
+    /// The speed an entity is pushed away from the player with when it's hit
+    knockback: f32,
+    /// The probability of a hit to deal double damage, from 0 to 1
+    #[serde(default)]
+    crit_chance: f32,
+    /// The projectile which the weapon launches, it's required by the throwing weapons
+    #[serde(default)]
+    projectile: Option<ProjectileDefinition>,
+}
+
+impl ItemWeapon {
+    #[inline(always)]
+    pub(crate) const fn damage(&self) -> i32 {
+        self.damage
+    }
+
+    #[inline(always)]
+    pub(crate) const fn knockback(&self) -> f32 {
+        self.knockback
+    }
+
+    #[inline(always)]
+    pub(crate) const fn crit_chance(&self) -> f32 {
+        self.crit_chance
+    }
+
+    #[inline(always)]
+    pub(crate) const fn projectile(&self) -> Option<ProjectileDefinition> {
+        self.projectile
+    }
+}
@@ -1,4 +1,4 @@
-use crate::items::{Item, ItemTool, ItemSeed, Axe, Pickaxe, ItemBlock, Hammer, ItemWall};
+use crate::items::Item;
 
 #[derive(Clone, Copy, PartialEq, Eq)]
 pub(crate) enum LanguageStringKey {
@@ -6,46 +6,13 @@ pub(crate) enum LanguageStringKey {
     Items(ItemStringKey)
 }
 
+/// The key of an item name in the language files, it's set in the item definition
 #[derive(Clone, Copy, PartialEq, Eq)]
-pub(crate) enum ItemStringKey {
-    CopperPickaxe,
-    CopperAxe,
-    CopperHammer,
-    DirtBlock,
-    StoneBlock,
-    DirtWall,
-    StoneWall,
-    GrassSeeds,
-    Wood
-}
+pub(crate) struct ItemStringKey(pub(super) &'static str);
 
 impl ItemStringKey {
-    pub(crate) const fn get_by_item(item: Item) -> Self {
-        match item {
-            Item::Tool(tool) => match tool {
-                ItemTool::Pickaxe(pickaxe) => match pickaxe {
-                    Pickaxe::CopperPickaxe => ItemStringKey::CopperPickaxe,
-                },
-                ItemTool::Axe(axe) => match axe {
-                    Axe::CopperAxe => ItemStringKey::CopperAxe,
-                },
-                ItemTool::Hammer(hammer) => match hammer {
-                    Hammer::CopperHammer => ItemStringKey::CopperHammer,
-                }
-            },
-            Item::Block(block) => match block {
-                ItemBlock::Dirt => ItemStringKey::DirtBlock,
-                ItemBlock::Stone => ItemStringKey::StoneBlock,
-                ItemBlock::Wood => ItemStringKey::Wood,
-            },
-            Item::Wall(wall) => match wall {
-                ItemWall::Dirt => ItemStringKey::DirtWall,
-                ItemWall::Stone => ItemStringKey::StoneWall,
-            }
-            Item::Seed(seed) => match seed {
-                ItemSeed::Grass => ItemStringKey::GrassSeeds,
-            },
-        }
+    pub(crate) fn get_by_item(item: Item) -> Self {
+        ItemStringKey(&item.definition().name)
     }
 }
 
@@ -53,6 +20,7 @@ impl ItemStringKey {
 pub(crate) enum UIStringKey {
     Items,
     Inventory,
+    Crafting,
     General,
     Settings,
     SettingsMenu,
@@ -82,7 +50,14 @@ pub(crate) enum UIStringKey {
     Medium,
     High,
     Ultra,
-    SpawnPoint
+    SpawnPoint,
+    Life,
+    Chest,
+    LootAll,
+    DepositAll,
+    QuickStack,
+    SortInventory,
+    Trash
 }
 
 impl From<UIStringKey> for LanguageStringKey {
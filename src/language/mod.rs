@@ -1,12 +1,14 @@
 pub(crate) mod keys;
 pub(crate) mod plugin;
 
-use std::{io::BufReader, fs::File, error::Error, sync::Arc};
+use std::{io::BufReader, fs::File, error::Error, sync::Arc, collections::HashMap};
 
 use bevy::prelude::{Resource, Component};
 use dyn_fmt::AsStrFormatExt;
 use serde::Deserialize;
 
+use crate::items::Item;
+
 use self::keys::{LanguageStringKey, UIStringKey, ItemStringKey};
 
 pub(crate) enum Language {
@@ -33,6 +35,7 @@ impl Language {
 struct UI {
     items: String,
     inventory: String,
+    crafting: String,
     general: String,
     settings: String,
     settings_menu: String,
@@ -66,20 +69,13 @@ struct UI {
     high: String,
     ultra: String,
     spawn_point: String,
-}
-
-#[derive(Deserialize)]
-#[serde(rename_all = "PascalCase")]
-struct Items {
-    copper_pickaxe: String,
-    copper_axe: String,
-    copper_hammer: String,
-    dirt_block: String,
-    stone_block: String,
-    dirt_wall: String,
-    stone_wall: String,
-    grass_seed: String,
-    wood: String
+    life: String,
+    chest: String,
+    loot_all: String,
+    deposit_all: String,
+    quick_stack: String,
+    sort_inventory: String,
+    trash: String,
 }
 
 #[derive(Deserialize, Resource)]
@@ -89,7 +85,8 @@ pub(super) struct LanguageContent {
     pub(super) titles: Vec<String>,
     #[serde(rename = "UI")]
     ui: UI,
-    items: Items
+    /// Item names by the keys from the item definitions
+    items: HashMap<String, String>
 }
 
 impl LanguageContent {
@@ -98,6 +95,7 @@ impl LanguageContent {
             LanguageStringKey::UI(ui_key) => match ui_key {
                 keys::UIStringKey::Items => &self.ui.items,
                 keys::UIStringKey::Inventory => &self.ui.inventory,
+                keys::UIStringKey::Crafting => &self.ui.crafting,
                 keys::UIStringKey::General => &self.ui.general,
                 keys::UIStringKey::Settings => &self.ui.settings,
                 keys::UIStringKey::SettingsMenu => &self.ui.settings_menu,
@@ -127,21 +125,33 @@ impl LanguageContent {
                 keys::UIStringKey::Medium => &self.ui.medium,
                 keys::UIStringKey::High => &self.ui.high,
                 keys::UIStringKey::Ultra => &self.ui.ultra,
-                keys::UIStringKey::SpawnPoint => &self.ui.spawn_point
+                keys::UIStringKey::SpawnPoint => &self.ui.spawn_point,
+                keys::UIStringKey::Life => &self.ui.life,
+                keys::UIStringKey::Chest => &self.ui.chest,
+                keys::UIStringKey::LootAll => &self.ui.loot_all,
+                keys::UIStringKey::DepositAll => &self.ui.deposit_all,
+                keys::UIStringKey::QuickStack => &self.ui.quick_stack,
+                keys::UIStringKey::SortInventory => &self.ui.sort_inventory,
+                keys::UIStringKey::Trash => &self.ui.trash,
             },
-            LanguageStringKey::Items(item_key) => match item_key {
-                keys::ItemStringKey::CopperPickaxe => &self.items.copper_pickaxe,
-                keys::ItemStringKey::CopperAxe => &self.items.copper_axe,
-                keys::ItemStringKey::CopperHammer => &self.items.copper_hammer,
-                keys::ItemStringKey::DirtBlock => &self.items.dirt_block,
-                keys::ItemStringKey::StoneBlock => &self.items.stone_block,
-                keys::ItemStringKey::DirtWall => &self.items.dirt_wall,
-                keys::ItemStringKey::StoneWall => &self.items.stone_wall,
-                keys::ItemStringKey::GrassSeeds => &self.items.grass_seed,
-                keys::ItemStringKey::Wood => &self.items.wood
+            LanguageStringKey::Items(item_key) => {
+                self.items.get(item_key.0).map(String::as_str).unwrap_or(item_key.0)
             },
         }
     }
+
+    /// Checks that every item has a name
+    pub(crate) fn validate_item_names(&self) -> Result<(), String> {
+        for item in Item::all() {
+            let name = &item.definition().name;
+
+            if !self.items.contains_key(name) {
+                return Err(format!("There is no name \"{}\" for the item \"{}\"", name, item.definition().id));
+            }
+        }
+
+        Ok(())
+    }
 }
 
 pub(crate) fn load_language(language: Language) -> Result<LanguageContent, Box<dyn Error>> {
@@ -178,6 +188,13 @@ impl Localize for f32 {
     }
 }
 
+impl Localize for i32 {
+    #[inline]
+    fn localize(&self, _: &LanguageContent) -> Box<str> {
+        Box::from(self.to_string())
+    }
+}
+
 impl Localize for u16 {
     #[inline]
     fn localize(&self, _: &LanguageContent) -> Box<str> {
@@ -12,30 +12,48 @@ use bevy::{
     asset::ChangeWatcher
 };
 
+use items::load_recipes;
 use language::{load_language, Language};
+use npcs::load_npcs;
 use plugins::{
-    config::{FullScreen, Resolution, ConfigPlugin, VSync}, main::MainPlugin,
+    config::{FullScreen, Resolution, ConfigPlugin, VSync}, main::MainPlugin, inventory::validate_starting_items,
 };
 use rand::seq::SliceRandom;
+use world::registry::{load_tiles, validate_tile_drops};
 
 pub mod world;
 
+pub use items::load_items;
+
 pub(crate) mod animation;
 pub(crate) mod common;
 pub(crate) mod items;
 pub(crate) mod language;
 pub(crate) mod lighting;
+pub(crate) mod npcs;
 pub(crate) mod parallax;
 pub(crate) mod plugins;
 
+#[cfg(test)]
+pub(crate) mod test_utils;
+
 pub(crate) const BACKGROUND_LAYER: f32 = 0.;
 pub(crate) const WALL_LAYER: f32 = 1.;
 pub(crate) const TILES_LAYER: f32 = 2.;
+pub(crate) const NPC_LAYER: f32 = 2.8;
+pub(crate) const PROJECTILE_LAYER: f32 = 2.9;
 pub(crate) const PLAYER_LAYER: f32 = 3.;
 
 pub fn create_app() -> Result<App, Box<dyn Error>> {
     let language_content = load_language(Language::English)?;
     let title = language_content.titles.choose(&mut rand::thread_rng()).unwrap();
+    load_tiles()?;
+    load_items()?;
+    validate_starting_items()?;
+    language_content.validate_item_names()?;
+    validate_tile_drops()?;
+    load_npcs()?;
+    let recipes = load_recipes()?;
 
     let mut app = App::new();
 
@@ -72,6 +90,7 @@ pub fn create_app() -> Result<App, Box<dyn Error>> {
             .set(ImagePlugin::default_nearest())
         )
         .insert_resource(language_content)
+        .insert_resource(recipes)
         .insert_resource(ClearColor(Color::BLACK))
         .insert_resource(FixedTime::new_from_secs(1. / 60.))
         .add_plugins(MainPlugin);
@@ -1,7 +1,11 @@
-use bevy::{prelude::{Image, Res, ResMut, Assets, GlobalTransform, OrthographicProjection, With, Query, Deref, UVec2, EventReader, Commands, Transform, Resource, ComputedVisibility, Color}, render::{render_resource::{Extent3d, TextureDimension, TextureUsages, UniformBuffer, StorageBuffer, FilterMode, SamplerDescriptor}, renderer::{RenderQueue, RenderDevice}, Extract, extract_resource::ExtractResource, texture::ImageSampler}, utils::default, math::{URect, Vec3Swizzles}};
+use bevy::{prelude::{Image, Res, ResMut, Assets, GlobalTransform, OrthographicProjection, With, Query, Deref, UVec2, EventReader, Commands, Resource, ComputedVisibility, Color}, render::{render_resource::{Extent3d, TextureDimension, TextureUsages, UniformBuffer, StorageBuffer, FilterMode, SamplerDescriptor}, renderer::{RenderQueue, RenderDevice}, Extract, extract_resource::ExtractResource, texture::ImageSampler}, utils::default, math::{URect, Vec3Swizzles}};
+use bevy_ecs_tilemap::tiles::TilePos;
 use rand::{thread_rng, Rng};
 
-use crate::{world::WorldData, plugins::{camera::components::WorldCamera, world::{constants::TILE_SIZE, WorldSize, events::{PlaceTileEvent, BreakTileEvent}, TileType, time::GameTime}, config::LightSmoothness}, lighting::{LightMapTexture, LIGHTMAP_FORMAT, gpu_types::{GpuLightSourceBuffer, GpuLightSource}, TILES_FORMAT, TileTexture, types::LightSource}};
+use crate::{world::{WorldData, block::BlockType, wall::WallType, liquid::LiquidType}, plugins::{camera::components::WorldCamera, world::{constants::TILE_SIZE, WorldSize, events::{PlaceTileEvent, TileRemovedEvent, LiquidChangedEvent}, TileType, time::GameTime}, config::LightSmoothness}, lighting::{LightMapTexture, LIGHTMAP_FORMAT, gpu_types::{GpuLightSourceBuffer, GpuLightSource}, TILES_FORMAT, TileTexture, types::LightSource}};
+
+/// Size of a texel of the tiles texture in bytes
+const TILE_TEXEL_SIZE: usize = 4;
 
 #[derive(Resource, ExtractResource, Deref, Clone, Copy, Default)]
 pub(crate) struct BlurArea(pub(crate) URect);
@@ -31,21 +35,16 @@ pub(crate) fn init_tiles_texture(
     world_data: Res<WorldData>,
     mut images: ResMut<Assets<Image>>,
 ) {
-    let mut bytes = vec![1u8; world_data.width() * world_data.height()];
+    let mut bytes = vec![0u8; world_data.width() * world_data.height() * TILE_TEXEL_SIZE];
 
     for y in 0..world_data.height() {
         for x in 0..world_data.width() {
-            let block_exists = world_data.solid_block_exists((x, y));
-            let wall_exists = world_data.wall_exists((x, y));
-            let index = (y * world_data.width()) + x;
-
-            if block_exists {
-                bytes[index] = 1;
-            } else if wall_exists || y >= world_data.layer.underground {
-                bytes[index] = 2;
-            } else {
-                bytes[index] = 0;
-            }
+            let block = world_data.get_block((x, y)).map(|block| block.block_type);
+            let wall = world_data.get_wall((x, y)).map(|wall| wall.wall_type);
+            let liquid = world_data.get_liquid((x, y)).map(|liquid| liquid.liquid_type);
+            let index = ((y * world_data.width()) + x) * TILE_TEXEL_SIZE;
+
+            bytes[index..index + TILE_TEXEL_SIZE].copy_from_slice(&tile_texel(&world_data, y, block, wall, liquid));
         }
     }
 
@@ -100,61 +99,86 @@ pub(crate) fn handle_update_tiles_texture_event(
     world_data: Res<WorldData>,
     mut images: ResMut<Assets<Image>>,
     mut place_tile_events: EventReader<PlaceTileEvent>,
-    mut break_tile_events: EventReader<BreakTileEvent>
+    mut tile_removed_events: EventReader<TileRemovedEvent>,
+    mut liquid_changed_events: EventReader<LiquidChangedEvent>,
 ) {
     let image = images.get_mut(&tile_texture.0).unwrap();
 
-    for event in break_tile_events.iter() {
-        let x = event.tile_pos.x as usize;
-        let y = event.tile_pos.y as usize;
-
-        let index = y * world_data.width() + x;
-
-        match event.tile_type {
-            TileType::Block(_) => {
-                let wall_exists = world_data.wall_exists(event.tile_pos);
-
-                if wall_exists || y >= world_data.layer.underground {
-                    image.data[index] = 2;
-                } else {
-                    image.data[index] = 0;
-                }
-            },
-            TileType::Wall(_) => {
-                let block_exists = world_data.block_exists(event.tile_pos);
-
-                if block_exists {
-                    image.data[index] = 1;
-                } else {
-                    image.data[index] = 0;
-                }
-            },
-        }
+    let tile_positions = tile_removed_events.iter().map(|event| event.tile_pos)
+        .chain(liquid_changed_events.iter().map(|event| event.tile_pos));
+
+    for tile_pos in tile_positions {
+        let block = world_data.get_block(tile_pos).map(|block| block.block_type);
+        let wall = world_data.get_wall(tile_pos).map(|wall| wall.wall_type);
+
+        set_tile_texel(&mut image.data, &world_data, tile_pos, block, wall);
     }
 
+    // The placed tile may not be in the world data yet, so it's taken from the event
     for event in place_tile_events.iter() {
-        let x = event.tile_pos.x as usize;
-        let y = event.tile_pos.y as usize;
-
-        let index = y * world_data.width() + x;
-
-        match event.tile_type {
-            TileType::Block(Some(block_type)) => {
-                if !block_type.is_solid() { continue; }
-                image.data[index] = 1;
-            },
-            TileType::Wall(_) => {
-                let block_exists = world_data.block_exists(event.tile_pos);
-
-                if !block_exists {
-                    image.data[index] = 2;
-                }
-            },
-            _ => unreachable!()
-        }
+        let block = world_data.get_block(event.tile_pos).map(|block| block.block_type);
+        let wall = world_data.get_wall(event.tile_pos).map(|wall| wall.wall_type);
+
+        let (block, wall) = match event.tile_type {
+            TileType::Block(Some(block_type)) => (Some(block_type), wall),
+            TileType::Wall(Some(wall_type)) => (block, Some(wall_type)),
+            TileType::Block(None) | TileType::Wall(None) => (block, wall),
+        };
+
+        set_tile_texel(&mut image.data, &world_data, event.tile_pos, block, wall);
     }
 }
 
+fn set_tile_texel(data: &mut [u8], world_data: &WorldData, tile_pos: TilePos, block: Option<BlockType>, wall: Option<WallType>) {
+    let x = tile_pos.x as usize;
+    let y = tile_pos.y as usize;
+
+    let index = (y * world_data.width() + x) * TILE_TEXEL_SIZE;
+    let liquid = world_data.get_liquid(tile_pos).map(|liquid| liquid.liquid_type);
+
+    data[index..index + TILE_TEXEL_SIZE].copy_from_slice(&tile_texel(world_data, y, block, wall, liquid));
+}
+
+/// Builds the texel of the tiles texture for a tile, see [`TILES_FORMAT`]
+fn tile_texel(
+    world_data: &WorldData,
+    y: usize,
+    block: Option<BlockType>,
+    wall: Option<WallType>,
+    liquid: Option<LiquidType>,
+) -> [u8; TILE_TEXEL_SIZE] {
+    let solid = block.is_some_and(|block_type| block_type.is_solid());
+
+    let kind = if solid {
+        1
+    } else if wall.is_some() || y >= world_data.layer.underground {
+        2
+    } else {
+        0
+    };
+
+    let absorption = block.map_or(0., |block_type| block_type.light_absorption());
+
+    // A solid block hides the glow of the liquid and the wall behind it
+    let glow = block
+        .and_then(|block_type| block_type.glow())
+        .or_else(|| if solid { None } else { liquid.and_then(|liquid_type| liquid_type.glow()) })
+        .or_else(|| if solid { None } else { wall.and_then(|wall_type| wall_type.glow()) })
+        .map_or(0, pack_rgb565);
+
+    let [glow_high, glow_low] = glow.to_be_bytes();
+
+    [kind, (absorption * 255.).round() as u8, glow_high, glow_low]
+}
+
+fn pack_rgb565([r, g, b]: [f32; 3]) -> u16 {
+    let r = (r.clamp(0., 1.) * 31.).round() as u16;
+    let g = (g.clamp(0., 1.) * 63.).round() as u16;
+    let b = (b.clamp(0., 1.) * 31.).round() as u16;
+
+    (r << 11) | (g << 5) | b
+}
+
 pub(crate) fn update_blur_area(
     mut blur_area: ResMut<BlurArea>,
     light_smoothness: Res<LightSmoothness>,
@@ -197,7 +221,7 @@ pub(crate) fn extract_lightmap_pipeline_assets(
     mut light_source_count: ResMut<LightSourceCount>,
     mut pipeline_assets: ResMut<LightMapPipelineAssets>,
 
-    query_light_source: Extract<Query<(&Transform, &LightSource, &ComputedVisibility)>>,
+    query_light_source: Extract<Query<(&GlobalTransform, &LightSource, &ComputedVisibility)>>,
 ) {
     pipeline_assets.area_min.set(blur_area.min);
     pipeline_assets.area_max.set(blur_area.max);
@@ -215,7 +239,7 @@ pub(crate) fn extract_lightmap_pipeline_assets(
     for (transform, light_source, visibility) in &query_light_source {
         if !visibility.is_visible() { continue; }
 
-        let uv = transform.translation.xy().abs() / (world_size * TILE_SIZE);
+        let uv = transform.translation().xy().abs() / (world_size * TILE_SIZE);
         let light_pos = (uv * world_size * light_smoothness.subdivision() as f32).as_uvec2();
 
         let intensity = if light_source.jitter_intensity > 0. {
@@ -11,7 +11,7 @@ use crate::common::state::GameState;
 use crate::common::systems::set_resource;
 use crate::plugins::InGameSystemSet;
 use crate::plugins::world::WorldSize;
-use crate::plugins::world::events::{BreakTileEvent, PlaceTileEvent};
+use crate::plugins::world::events::{TileRemovedEvent, PlaceTileEvent, LiquidChangedEvent};
 use crate::plugins::world::resources::WorldUndergroundLevel;
 
 use self::lightmap::LightMapNode;
@@ -51,7 +51,11 @@ struct PostProcessCamera;
 
 const WORKGROUP: u32 = 16;
 const LIGHTMAP_FORMAT: TextureFormat = TextureFormat::Rgba8Unorm;
-const TILES_FORMAT: TextureFormat = TextureFormat::R8Uint;
+/// Each texel of the tiles texture describes a tile for the light map shaders:
+/// - r: 0 if the sky lights the tile, 1 if there is a solid block, 2 otherwise
+/// - g: how much light the tile absorbs, from 0 like air to 255 like a solid block
+/// - b, a: the color the tile glows with, packed as RGB565
+const TILES_FORMAT: TextureFormat = TextureFormat::Rgba8Uint;
 
 pub(crate) struct LightingPlugin;
 impl Plugin for LightingPlugin {
@@ -77,7 +81,11 @@ impl Plugin for LightingPlugin {
                 toggle_do_lighting.run_if(on_event::<WindowResized>()),
                 (
                     lightmap::assets::handle_update_tiles_texture_event
-                        .run_if(on_event::<BreakTileEvent>().or_else(on_event::<PlaceTileEvent>())),
+                        .run_if(
+                            on_event::<TileRemovedEvent>()
+                                .or_else(on_event::<PlaceTileEvent>())
+                                .or_else(on_event::<LiquidChangedEvent>())
+                        ),
                     compositing::update_image_to_window_size,
                 ).in_set(InGameSystemSet::Update)
             )
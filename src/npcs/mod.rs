@@ -0,0 +1,5 @@
+mod npc;
+mod registry;
+
+pub(crate) use npc::*;
+pub(crate) use registry::*;
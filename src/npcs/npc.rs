@@ -0,0 +1,22 @@
+use super::{NpcDefinition, registry::npc_definitions};
+
+/// An id of an NPC in the NPC registry
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) struct NpcType(u16);
+
+impl NpcType {
+    /// Returns all NPC types in the order they are defined
+    pub(crate) fn all() -> impl Iterator<Item = NpcType> {
+        (0..npc_definitions().len()).map(|index| NpcType(index as u16))
+    }
+
+    #[inline]
+    pub(crate) fn definition(&self) -> &'static NpcDefinition {
+        &npc_definitions()[self.0 as usize]
+    }
+
+    #[inline(always)]
+    pub(crate) const fn index(&self) -> usize {
+        self.0 as usize
+    }
+}
@@ -0,0 +1,225 @@
+use std::{io::BufReader, fs::File, error::Error, path::Path, sync::OnceLock};
+
+use rand::{Rng, seq::SliceRandom};
+use serde::Deserialize;
+
+use crate::items::{Item, ProjectileDefinition};
+
+use super::NpcType;
+
+static NPC_REGISTRY: OnceLock<Vec<NpcDefinition>> = OnceLock::new();
+
+/// How the NPC moves
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub(crate) enum AiType {
+    /// Waits on the ground and hops towards the player
+    Slime,
+    /// Walks towards the player and jumps over obstacles
+    Walker,
+    /// Ignores gravity and flies towards the player
+    Flyer,
+}
+
+impl AiType {
+    #[inline]
+    pub(crate) const fn has_gravity(&self) -> bool {
+        !matches!(self, AiType::Flyer)
+    }
+}
+
+/// The part of the world where the NPC can spawn
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub(crate) enum SpawnLayer {
+    Surface,
+    Underground,
+    Cavern,
+}
+
+/// The time of day when the NPC can spawn
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub(crate) enum SpawnTime {
+    Day,
+    Night,
+    Always,
+}
+
+impl SpawnTime {
+    #[inline]
+    pub(crate) const fn matches(&self, is_day: bool) -> bool {
+        match self {
+            SpawnTime::Day => is_day,
+            SpawnTime::Night => !is_day,
+            SpawnTime::Always => true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub(crate) struct SpawnRule {
+    pub(crate) layer: SpawnLayer,
+    pub(crate) time: SpawnTime,
+    /// The chance of the NPC to be picked relative to the other NPCs which can spawn at the same place
+    pub(crate) weight: u32,
+}
+
+/// An item which can be dropped when the NPC dies
+#[derive(Clone, Copy, Deserialize)]
+pub(crate) struct Loot {
+    pub(crate) item: Item,
+    pub(crate) min: u16,
+    pub(crate) max: u16,
+    /// The probability of the item to be dropped, from 0 to 1
+    pub(crate) chance: f32,
+}
+
+/// A projectile which the NPC shoots at the player
+#[derive(Deserialize)]
+pub(crate) struct RangedAttack {
+    /// Path to the sprite of the projectile inside of the assets directory
+    pub(crate) sprite: String,
+    pub(crate) damage: i32,
+    #[serde(default)]
+    pub(crate) knockback: f32,
+    /// The number of ticks between two shots
+    pub(crate) interval: u32,
+    /// The NPC shoots when the player is closer than this number of tiles
+    pub(crate) range: f32,
+    pub(crate) projectile: ProjectileDefinition,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct NpcDefinition {
+    /// The unique name of the NPC which is used to refer to it in the data files
+    pub(crate) id: String,
+    /// Path to the sprite sheet of the NPC inside of the assets directory. The frames are stacked vertically.
+    pub(crate) sprite: String,
+    pub(crate) frame_size: [f32; 2],
+    pub(crate) frames: usize,
+    /// The color the sprite is tinted with
+    pub(crate) color: Option<[f32; 4]>,
+    /// The size of the hitbox
+    pub(crate) size: [f32; 2],
+    pub(crate) max_health: i32,
+    /// The damage the NPC deals to the player on contact
+    pub(crate) damage: i32,
+    /// The part of the knockback which the NPC ignores, from 0 to 1
+    #[serde(default)]
+    pub(crate) knockback_resistance: f32,
+    pub(crate) ai: AiType,
+    pub(crate) spawn: Vec<SpawnRule>,
+    #[serde(default)]
+    pub(crate) loot: Vec<Loot>,
+    pub(crate) attack: Option<RangedAttack>,
+}
+
+impl NpcDefinition {
+    fn validate(&self) -> Result<(), String> {
+        if self.id.is_empty() {
+            return Err("the id is empty".to_owned());
+        }
+
+        if self.frames == 0 {
+            return Err("the number of frames must be greater than zero".to_owned());
+        }
+
+        if self.frame_size.iter().chain(&self.size).any(|&value| value <= 0.) {
+            return Err("the frame size and the size must be greater than zero".to_owned());
+        }
+
+        if self.max_health <= 0 {
+            return Err("the max health must be greater than zero".to_owned());
+        }
+
+        if self.damage < 0 {
+            return Err("the damage must not be negative".to_owned());
+        }
+
+        if !(0. ..=1.).contains(&self.knockback_resistance) {
+            return Err("the knockback resistance must be between 0 and 1".to_owned());
+        }
+
+        if !Path::new("./assets").join(&self.sprite).is_file() {
+            return Err(format!("the sprite \"{}\" doesn't exist", self.sprite));
+        }
+
+        if let Some(attack) = &self.attack {
+            if attack.damage <= 0 || attack.interval == 0 || attack.range <= 0. {
+                return Err("the damage, the interval and the range of the attack must be greater than zero".to_owned());
+            }
+
+            if !Path::new("./assets").join(&attack.sprite).is_file() {
+                return Err(format!("the sprite \"{}\" doesn't exist", attack.sprite));
+            }
+
+            attack.projectile.validate()?;
+        }
+
+        for (i, loot) in self.loot.iter().enumerate() {
+            if loot.min == 0 || loot.min > loot.max || loot.max > loot.item.max_stack() {
+                return Err(format!("the loot #{} has an invalid stack range", i));
+            }
+
+            if !(0. ..=1.).contains(&loot.chance) {
+                return Err(format!("the chance of the loot #{} must be between 0 and 1", i));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The chance of the NPC to spawn in the layer at the time of day, zero if it can't spawn there
+    pub(crate) fn spawn_weight(&self, layer: SpawnLayer, is_day: bool) -> u32 {
+        self.spawn.iter()
+            .filter(|rule| rule.layer == layer && rule.time.matches(is_day))
+            .map(|rule| rule.weight)
+            .sum()
+    }
+}
+
+/// Returns all NPC definitions, the index of a definition is the id of its [`NpcType`]
+#[inline]
+pub(super) fn npc_definitions() -> &'static [NpcDefinition] {
+    NPC_REGISTRY.get().expect("The NPCs must be loaded before they are used")
+}
+
+/// Loads the NPC definitions into the registry. Must be called once after the items are loaded.
+pub(crate) fn load_npcs() -> Result<(), Box<dyn Error>> {
+    let reader = BufReader::new(
+        File::open("./assets/data/npcs.json")?
+    );
+    let definitions: Vec<NpcDefinition> = serde_json::from_reader(reader)?;
+
+    validate_npcs(&definitions)?;
+
+    NPC_REGISTRY.set(definitions).map_err(|_| "The NPCs are already loaded")?;
+
+    Ok(())
+}
+
+fn validate_npcs(definitions: &[NpcDefinition]) -> Result<(), String> {
+    if definitions.len() > u16::MAX as usize {
+        return Err(format!("There are more than {} NPCs", u16::MAX));
+    }
+
+    for (i, definition) in definitions.iter().enumerate() {
+        definition.validate().map_err(|err| format!("NPC \"{}\" is invalid: {}", definition.id, err))?;
+
+        if definitions[..i].iter().any(|other| other.id == definition.id) {
+            return Err(format!("NPC \"{}\" is defined more than once", definition.id));
+        }
+    }
+
+    Ok(())
+}
+
+/// Picks a random NPC type which can spawn in the layer at the time of day
+pub(crate) fn pick_npc_to_spawn<R: Rng>(rng: &mut R, layer: SpawnLayer, is_day: bool) -> Option<NpcType> {
+    let candidates: Vec<NpcType> = NpcType::all()
+        .filter(|npc_type| npc_type.definition().spawn_weight(layer, is_day) > 0)
+        .collect();
+
+    candidates
+        .choose_weighted(rng, |npc_type| npc_type.definition().spawn_weight(layer, is_day))
+        .ok()
+        .copied()
+}
@@ -1,8 +1,8 @@
-use bevy::prelude::{Resource, AudioSource, OnExit};
+use bevy::prelude::{Resource, AudioSource, OnExit, World, HandleUntyped};
 use bevy::{
     math::Vec2,
     prelude::{App, AssetServer, Assets, Handle, Image, Plugin, Res, ResMut},
-    render::texture::ImageSampler,
+    render::{texture::ImageSampler, render_resource::Extent3d},
     sprite::TextureAtlas,
     text::Font,
 };
@@ -10,9 +10,12 @@ use bevy_asset_loader::prelude::{AssetCollection, LoadingState, LoadingStateAppE
 use rand::{RngCore, thread_rng};
 use rand::seq::SliceRandom;
 
-use crate::items::{Item, Pickaxe, ItemTool, Axe, ItemSeed, ItemBlock, Hammer, ItemWall};
+use crate::items::Item;
+use crate::npcs::NpcType;
 use crate::common::state::GameState;
-use crate::world::block::BlockType;
+use crate::common::helpers::{get_tile_start_index, get_shaped_tile_start_index, SHAPED_TILES_START_ROW, SHAPEABLE_BLOCKS};
+use crate::plugins::world::constants::TILE_SIZE;
+use crate::world::{biome::Biome, block::{BlockType, BlockShape}, registry::TileSound};
 
 use super::audio::{SoundType, MusicType};
 
@@ -45,30 +48,39 @@ macro_rules! handles {
     }
 }
 
-macro_rules! handles_mut {
+/// Declares a collection of the sprites of all items which are loaded from the paths in the item definitions
+macro_rules! item_sprites {
     (
-     $field_type:ty,
-     // meta data about struct
      $(#[$meta:meta])*
-     $vis:vis struct $struct_name:ident {
-        $(
-        // meta data about field
-        $(#[$field_meta:meta])*
-        $field_vis:vis $field_name:ident : $field_t:ty
-        ),*$(,)+
-    }
+     $vis:vis struct $struct_name:ident;
     ) => {
         $(#[$meta])*
-        pub struct $struct_name {
-            $(
-            $(#[$field_meta])*
-            pub $field_name : $field_type,
-            )*
+        #[derive(Resource)]
+        $vis struct $struct_name {
+            sprites: Vec<Handle<Image>>,
         }
 
         impl $struct_name {
-            fn handles_mut(&mut self) -> Vec<&mut $field_type> {
-                vec![$(&mut self.$field_name),*]
+            pub(crate) fn get_by_item(&self, item: Item) -> Handle<Image> {
+                self.sprites[item.index()].clone_weak()
+            }
+        }
+
+        impl AssetCollection for $struct_name {
+            fn create(world: &mut World) -> Self {
+                let asset_server = world.resource::<AssetServer>();
+
+                Self {
+                    sprites: Item::all().map(|item| asset_server.load(&item.definition().sprite)).collect()
+                }
+            }
+
+            fn load(world: &mut World) -> Vec<HandleUntyped> {
+                let asset_server = world.resource::<AssetServer>();
+
+                Item::all()
+                    .map(|item| asset_server.load_untyped(&item.definition().sprite))
+                    .collect()
             }
         }
     }
@@ -95,6 +107,7 @@ impl Plugin for AssetsPlugin {
         app.add_collection_to_loading_state::<_, SunAndMoonAssets>(GameState::AssetLoading);
         app.add_collection_to_loading_state::<_, ParticleAssets>(GameState::AssetLoading);
         app.add_collection_to_loading_state::<_, InventoryItemAssets>(GameState::AssetLoading);
+        app.add_collection_to_loading_state::<_, NpcAssets>(GameState::AssetLoading);
         
         app.add_systems(OnExit(GameState::AssetLoading), setup);
     }
@@ -102,6 +115,7 @@ impl Plugin for AssetsPlugin {
 
 fn setup(
     mut images: ResMut<Assets<Image>>,
+    block_assets: Res<BlockAssets>,
     ui_assets: Res<UiAssets>,
     cursor_assets: Res<CursorAssets>,
     background_assets: Res<BackgroundAssets>,
@@ -116,12 +130,86 @@ fn setup(
         image.sampler_descriptor = ImageSampler::linear();
     }
 
-    for handle in inventory_item_assets.handles_mut() {
+    for handle in inventory_item_assets.sprites.iter_mut() {
         let mut image = images.get(handle).unwrap().clone();
         image.sampler_descriptor = ImageSampler::linear();
 
         *handle = images.add(image);
     }
+
+    add_shaped_block_frames(images.get_mut(&block_assets.tiles).unwrap());
+}
+
+/// Appends the frames of the blocks shaped with a hammer to the tiles texture.
+/// The frames are cut from the frames of the block with an open top or bottom side, so the edge of the block follows the shape.
+fn add_shaped_block_frames(image: &mut Image) {
+    const FRAME_STRIDE: usize = TILE_SIZE as usize + 2;
+    const FRAME_SIZE: usize = TILE_SIZE as usize;
+    const PIXEL_SIZE: usize = 4;
+
+    let width = image.texture_descriptor.size.width as usize;
+
+    // The shaped blocks must follow the last row of the tiles texture
+    let height = image.texture_descriptor.size.height as usize;
+    if height != SHAPED_TILES_START_ROW as usize * FRAME_STRIDE {
+        eprintln!(
+            "Skipping the shaped block frames: the tiles texture is {height} pixels high, expected {}",
+            SHAPED_TILES_START_ROW as usize * FRAME_STRIDE
+        );
+        return;
+    }
+
+    image.resize(Extent3d {
+        width: width as u32,
+        height: ((SHAPED_TILES_START_ROW as usize + SHAPEABLE_BLOCKS.len()) * FRAME_STRIDE) as u32,
+        depth_or_array_layers: 1,
+    });
+
+    let pixel_index = |frame_x: u32, frame_y: u32, x: usize, y: usize| -> usize {
+        ((frame_y as usize * FRAME_STRIDE + y) * width + frame_x as usize * FRAME_STRIDE + x) * PIXEL_SIZE
+    };
+
+    for block_type in SHAPEABLE_BLOCKS {
+        let start = get_tile_start_index(block_type);
+        let shaped_start = get_shaped_tile_start_index(block_type);
+
+        for variant in 0..3 {
+            // The frames of the block with only the top or only the bottom side exposed
+            let top_frame = (start.x + 1 + variant, start.y);
+            let bottom_frame = (start.x + 1 + variant, start.y + 2);
+
+            for shape in BlockShape::ALL {
+                let Some(column) = shape.texture_column(variant) else { continue; };
+
+                for x in 0..FRAME_SIZE {
+                    // Slopes go down in steps of two pixels
+                    let step_from_left = (x / 2) * 2;
+                    let step_from_right = ((FRAME_SIZE - 1 - x) / 2) * 2;
+
+                    for y in 0..FRAME_SIZE {
+                        let source = match shape {
+                            BlockShape::Half => y.checked_sub(FRAME_SIZE / 2).map(|y| (top_frame, y)),
+                            BlockShape::SlopeBottomLeft => y.checked_sub(step_from_left).map(|y| (top_frame, y)),
+                            BlockShape::SlopeBottomRight => y.checked_sub(step_from_right).map(|y| (top_frame, y)),
+                            BlockShape::SlopeTopLeft => Some(y + step_from_left).filter(|&y| y < FRAME_SIZE).map(|y| (bottom_frame, y)),
+                            BlockShape::SlopeTopRight => Some(y + step_from_right).filter(|&y| y < FRAME_SIZE).map(|y| (bottom_frame, y)),
+                            BlockShape::Full => None,
+                        };
+
+                        let destination = pixel_index(shaped_start.x + column, shaped_start.y, x, y);
+
+                        match source {
+                            Some(((frame_x, frame_y), source_y)) => {
+                                let source = pixel_index(frame_x, frame_y, x, source_y);
+                                image.data.copy_within(source..source + PIXEL_SIZE, destination);
+                            },
+                            None => image.data[destination..destination + PIXEL_SIZE].fill(0),
+                        }
+                    }
+                }
+            }
+        }
+    }
 }
 
 #[derive(Resource, AssetCollection)]
@@ -140,6 +228,12 @@ pub(crate) struct BlockAssets {
 
     #[asset(path = "sprites/tiles/Tree_Tops_0.png")]
     pub(crate) tree_tops_forest: Handle<Image>,
+
+    #[asset(path = "sprites/tiles/Liquids.png")]
+    pub(crate) liquids: Handle<Image>,
+
+    #[asset(path = "sprites/tiles/Furniture.png")]
+    pub(crate) furniture: Handle<Image>,
 }
 
 handles! {
@@ -158,6 +252,9 @@ handles! {
         #[asset(path = "sprites/Inventory_Back14.png")]
         pub(crate) selected_inventory_background: Handle<Image>,
 
+        #[asset(path = "sprites/Inventory_Back10.png")]
+        pub(crate) favourite_inventory_background: Handle<Image>,
+
         #[asset(path = "sprites/ui/Radial.png")]
         pub(crate) radial: Handle<Image>,
 
@@ -175,6 +272,12 @@ handles! {
 
         #[asset(path = "sprites/ui/SpawnPoint.png")]
         pub(crate) spawn_point: Handle<Image>,
+
+        #[asset(path = "sprites/ui/Heart.png")]
+        pub(crate) heart: Handle<Image>,
+
+        #[asset(path = "sprites/ui/Mana.png")]
+        pub(crate) mana: Handle<Image>,
     }
 }
 
@@ -226,114 +329,72 @@ pub(crate) struct FontAssets {
     pub(crate) andy_regular: Handle<Font>,
 }
 
-#[derive(Resource, AssetCollection)]
-pub(crate) struct ItemAssets {
-    #[asset(path = "sprites/items/Item_2.png")]
-    pub(crate) dirt_block: Handle<Image>,
-
-    #[asset(path = "sprites/items/Item_3.png")]
-    pub(crate) stone_block: Handle<Image>,
-
-    #[asset(path = "sprites/items/Item_30.png")]
-    pub(crate) dirt_wall: Handle<Image>,
-
-    #[asset(path = "sprites/items/Item_26.png")]
-    pub(crate) stone_wall: Handle<Image>,
-
-    #[asset(path = "sprites/items/Item_62.png")]
-    pub(crate) grass_seed: Handle<Image>,
-
-    #[asset(path = "sprites/items/Item_3509.png")]
-    pub(crate) copper_pickaxe: Handle<Image>,
-
-    #[asset(path = "sprites/items/Item_3506.png")]
-    pub(crate) copper_axe: Handle<Image>,
-
-    #[asset(path = "sprites/items/Item_3505.png")]
-    pub(crate) copper_hammer: Handle<Image>,
+item_sprites! {
+    /// Sprites of the items held by the player and dropped in the world
+    pub(crate) struct ItemAssets;
+}
 
-    #[asset(path = "sprites/items/Item_9.png")]
-    pub(crate) wood: Handle<Image>,
+item_sprites! {
+    /// Sprites of the items shown in the user interface
+    pub(crate) struct InventoryItemAssets;
 }
 
-impl ItemAssets {
-    pub(crate) fn get_by_item(&self, item: Item) -> Handle<Image> {
-        match item {
-            Item::Block(block) => {
-                match block {
-                    ItemBlock::Dirt => self.dirt_block.clone_weak(),
-                    ItemBlock::Stone => self.stone_block.clone_weak(),
-                    ItemBlock::Wood => self.wood.clone_weak(),
-                }
-            }
-            Item::Wall(wall) => match wall {
-                ItemWall::Dirt => self.dirt_wall.clone_weak(),
-                ItemWall::Stone => self.stone_wall.clone_weak(),
-            }
-            Item::Tool(tool) => match tool {
-                ItemTool::Pickaxe(Pickaxe::CopperPickaxe) => self.copper_pickaxe.clone_weak(),
-                ItemTool::Axe(Axe::CopperAxe) => self.copper_axe.clone_weak(),
-                ItemTool::Hammer(Hammer::CopperHammer) => self.copper_hammer.clone_weak(),
-            }
-            Item::Seed(ItemSeed::Grass) => self.grass_seed.clone_weak()
-        }
-    }
+/// Sprite sheets of all NPCs and sprites of their projectiles which are loaded from the paths in the NPC definitions
+#[derive(Resource)]
+pub(crate) struct NpcAssets {
+    sprites: Vec<Handle<TextureAtlas>>,
+    projectiles: Vec<Option<Handle<Image>>>,
 }
 
-handles_mut! {
-    Handle<Image>,
-    #[derive(Resource, AssetCollection)]
-    pub(crate) struct InventoryItemAssets {
-        #[asset(path = "sprites/items/Item_2.png")]
-        pub(crate) dirt_block: Handle<Image>,
+impl NpcAssets {
+    pub(crate) fn get_by_npc(&self, npc_type: NpcType) -> Handle<TextureAtlas> {
+        self.sprites[npc_type.index()].clone_weak()
+    }
 
-        #[asset(path = "sprites/items/Item_3.png")]
-        pub(crate) stone_block: Handle<Image>,
+    pub(crate) fn get_projectile_by_npc(&self, npc_type: NpcType) -> Option<Handle<Image>> {
+        self.projectiles[npc_type.index()].as_ref().map(Handle::clone_weak)
+    }
+}
 
-        #[asset(path = "sprites/items/Item_30.png")]
-        pub(crate) dirt_wall: Handle<Image>,
+impl AssetCollection for NpcAssets {
+    fn create(world: &mut World) -> Self {
+        let asset_server = world.resource::<AssetServer>();
 
-        #[asset(path = "sprites/items/Item_26.png")]
-        pub(crate) stone_wall: Handle<Image>,
+        let textures: Vec<Handle<Image>> = NpcType::all()
+            .map(|npc_type| asset_server.load(&npc_type.definition().sprite))
+            .collect();
 
-        #[asset(path = "sprites/items/Item_62.png")]
-        pub(crate) grass_seed: Handle<Image>,
+        let projectiles = NpcType::all()
+            .map(|npc_type| {
+                npc_type.definition().attack.as_ref().map(|attack| asset_server.load(&attack.sprite))
+            })
+            .collect();
 
-        #[asset(path = "sprites/items/Item_3509.png")]
-        pub(crate) copper_pickaxe: Handle<Image>,
+        let mut texture_atlases = world.resource_mut::<Assets<TextureAtlas>>();
 
-        #[asset(path = "sprites/items/Item_3506.png")]
-        pub(crate) copper_axe: Handle<Image>,
+        let sprites = NpcType::all().zip(textures)
+            .map(|(npc_type, texture)| {
+                let definition = npc_type.definition();
+                let frame_size = Vec2::from_array(definition.frame_size);
 
-        #[asset(path = "sprites/items/Item_3505.png")]
-        pub(crate) copper_hammer: Handle<Image>,
+                texture_atlases.add(TextureAtlas::from_grid(texture, frame_size, 1, definition.frames, None, None))
+            })
+            .collect();
 
-        #[asset(path = "sprites/items/Item_9.png")]
-        pub(crate) wood: Handle<Image>,
+        Self { sprites, projectiles }
     }
-}
 
-impl InventoryItemAssets {
-    pub(crate) fn get_by_item(&self, item: Item) -> Handle<Image> {
-        match item {
-            Item::Block(block) => {
-                match block {
-                    ItemBlock::Dirt => self.dirt_block.clone_weak(),
-                    ItemBlock::Stone => self.stone_block.clone_weak(),
-                    ItemBlock::Wood => self.wood.clone_weak(),
-                }
-            }
-            Item::Wall(wall) => match wall {
-                ItemWall::Dirt => self.dirt_wall.clone_weak(),
-                ItemWall::Stone => self.stone_wall.clone_weak(),
-            }
-            Item::Tool(tool) => match tool {
-                ItemTool::Pickaxe(Pickaxe::CopperPickaxe) => self.copper_pickaxe.clone_weak(),
-                ItemTool::Axe(Axe::CopperAxe) => self.copper_axe.clone_weak(),
-                ItemTool::Hammer(Hammer::CopperHammer) => self.copper_hammer.clone_weak(),
-            }
-            Item::Seed(ItemSeed::Grass) => self.grass_seed.clone_weak()
-        }
+    fn load(world: &mut World) -> Vec<HandleUntyped> {
+        let asset_server = world.resource::<AssetServer>();
+
+        NpcType::all()
+            .flat_map(|npc_type| {
+                let definition = npc_type.definition();
+                [Some(&definition.sprite), definition.attack.as_ref().map(|attack| &attack.sprite)]
+            })
+            .flatten()
+            .map(|path| asset_server.load_untyped(path))
+            .collect()
     }
 }
 
@@ -389,6 +450,33 @@ handles! {
         #[asset(path = "sprites/backgrounds/Background_114.png")]
         pub(crate) background_114: Handle<Image>,
 
+        #[asset(path = "sprites/backgrounds/Background_Desert_0.png")]
+        pub(crate) background_desert_0: Handle<Image>,
+
+        #[asset(path = "sprites/backgrounds/Background_Desert_1.png")]
+        pub(crate) background_desert_1: Handle<Image>,
+
+        #[asset(path = "sprites/backgrounds/Background_Desert_2.png")]
+        pub(crate) background_desert_2: Handle<Image>,
+
+        #[asset(path = "sprites/backgrounds/Background_Snow_0.png")]
+        pub(crate) background_snow_0: Handle<Image>,
+
+        #[asset(path = "sprites/backgrounds/Background_Snow_1.png")]
+        pub(crate) background_snow_1: Handle<Image>,
+
+        #[asset(path = "sprites/backgrounds/Background_Snow_2.png")]
+        pub(crate) background_snow_2: Handle<Image>,
+
+        #[asset(path = "sprites/backgrounds/Background_Jungle_0.png")]
+        pub(crate) background_jungle_0: Handle<Image>,
+
+        #[asset(path = "sprites/backgrounds/Background_Jungle_1.png")]
+        pub(crate) background_jungle_1: Handle<Image>,
+
+        #[asset(path = "sprites/backgrounds/Background_Jungle_2.png")]
+        pub(crate) background_jungle_2: Handle<Image>,
+
         #[asset(path = "sprites/backgrounds/Star_0.png")]
         pub(crate) star_0: Handle<Image>,
 
@@ -449,6 +537,18 @@ pub(crate) struct SunAndMoonAssets {
     pub(crate) moon_8: Handle<TextureAtlas>,
 }
 
+impl BackgroundAssets {
+    /// The surface layers of the biome, from the nearest to the farthest
+    pub(crate) fn get_by_biome(&self, biome: Biome) -> [Handle<Image>; 3] {
+        match biome {
+            Biome::Forest => [self.background_55.clone_weak(), self.background_114.clone_weak(), self.background_93.clone_weak()],
+            Biome::Desert => [self.background_desert_0.clone_weak(), self.background_desert_1.clone_weak(), self.background_desert_2.clone_weak()],
+            Biome::Snow => [self.background_snow_0.clone_weak(), self.background_snow_1.clone_weak(), self.background_snow_2.clone_weak()],
+            Biome::Jungle => [self.background_jungle_0.clone_weak(), self.background_jungle_1.clone_weak(), self.background_jungle_2.clone_weak()],
+        }
+    }
+}
+
 impl SunAndMoonAssets {
     pub(crate) const fn moons(&self) -> [&Handle<TextureAtlas>; 9] {
         [&self.moon_0, &self.moon_1, &self.moon_2, &self.moon_3, &self.moon_4, &self.moon_5, &self.moon_6, &self.moon_7, &self.moon_8]
@@ -493,17 +593,20 @@ impl SoundAssets {
             SoundType::MenuClose => self.menu_close.clone_weak(),
             SoundType::BlockHit(block_type) => self.get_by_block(block_type, &mut thread_rng()),
             SoundType::BlockPlace(block_type) => self.get_by_block(block_type, &mut thread_rng()),
-            SoundType::PlayerToolSwing(_tool) => self.swing.choose(&mut thread_rng()).unwrap().clone_weak(),
+            SoundType::PlayerToolSwing | SoundType::PlayerWeaponSwing => self.swing.choose(&mut thread_rng()).unwrap().clone_weak(),
             SoundType::ItemGrab => self.grab.clone_weak(),
             SoundType::WallHit => self.dig.choose(&mut thread_rng()).unwrap().clone_weak(),
+            SoundType::ToolTooWeak => self.tink.choose(&mut thread_rng()).unwrap().clone_weak(),
         }
     }
     
     fn get_by_block<Rng: RngCore>(&self, block: BlockType, rng: &mut Rng) -> Handle<AudioSource> {
-        match block {
-            BlockType::Stone => self.tink.choose(rng).unwrap().clone_weak(),
-            _ => self.dig.choose(rng).unwrap().clone_weak()
-        }
+        let sounds = match block.definition().sound {
+            TileSound::Dig => &self.dig,
+            TileSound::Tink => &self.tink,
+        };
+
+        sounds.choose(rng).unwrap().clone_weak()
     }
 }
 
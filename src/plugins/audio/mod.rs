@@ -1,6 +1,6 @@
 use bevy::{prelude::{Plugin, App, PostUpdate, IntoSystemConfigs, not, in_state, OnEnter, Commands, World, AudioBundle, PlaybackSettings}, ecs::system::Command, audio::Volume};
 
-use crate::{world::block::BlockType, items::ItemTool, common::state::GameState};
+use crate::{world::block::BlockType, common::state::GameState};
 
 mod systems;
 mod events;
@@ -48,8 +48,11 @@ pub(crate) enum SoundType {
     BlockHit(BlockType),
     WallHit,
     BlockPlace(BlockType),
+    /// A block is hit with a tool which doesn't have enough power to damage it
+    ToolTooWeak,
 
-    PlayerToolSwing(ItemTool),
+    PlayerToolSwing,
+    PlayerWeaponSwing,
 
     ItemGrab
 }
@@ -1,4 +1,4 @@
-use crate::common::{state::GameState, systems::despawn_with};
+use crate::{common::{state::GameState, systems::despawn_with}, world::biome::Biome};
 use bevy::{
     prelude::{
         in_state, not, App, Component, IntoSystemConfigs, OnEnter, OnExit, Plugin, PostUpdate,
@@ -38,7 +38,7 @@ impl Plugin for BackgroundPlugin {
                 systems::spawn_ingame_background_camera,
                 systems::spawn_sky_background,
                 systems::spawn_ingame_background,
-                systems::spawn_forest_background,
+                systems::spawn_biome_backgrounds,
             ),
         );
 
@@ -47,6 +47,13 @@ impl Plugin for BackgroundPlugin {
             systems::update_sprites_color.run_if(not(in_state(GameState::AssetLoading))),
         );
 
+        app.add_systems(
+            Update,
+            systems::update_biome_backgrounds
+                .after(systems::update_sprites_color)
+                .in_set(InGameSystemSet::Update),
+        );
+
         app.add_systems(
             PostUpdate,
             systems::follow_camera_system
@@ -61,7 +68,11 @@ impl Plugin for BackgroundPlugin {
 pub(crate) struct MenuParallaxContainer;
 
 #[derive(Component)]
-pub(crate) struct BiomeParallaxContainer;
+pub(crate) struct BiomeParallaxContainer {
+    pub(crate) biome: Biome,
+    /// Opacity of the layers, fades in when the camera enters the biome
+    pub(crate) alpha: f32,
+}
 
 #[derive(Component)]
 pub(crate) struct InGameParallaxContainer;
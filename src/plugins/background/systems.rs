@@ -3,21 +3,22 @@ use crate::{
         LayerComponent, LayerData, LayerDataComponent, LayerSpeed, LayerTextureComponent,
         ParallaxCameraComponent, ParallaxContainer,
     },
-    world::WorldData,
+    world::{biome::Biome, WorldData},
     BACKGROUND_LAYER
 };
 use bevy::{
     core_pipeline::{clear_color::ClearColorConfig, tonemapping::Tonemapping},
     prelude::{
         default, Assets, Camera, Camera2d, Camera2dBundle, Commands, Image, Name, Query, Res,
-        Transform, UiCameraConfig, Vec2, With, Without,
+        Transform, UiCameraConfig, Vec2, Visibility, With, Without, Children,
     },
+    time::Time,
     sprite::{Anchor, Sprite}
 };
 
 use crate::plugins::{
     assets::BackgroundAssets,
-    camera::components::{BackgroundCamera, InGameBackgroundCamera, MoveCamera, ZoomableCamera},
+    camera::components::{BackgroundCamera, InGameBackgroundCamera, MoveCamera, ZoomableCamera, MainCamera},
     world::{constants::TILE_SIZE, time::GameTime},
     DespawnOnGameExit,
 };
@@ -215,52 +216,59 @@ pub(super) fn spawn_ingame_background(
     ));
 }
 
-pub(super) fn spawn_forest_background(
+pub(super) fn spawn_biome_backgrounds(
     mut commands: Commands,
     backgrounds: Res<BackgroundAssets>,
     world_data: Res<WorldData>,
 ) {
-    commands.spawn((
-        Name::new("Biome Parallax Container"),
-        BiomeParallaxContainer,
-        DespawnOnGameExit,
-        ParallaxContainer::new(vec![
-            LayerData {
-                speed: LayerSpeed::Bidirectional(0.8, 0.6),
-                image: backgrounds.background_55.clone_weak(),
-                z: BACKGROUND_LAYER + 0.4,
-                scale: 2.5,
-                position: (world_data.layer.underground - world_data.layer.dirt_height / 2) as f32
-                    * TILE_SIZE
-                    * Vec2::NEG_Y,
-                anchor: Anchor::Center,
-                ..default()
-            },
-            LayerData {
-                speed: LayerSpeed::Bidirectional(0.4, 0.5),
-                image: backgrounds.background_114.clone_weak(),
-                z: BACKGROUND_LAYER + 0.3,
-                scale: 2.,
-                position: (world_data.layer.underground - world_data.layer.dirt_height / 2) as f32
-                    * TILE_SIZE
-                    * Vec2::NEG_Y,
-                anchor: Anchor::Center,
-                ..default()
-            },
-            LayerData {
-                speed: LayerSpeed::Bidirectional(0.2, 0.4),
-                image: backgrounds.background_93.clone_weak(),
-                z: BACKGROUND_LAYER + 0.2,
-                scale: 2.,
-                position: (world_data.layer.underground - world_data.layer.dirt_height) as f32
-                    * TILE_SIZE
-                    * Vec2::NEG_Y,
-                anchor: Anchor::Center,
-                ..default()
+    let spawn_biome = world_data.get_biome(world_data.spawn_point.x as usize);
+    let near_position = (world_data.layer.underground - world_data.layer.dirt_height / 2) as f32 * TILE_SIZE * Vec2::NEG_Y;
+    let far_position = (world_data.layer.underground - world_data.layer.dirt_height) as f32 * TILE_SIZE * Vec2::NEG_Y;
+
+    for biome in Biome::ALL {
+        if !world_data.biomes.contains(&biome) { continue; }
+
+        let [near, middle, far] = backgrounds.get_by_biome(biome);
+
+        commands.spawn((
+            Name::new(format!("{:?} Parallax Container", biome)),
+            BiomeParallaxContainer {
+                biome,
+                alpha: if biome == spawn_biome { 1. } else { 0. },
             },
-        ])
-        .with_render_layer(BACKGROUND_RENDER_LAYER),
-    ));
+            DespawnOnGameExit,
+            ParallaxContainer::new(vec![
+                LayerData {
+                    speed: LayerSpeed::Bidirectional(0.8, 0.6),
+                    image: near,
+                    z: BACKGROUND_LAYER + 0.4,
+                    scale: 2.5,
+                    position: near_position,
+                    anchor: Anchor::Center,
+                    ..default()
+                },
+                LayerData {
+                    speed: LayerSpeed::Bidirectional(0.4, 0.5),
+                    image: middle,
+                    z: BACKGROUND_LAYER + 0.3,
+                    scale: 2.,
+                    position: near_position,
+                    anchor: Anchor::Center,
+                    ..default()
+                },
+                LayerData {
+                    speed: LayerSpeed::Bidirectional(0.2, 0.4),
+                    image: far,
+                    z: BACKGROUND_LAYER + 0.2,
+                    scale: 2.,
+                    position: far_position,
+                    anchor: Anchor::Center,
+                    ..default()
+                },
+            ])
+            .with_render_layer(BACKGROUND_RENDER_LAYER),
+        ));
+    }
 }
 
 pub(super) fn update_sprites_color(
@@ -273,3 +281,38 @@ pub(super) fn update_sprites_color(
         sprite.color = ambient_color;
     }
 }
+
+pub(super) fn update_biome_backgrounds(
+    time: Res<Time>,
+    game_time: Res<GameTime>,
+    world_data: Res<WorldData>,
+    query_camera: Query<&Transform, With<MainCamera>>,
+    mut query_container: Query<(&mut BiomeParallaxContainer, &mut Visibility, &Children)>,
+    query_layer: Query<&Children, With<LayerComponent>>,
+    mut query_sprite: Query<&mut Sprite, With<LayerTextureComponent>>,
+) {
+    const TRANSITION_SPEED: f32 = 2.;
+
+    let Ok(camera_transform) = query_camera.get_single() else { return; };
+
+    let x = (camera_transform.translation.x / TILE_SIZE).max(0.) as usize;
+    let current_biome = world_data.get_biome(x);
+
+    let ambient_color = game_time.ambient_color();
+
+    for (mut container, mut visibility, layers) in &mut query_container {
+        // Fade between the biomes instead of switching instantly
+        let target_alpha = if container.biome == current_biome { 1. } else { 0. };
+        let step = time.delta_seconds() * TRANSITION_SPEED;
+        container.alpha += (target_alpha - container.alpha).clamp(-step, step);
+
+        *visibility = if container.alpha > 0. { Visibility::Inherited } else { Visibility::Hidden };
+
+        for textures in query_layer.iter_many(layers) {
+            let mut iter = query_sprite.iter_many_mut(textures);
+            while let Some(mut sprite) = iter.fetch_next() {
+                sprite.color = ambient_color.with_a(container.alpha);
+            }
+        }
+    }
+}
@@ -45,6 +45,8 @@ pub(crate) struct Config {
     pub(crate) sound_volume: f32,
     pub(crate) music_volume: f32,
     pub(crate) light_smoothness: u8,
+    #[serde(default)]
+    pub(crate) dropped_items: DroppedItemsConfig,
 }
 
 
@@ -59,7 +61,8 @@ impl Default for Config {
             zoom: 0.67,
             sound_volume: 1.,
             music_volume: 1.,
-            light_smoothness: LightSmoothness::Classic.to_u8()
+            light_smoothness: LightSmoothness::Classic.to_u8(),
+            dropped_items: DroppedItemsConfig::default()
         }
     }
 }
@@ -78,6 +81,7 @@ impl Plugin for ConfigPlugin {
         app.insert_resource(LightSmoothness::new(config.light_smoothness));
         app.insert_resource(config.cursor_color);
         app.insert_resource(config.resolution);
+        app.insert_resource(config.dropped_items);
 
         app.add_systems(
             Update,
@@ -106,6 +110,7 @@ fn on_exit(
     sound_volume: Res<SoundVolume>,
     zoom: Res<Zoom>,
     light_smoothness: Res<LightSmoothness>,
+    dropped_items: Res<DroppedItemsConfig>,
 ) {
     save_config(Config {
         full_screen: fullscreen.0,
@@ -116,7 +121,8 @@ fn on_exit(
         sound_volume: sound_volume.get(),
         music_volume: music_volume.get(),
         zoom: zoom.get(),
-        light_smoothness: light_smoothness.to_u8()
+        light_smoothness: light_smoothness.to_u8(),
+        dropped_items: *dropped_items
     });
 }
 
@@ -75,6 +75,28 @@ impl FullScreen {
     }
 }
 
+/// Limits for the items lying on the ground
+#[derive(Resource, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase", default)]
+pub(crate) struct DroppedItemsConfig {
+    /// Seconds a dropped item lies on the ground before it despawns
+    pub(crate) lifetime: f32,
+    /// Max number of dropped items in the world, the oldest ones despawn first
+    pub(crate) max_count: usize,
+    /// Distance in tiles within which the dropped stacks of the same item merge
+    pub(crate) merge_radius: f32,
+}
+
+impl Default for DroppedItemsConfig {
+    fn default() -> Self {
+        Self {
+            lifetime: 300.,
+            max_count: 400,
+            merge_radius: 0.75
+        }
+    }
+}
+
 #[derive(Resource, Deref, DerefMut, Clone, Copy)]
 pub(crate) struct MusicVolume(VolumeLevel);
 
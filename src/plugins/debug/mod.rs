@@ -6,7 +6,7 @@ use crate::common::state::GameState;
 
 use self::resources::HoverBlockData;
 
-use super::{inventory::{UseItemAnimationIndex, UseItemAnimationData}, player::FaceDirection, InGameSystemSet, entity::components::EntityRect};
+use super::{inventory::{UseItemAnimationIndex, UseItemAnimationData}, player::FaceDirection, InGameSystemSet, entity::components::{EntityRect, Health, Mana}};
 
 mod components;
 mod gui;
@@ -44,6 +44,8 @@ impl Plugin for DebugPlugin {
         app.register_type::<UseItemAnimationData>();
         app.register_type::<FaceDirection>();
         app.register_type::<EntityRect>();
+        app.register_type::<Health>();
+        app.register_type::<Mana>();
 
         app.add_systems(PreUpdate, systems::cursor_visibility);
 
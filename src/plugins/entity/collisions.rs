@@ -0,0 +1,280 @@
+use bevy::prelude::{Query, Res};
+
+use crate::{common::rect::FRect, world::{WorldData, block::{BlockShape, BlockType}}, plugins::world::constants::TILE_SIZE};
+
+use super::{clamp_to_playable_area, components::{EntityRect, Velocity, Collider, Collisions}};
+
+#[cfg(feature = "debug")]
+use bevy::prelude::{Gizmos, Color, With};
+
+/// Tiles which only touch an entity don't collide with it, so an entity standing on the ground can walk along it
+const SKIN: f32 = 0.01;
+
+/// The longest distance an entity is moved by before its collisions are resolved
+const MAX_STEP: f32 = TILE_SIZE / 2.;
+
+struct TileCollider {
+    rect: FRect,
+    shape: BlockShape,
+    block_type: Option<BlockType>,
+}
+
+impl TileCollider {
+    /// Height of the surface of a floor slope under `x`, it's `None` on the low side of the slope
+    fn slope_surface(&self, x: f32) -> Option<f32> {
+        let offset = (x - self.rect.left()) / TILE_SIZE;
+
+        if (0. ..=1.).contains(&offset) {
+            return Some(self.rect.bottom() + self.shape.top_height(offset) * TILE_SIZE);
+        }
+
+        let on_low_side = match self.shape {
+            BlockShape::SlopeBottomLeft => offset > 1.,
+            _ => offset < 0.,
+        };
+
+        (!on_low_side).then_some(self.rect.top())
+    }
+
+    /// The entity walks up and down a floor slope when it's on the level of the slope.
+    /// Otherwise the slope is solid like a full block.
+    #[inline]
+    fn is_walkable_slope(&self, entity_rect: &FRect) -> bool {
+        self.shape.is_floor_slope() && entity_rect.bottom() + SKIN >= self.rect.bottom()
+    }
+}
+
+#[inline]
+fn overlaps(a: &FRect, b: &FRect) -> bool {
+    a.left() < b.right() - SKIN &&
+        a.right() > b.left() + SKIN &&
+        a.bottom() < b.top() - SKIN &&
+        a.top() > b.bottom() + SKIN
+}
+
+/// The solid tiles which can overlap with `rect`.
+/// The tiles below the playable area are solid, so nothing falls out of the world.
+fn solid_tiles(world_data: &WorldData, rect: FRect) -> impl Iterator<Item = TileCollider> + '_ {
+    let left = (rect.left() / TILE_SIZE).floor().max(0.) as u32;
+    let right = (rect.right() / TILE_SIZE).ceil().max(0.) as u32;
+    let top = (-rect.top() / TILE_SIZE).floor().max(0.) as u32;
+    let bottom = (-rect.bottom() / TILE_SIZE).ceil().max(0.) as u32;
+
+    (left..right)
+        .flat_map(move |x| (top..bottom).map(move |y| (x, y)))
+        .filter_map(|(x, y)| {
+            let block = world_data.get_block((x, y)).filter(|block| block.is_solid());
+
+            if block.is_none() && y < world_data.playable_area.max.y { return None; }
+
+            let shape = block.map_or(BlockShape::Full, |block| block.shape);
+
+            let rect = if shape == BlockShape::Half {
+                FRect::new_center(
+                    x as f32 * TILE_SIZE + TILE_SIZE / 2.,
+                    -(y as f32 * TILE_SIZE + TILE_SIZE * 3. / 4.),
+                    TILE_SIZE,
+                    TILE_SIZE / 2.
+                )
+            } else {
+                FRect::new_center(
+                    x as f32 * TILE_SIZE + TILE_SIZE / 2.,
+                    -(y as f32 * TILE_SIZE + TILE_SIZE / 2.),
+                    TILE_SIZE,
+                    TILE_SIZE
+                )
+            };
+
+            Some(TileCollider { rect, shape, block_type: block.map(|block| block.block_type) })
+        })
+}
+
+#[inline]
+fn is_free(world_data: &WorldData, rect: &FRect) -> bool {
+    solid_tiles(world_data, *rect).all(|tile| !overlaps(rect, &tile.rect))
+}
+
+/// Moves the entities with a [`Collider`] by their velocity one axis at a time
+/// and pushes them out of the solid tiles they run into
+pub(super) fn move_colliders(
+    world_data: Res<WorldData>,
+    mut query: Query<(&mut EntityRect, &mut Velocity, &Collider, Option<&mut Collisions>)>
+) {
+    for (mut entity_rect, mut velocity, collider, collisions) in &mut query {
+        let mut new_collisions = Collisions::default();
+        let mut rect = entity_rect.0;
+
+        // An entity is moved at most half a tile at once, so a fast one doesn't pass through a thin wall
+        let steps = (velocity.x.abs().max(velocity.y.abs()) / MAX_STEP).ceil().max(1.) as u32;
+
+        for _ in 0..steps {
+            // A collision stops the rest of the move on its axis
+            let mut step_velocity = Velocity(velocity.0 / steps as f32);
+            move_step(&world_data, collider, &mut rect, &mut step_velocity, &mut new_collisions);
+            velocity.0 = step_velocity.0 * steps as f32;
+        }
+
+        clamp_to_playable_area(&world_data, &mut rect);
+        entity_rect.0 = rect;
+
+        if let Some(mut collisions) = collisions {
+            *collisions = new_collisions;
+        }
+    }
+}
+
+fn move_step(
+    world_data: &WorldData,
+    collider: &Collider,
+    rect: &mut FRect,
+    velocity: &mut Velocity,
+    collisions: &mut Collisions
+) {
+    let start = *rect;
+
+    if velocity.x != 0. {
+        rect.centerx += velocity.x;
+        collide_horizontally(world_data, collider, &start, rect, velocity, collisions);
+    }
+
+    let before_vertical_move = *rect;
+    rect.centery += velocity.y;
+    collide_vertically(world_data, &start, &before_vertical_move, rect, velocity, collisions);
+}
+
+fn collide_horizontally(
+    world_data: &WorldData,
+    collider: &Collider,
+    start: &FRect,
+    rect: &mut FRect,
+    velocity: &mut Velocity,
+    collisions: &mut Collisions
+) {
+    let direction = velocity.x.signum();
+
+    for tile in solid_tiles(world_data, *rect) {
+        if !overlaps(rect, &tile.rect) { continue; }
+
+        // The entity was already stuck in the tile, so let it get out
+        if (direction > 0. && start.right() > tile.rect.left() + SKIN)
+            || (direction < 0. && start.left() < tile.rect.right() - SKIN)
+        {
+            continue;
+        }
+
+        let surface = if tile.is_walkable_slope(start) {
+            let Some(surface) = tile.slope_surface(rect.centerx) else { continue; };
+
+            // The entity is lifted onto the surface of the slope when it moves vertically
+            if surface - rect.bottom() <= collider.step_height { continue; }
+
+            surface
+        } else {
+            tile.rect.top()
+        };
+
+        // Step up onto the ledge if there is enough space above it
+        let step = surface - rect.bottom();
+        if step > 0. && step <= collider.step_height {
+            let mut stepped_rect = *rect;
+            stepped_rect.centery += step;
+
+            if is_free(world_data, &stepped_rect) {
+                *rect = stepped_rect;
+                collisions.bottom = true;
+                collisions.ground = tile.block_type;
+                continue;
+            }
+        }
+
+        if direction > 0. {
+            collisions.right = true;
+            rect.centerx = tile.rect.left() - rect.half_width();
+        } else {
+            collisions.left = true;
+            rect.centerx = tile.rect.right() + rect.half_width();
+        }
+
+        velocity.x = 0.;
+    }
+}
+
+fn collide_vertically(
+    world_data: &WorldData,
+    start: &FRect,
+    before: &FRect,
+    rect: &mut FRect,
+    velocity: &mut Velocity,
+    collisions: &mut Collisions
+) {
+    for tile in solid_tiles(world_data, *rect) {
+        if !overlaps(rect, &tile.rect) { continue; }
+
+        if tile.is_walkable_slope(start) {
+            let Some(surface) = tile.slope_surface(rect.centerx) else { continue; };
+
+            if rect.bottom() < surface {
+                collisions.bottom = true;
+                collisions.ground = tile.block_type;
+
+                velocity.y = velocity.y.max(0.);
+                rect.centery += surface - rect.bottom();
+            }
+
+            continue;
+        }
+
+        if velocity.y > 0. {
+            if before.top() > tile.rect.bottom() + SKIN { continue; }
+
+            collisions.top = true;
+            velocity.y = 0.;
+            rect.centery = tile.rect.bottom() - rect.half_height();
+        } else {
+            if before.bottom() < tile.rect.top() - SKIN { continue; }
+
+            collisions.bottom = true;
+            collisions.ground = tile.block_type;
+            velocity.y = 0.;
+            rect.centery = tile.rect.top() + rect.half_height();
+        }
+    }
+}
+
+#[cfg(feature = "debug")]
+pub(super) fn draw_collisions(
+    world_data: Res<WorldData>,
+    mut gizmos: Gizmos,
+    query: Query<&EntityRect, With<Collider>>
+) {
+    const MAX_DISTANCE: f32 = 1.;
+
+    for entity_rect in &query {
+        let area = FRect::new_center(
+            entity_rect.centerx,
+            entity_rect.centery,
+            entity_rect.width() + MAX_DISTANCE * 2.,
+            entity_rect.height() + MAX_DISTANCE * 2.
+        );
+
+        for tile in solid_tiles(&world_data, area) {
+            let tile_rect = tile.rect;
+
+            let overlaps_x = entity_rect.left() < tile_rect.right() && entity_rect.right() > tile_rect.left();
+            let overlaps_y = entity_rect.bottom() < tile_rect.top() && entity_rect.top() > tile_rect.bottom();
+
+            if overlaps_x && (entity_rect.bottom() - tile_rect.top()).abs() <= MAX_DISTANCE {
+                tile_rect.draw_top_side(&mut gizmos, Color::RED);
+            }
+            if overlaps_x && (tile_rect.bottom() - entity_rect.top()).abs() <= MAX_DISTANCE {
+                tile_rect.draw_bottom_side(&mut gizmos, Color::YELLOW);
+            }
+            if overlaps_y && (entity_rect.left() - tile_rect.right()).abs() <= MAX_DISTANCE {
+                tile_rect.draw_right_side(&mut gizmos, Color::BLUE);
+            }
+            if overlaps_y && (tile_rect.left() - entity_rect.right()).abs() <= MAX_DISTANCE {
+                tile_rect.draw_left_side(&mut gizmos, Color::GREEN);
+            }
+        }
+    }
+}
@@ -1,6 +1,6 @@
-use bevy::prelude::{Component, DerefMut, Deref, Vec2};
+use bevy::{prelude::{Component, DerefMut, Deref, Vec2}, time::Timer, math::vec2};
 
-use crate::common::rect::FRect;
+use crate::{common::rect::FRect, plugins::world::constants::TILE_SIZE, world::block::BlockType};
 
 #[cfg(feature = "debug")]
 use bevy::prelude::{ReflectComponent, Reflect};
@@ -21,4 +21,101 @@ impl From<Vec2> for Velocity {
     fn from(value: Vec2) -> Self {
         Self(value)
     }
-}
\ No newline at end of file
+}
+
+/// Hit points of an entity, it dies when they run out
+#[derive(Component, Clone, Copy, Default)]
+#[cfg_attr(feature = "debug", derive(bevy_inspector_egui::InspectorOptions))]
+#[cfg_attr(feature = "debug", derive(Reflect))]
+#[cfg_attr(feature = "debug", reflect(Component))]
+pub(crate) struct Health {
+    pub(crate) current: i32,
+    pub(crate) max: i32,
+}
+
+impl Health {
+    pub(crate) const fn new(max: i32) -> Self {
+        Self { current: max, max }
+    }
+
+    #[inline]
+    pub(crate) fn damage(&mut self, amount: i32) {
+        self.current = (self.current - amount).max(0);
+    }
+
+    #[inline]
+    pub(crate) fn restore(&mut self) {
+        self.current = self.max;
+    }
+
+    #[inline(always)]
+    pub(crate) const fn is_dead(&self) -> bool {
+        self.current <= 0
+    }
+}
+
+/// Magic points of an entity, they regenerate over time
+#[derive(Component, Clone, Copy, Default)]
+#[cfg_attr(feature = "debug", derive(bevy_inspector_egui::InspectorOptions))]
+#[cfg_attr(feature = "debug", derive(Reflect))]
+#[cfg_attr(feature = "debug", reflect(Component))]
+pub(crate) struct Mana {
+    pub(crate) current: f32,
+    pub(crate) max: i32,
+    /// Mana points restored per second
+    pub(crate) regen: f32,
+}
+
+impl Mana {
+    pub(crate) const fn new(max: i32, regen: f32) -> Self {
+        Self { current: max as f32, max, regen }
+    }
+
+    #[inline]
+    pub(crate) fn restore(&mut self) {
+        self.current = self.max as f32;
+    }
+
+    #[inline(always)]
+    pub(crate) fn is_full(&self) -> bool {
+        self.current >= self.max as f32
+    }
+}
+
+/// Protects the entity from damage until the timer finishes
+#[derive(Component, Deref, DerefMut)]
+pub(crate) struct Immunity(pub(crate) Timer);
+
+/// Makes the entity collide with the solid tiles when it moves
+#[derive(Component, Clone, Copy)]
+pub(crate) struct Collider {
+    /// The height of the ledges the entity walks onto without jumping
+    pub(crate) step_height: f32,
+}
+
+impl Default for Collider {
+    fn default() -> Self {
+        Self { step_height: TILE_SIZE / 2. }
+    }
+}
+
+/// The sides of an entity with a [`Collider`] which hit solid tiles during its last move
+#[derive(Component, Clone, Copy, Default)]
+pub(crate) struct Collisions {
+    pub(crate) top: bool,
+    pub(crate) bottom: bool,
+    pub(crate) left: bool,
+    pub(crate) right: bool,
+    /// The block the entity stands on, it's `None` in the air and at the bottom of the world
+    pub(crate) ground: Option<BlockType>,
+}
+
+impl Collisions {
+    /// The sum of the normals of the hit tile sides, it points away from the tiles
+    pub(crate) fn normal(&self) -> Vec2 {
+        vec2(
+            f32::from(self.left) - f32::from(self.right),
+            f32::from(self.bottom) - f32::from(self.top)
+        )
+    }
+}
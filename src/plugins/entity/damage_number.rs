@@ -0,0 +1,75 @@
+use std::time::Duration;
+
+use bevy::{prelude::{Commands, Component, Query, With, EventReader, Transform, Vec2, Vec3, Color, default}, text::{Text, TextStyle, TextAlignment, Text2dBundle}};
+use interpolation::EaseFunction;
+
+use crate::{
+    animation::{Tween, Animator, RepeatStrategy, TweenCompleted},
+    common::lens::TransformLens,
+    plugins::{DespawnOnGameExit, assets::FontAssets, world::WORLD_RENDER_LAYER}
+};
+
+const DAMAGE_NUMBER_LAYER: f32 = 20.;
+const DAMAGE_NUMBER_LIFETIME: Duration = Duration::from_millis(800);
+/// The distance the number floats up before it disappears
+const DAMAGE_NUMBER_RISE: f32 = 32.;
+
+#[derive(Component)]
+pub(super) struct DamageNumber;
+
+/// Spawns the amount of damage at the position which floats up and shrinks until it disappears.
+/// A critical hit is shown bigger.
+pub(crate) fn spawn_damage_number(
+    commands: &mut Commands,
+    fonts: &FontAssets,
+    position: Vec2,
+    damage: i32,
+    color: Color,
+    critical: bool
+) {
+    let scale = if critical { 1.3 } else { 1. };
+
+    let start = Transform::from_xyz(position.x, position.y, DAMAGE_NUMBER_LAYER)
+        .with_scale(Vec3::splat(scale));
+    let end = Transform::from_xyz(position.x, position.y + DAMAGE_NUMBER_RISE, DAMAGE_NUMBER_LAYER)
+        .with_scale(Vec3::splat(scale * 0.5));
+
+    let tween = Tween::new(
+        EaseFunction::QuadraticOut,
+        RepeatStrategy::Repeat,
+        DAMAGE_NUMBER_LIFETIME,
+        TransformLens { start, end }
+    )
+    .with_completed_event(0);
+
+    commands.spawn((
+        DamageNumber,
+        Text2dBundle {
+            text: Text::from_section(
+                damage.to_string(),
+                TextStyle {
+                    font: fonts.andy_bold.clone_weak(),
+                    font_size: 22.,
+                    color,
+                }
+            ).with_alignment(TextAlignment::Center),
+            transform: start,
+            ..default()
+        },
+        Animator::new(tween),
+        DespawnOnGameExit,
+        WORLD_RENDER_LAYER
+    ));
+}
+
+pub(super) fn despawn_damage_numbers(
+    mut commands: Commands,
+    mut tween_completed_events: EventReader<TweenCompleted>,
+    query_damage_number: Query<(), With<DamageNumber>>
+) {
+    for event in tween_completed_events.iter() {
+        if query_damage_number.contains(event.entity) {
+            commands.entity(event.entity).despawn();
+        }
+    }
+}
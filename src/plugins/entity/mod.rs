@@ -1,12 +1,22 @@
-use bevy::{prelude::{Plugin, App, SystemSet, FixedUpdate, IntoSystemSetConfigs, IntoSystemConfigs, Query, Res, Transform}, math::vec2};
+use bevy::{prelude::{Plugin, App, SystemSet, FixedUpdate, Update, IntoSystemSetConfigs, IntoSystemConfigs, Query, Res, Transform, Commands, Entity, Without}, math::vec2, time::Time};
 
-use crate::world::WorldData;
+use crate::{world::WorldData, common::rect::FRect};
 
-use self::components::{EntityRect, Velocity};
+use self::components::{EntityRect, Velocity, Immunity, Collider, Mana};
 
 use super::{InGameSystemSet, world::constants::TILE_SIZE};
 
+#[cfg(feature = "debug")]
+use crate::plugins::debug::DebugConfiguration;
+
 pub(crate) mod components;
+mod collisions;
+mod damage_number;
+mod projectile;
+
+pub(crate) use damage_number::spawn_damage_number;
+pub(crate) use projectile::{ProjectileBuilder, ProjectileCommandsExt};
+pub(crate) use crate::items::ProjectileDefinition;
 
 #[derive(SystemSet, Hash, PartialEq, Eq, Debug, Clone)]
 pub(crate) enum EntitySet {
@@ -30,36 +40,72 @@ impl Plugin for EntityPlugin {
         app.add_systems(
             FixedUpdate,
             (
-                update_entity_rect.in_set(EntitySet::UpdateEntityRect),
+                (update_entity_rect, collisions::move_colliders).in_set(EntitySet::UpdateEntityRect),
                 move_entity.in_set(EntitySet::MoveEntity),
             )
         );
+
+        app.add_systems(
+            FixedUpdate,
+            (
+                projectile::update_velocity.before(EntitySet::UpdateEntityRect),
+                (
+                    projectile::hit_entities,
+                    projectile::hit_tiles,
+                    projectile::rotate,
+                )
+                .chain()
+                .after(EntitySet::UpdateEntityRect),
+            )
+            .in_set(InGameSystemSet::FixedUpdate)
+        );
+
+        app.add_systems(
+            Update,
+            (
+                update_immunity,
+                regenerate_mana,
+                damage_number::despawn_damage_numbers,
+                projectile::update_lifetime,
+            )
+            .in_set(InGameSystemSet::Update)
+        );
+
+        #[cfg(feature = "debug")]
+        app.add_systems(
+            Update,
+            collisions::draw_collisions
+                .run_if(|config: Res<DebugConfiguration>| config.show_collisions)
+                .in_set(InGameSystemSet::Update)
+        );
     }
 }
 
 fn update_entity_rect(
     world_data: Res<WorldData>,
-    mut query: Query<(&mut EntityRect, &Velocity)>
+    mut query: Query<(&mut EntityRect, &Velocity), Without<Collider>>
 ) {
-    let playable_min_x = world_data.playable_area.min.x as f32 * TILE_SIZE;
-    let playable_max_x = world_data.playable_area.max.x as f32 * TILE_SIZE;
+    for (mut entity_rect, velocity) in &mut query {
+        entity_rect.centerx += velocity.x;
+        entity_rect.centery += velocity.y;
 
-    let playable_min_y = world_data.playable_area.min.y as f32 * TILE_SIZE;
-    let playable_max_y = world_data.playable_area.max.y as f32 * TILE_SIZE;
+        clamp_to_playable_area(&world_data, &mut entity_rect.0);
+    }
+}
 
-    for (mut entity_rect, velocity) in &mut query {
-        let min_x = playable_min_x + entity_rect.half_width();
-        let max_x = playable_max_x - entity_rect.half_width();
+fn clamp_to_playable_area(world_data: &WorldData, rect: &mut FRect) {
+    let playable_area = world_data.playable_area;
 
-        let min_y = -playable_max_y + entity_rect.half_height();
-        let max_y = -playable_min_y - entity_rect.half_height();
+    let min_x = playable_area.min.x as f32 * TILE_SIZE + rect.half_width();
+    let max_x = playable_area.max.x as f32 * TILE_SIZE - rect.half_width();
 
-        let new_position = (entity_rect.center() + velocity.0)
-            .clamp(vec2(min_x, min_y), vec2(max_x, max_y));
+    let min_y = -(playable_area.max.y as f32 * TILE_SIZE) + rect.half_height();
+    let max_y = -(playable_area.min.y as f32 * TILE_SIZE) - rect.half_height();
 
-        entity_rect.centerx = new_position.x;
-        entity_rect.centery = new_position.y;
-    }
+    let new_position = rect.center().clamp(vec2(min_x, min_y), vec2(max_x, max_y));
+
+    rect.centerx = new_position.x;
+    rect.centery = new_position.y;
 }
 
 fn move_entity(
@@ -69,4 +115,28 @@ fn move_entity(
         transform.translation.x = entity_rect.centerx;
         transform.translation.y = entity_rect.centery;
     });
-}
\ No newline at end of file
+}
+
+fn update_immunity(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut Immunity)>
+) {
+    for (entity, mut immunity) in &mut query {
+        if immunity.tick(time.delta()).finished() {
+            commands.entity(entity).remove::<Immunity>();
+        }
+    }
+}
+
+fn regenerate_mana(
+    time: Res<Time>,
+    mut query: Query<&mut Mana>
+) {
+    for mut mana in &mut query {
+        // Don't touch a full mana pool, so it isn't marked as changed every frame
+        if mana.is_full() { continue; }
+
+        mana.current = (mana.current + mana.regen * time.delta_seconds()).min(mana.max as f32);
+    }
+}
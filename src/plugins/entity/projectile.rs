@@ -0,0 +1,325 @@
+use std::{f32::consts::FRAC_PI_4, time::Duration};
+
+use bevy::{prelude::{Component, Commands, Query, Res, Entity, Transform, Handle, Image, Assets, World, Vec2, Quat, Name, With, Without, EventWriter, default}, sprite::SpriteBundle, ecs::system::Command, math::vec2, time::{Time, Timer, TimerMode}};
+use rand::{thread_rng, Rng};
+
+use crate::{
+    common::rect::FRect,
+    items::TileHit,
+    lighting::types::LightSource,
+    plugins::{DespawnOnGameExit, npc::{Npc, DamageNpcEvent}, player::{Player, DamagePlayerEvent, DamageSource, RespawnTimer}, world::WORLD_RENDER_LAYER},
+    PROJECTILE_LAYER
+};
+
+use super::{ProjectileDefinition, components::{EntityRect, Velocity, Collider, Collisions, Immunity}};
+
+#[derive(Component)]
+pub(super) struct Projectile {
+    damage: i32,
+    knockback: f32,
+    crit_chance: f32,
+    /// A hostile projectile hits the player instead of the NPCs
+    hostile: bool,
+    gravity: f32,
+    drag: f32,
+    tile_hit: TileHit,
+    piercing: u32,
+    spin: f32,
+    lifetime: Timer,
+    /// The velocity before the last move. The collision stops the projectile, so it's needed to bounce.
+    last_velocity: Vec2,
+    stuck: bool,
+    hit_entities: Vec<Entity>,
+}
+
+impl Projectile {
+    /// The entity which is hit is pushed in the direction the projectile flies in and a bit up
+    #[inline]
+    fn knockback(&self) -> Vec2 {
+        vec2(self.last_velocity.x.signum(), 0.5) * self.knockback
+    }
+}
+
+/// Sprites of the projectiles point up and right like the item sprites
+pub(crate) struct ProjectileBuilder {
+    texture: Handle<Image>,
+    position: Vec2,
+    velocity: Vec2,
+    damage: i32,
+    knockback: f32,
+    crit_chance: f32,
+    hostile: bool,
+    gravity: f32,
+    drag: f32,
+    tile_hit: TileHit,
+    piercing: u32,
+    lifetime: Duration,
+    spin: f32,
+    light_source: Option<LightSource>,
+}
+
+impl ProjectileBuilder {
+    pub(crate) fn new(texture: Handle<Image>, position: Vec2, velocity: Vec2, lifetime: Duration) -> Self {
+        Self {
+            texture,
+            position,
+            velocity,
+            damage: 0,
+            knockback: 0.,
+            crit_chance: 0.,
+            hostile: false,
+            gravity: 0.,
+            drag: 0.,
+            tile_hit: TileHit::Destroy,
+            piercing: 1,
+            lifetime,
+            spin: 0.,
+            light_source: None,
+        }
+    }
+
+    /// Launches the projectile described by `definition` in `direction`
+    pub(crate) fn from_definition(definition: &ProjectileDefinition, texture: Handle<Image>, position: Vec2, direction: Vec2) -> Self {
+        let velocity = direction.normalize_or_zero() * definition.speed;
+
+        let builder = Self::new(texture, position, velocity, Duration::from_secs_f32(definition.lifetime))
+            .with_gravity(definition.gravity)
+            .with_drag(definition.drag)
+            .with_tile_hit(definition.tile_hit)
+            .with_piercing(definition.piercing)
+            .with_spin(definition.spin);
+
+        match definition.light {
+            Some(light) => builder.with_light(light.light_source()),
+            None => builder,
+        }
+    }
+
+    pub(crate) fn with_damage(mut self, damage: i32, knockback: f32, crit_chance: f32) -> Self {
+        self.damage = damage;
+        self.knockback = knockback;
+        self.crit_chance = crit_chance;
+        self
+    }
+
+    pub(crate) fn hostile(mut self) -> Self {
+        self.hostile = true;
+        self
+    }
+
+    pub(crate) fn with_gravity(mut self, gravity: f32) -> Self {
+        self.gravity = gravity;
+        self
+    }
+
+    pub(crate) fn with_drag(mut self, drag: f32) -> Self {
+        self.drag = drag;
+        self
+    }
+
+    pub(crate) fn with_tile_hit(mut self, tile_hit: TileHit) -> Self {
+        self.tile_hit = tile_hit;
+        self
+    }
+
+    pub(crate) fn with_piercing(mut self, piercing: u32) -> Self {
+        self.piercing = piercing;
+        self
+    }
+
+    pub(crate) fn with_spin(mut self, spin: f32) -> Self {
+        self.spin = spin;
+        self
+    }
+
+    pub(crate) fn with_light(mut self, light_source: LightSource) -> Self {
+        self.light_source = Some(light_source);
+        self
+    }
+}
+
+struct SpawnProjectileCommand(ProjectileBuilder);
+
+impl Command for SpawnProjectileCommand {
+    fn apply(self, world: &mut World) {
+        let builder = self.0;
+
+        let images = world.resource::<Assets<Image>>();
+
+        // The hitbox is a square, so it fits the sprite at any angle
+        let size = images.get(&builder.texture).map_or(8., |image| image.size().min_element());
+
+        let mut entity = world.spawn((
+            Name::new("Projectile"),
+            SpriteBundle {
+                texture: builder.texture,
+                transform: Transform::from_xyz(builder.position.x, builder.position.y, PROJECTILE_LAYER)
+                    .with_rotation(velocity_rotation(builder.velocity)),
+                ..default()
+            },
+            Projectile {
+                damage: builder.damage,
+                knockback: builder.knockback,
+                crit_chance: builder.crit_chance,
+                hostile: builder.hostile,
+                gravity: builder.gravity,
+                drag: builder.drag,
+                tile_hit: builder.tile_hit,
+                piercing: builder.piercing,
+                spin: builder.spin,
+                lifetime: Timer::new(builder.lifetime, TimerMode::Once),
+                last_velocity: builder.velocity,
+                stuck: false,
+                hit_entities: Vec::new(),
+            },
+            EntityRect(FRect::new_center(builder.position.x, builder.position.y, size, size)),
+            Velocity(builder.velocity),
+            // Projectiles don't walk onto ledges
+            Collider { step_height: 0. },
+            Collisions::default(),
+            DespawnOnGameExit,
+            WORLD_RENDER_LAYER
+        ));
+
+        if let Some(light_source) = builder.light_source {
+            entity.insert(light_source);
+        }
+    }
+}
+
+pub(crate) trait ProjectileCommandsExt {
+    fn spawn_projectile(&mut self, projectile_builder: ProjectileBuilder);
+}
+
+impl ProjectileCommandsExt for Commands<'_, '_> {
+    fn spawn_projectile(&mut self, projectile_builder: ProjectileBuilder) {
+        self.add(SpawnProjectileCommand(projectile_builder));
+    }
+}
+
+#[inline]
+fn velocity_rotation(velocity: Vec2) -> Quat {
+    Quat::from_rotation_z(velocity.y.atan2(velocity.x) - FRAC_PI_4)
+}
+
+pub(super) fn update_velocity(
+    mut query: Query<(&mut Projectile, &mut Velocity)>
+) {
+    for (mut projectile, mut velocity) in &mut query {
+        if projectile.stuck { continue; }
+
+        velocity.y -= projectile.gravity;
+        velocity.0 *= 1. - projectile.drag;
+
+        projectile.last_velocity = velocity.0;
+    }
+}
+
+pub(super) fn hit_entities(
+    mut commands: Commands,
+    respawn_timer: Option<Res<RespawnTimer>>,
+    query_player: Query<&EntityRect, (With<Player>, Without<Immunity>)>,
+    query_npcs: Query<(Entity, &EntityRect), With<Npc>>,
+    mut query_projectiles: Query<(Entity, &mut Projectile, &EntityRect)>,
+    mut damage_npc_events: EventWriter<DamageNpcEvent>,
+    mut damage_player_events: EventWriter<DamagePlayerEvent>,
+) {
+    let mut rng = thread_rng();
+
+    for (entity, mut projectile, projectile_rect) in &mut query_projectiles {
+        if projectile.stuck || projectile.damage <= 0 { continue; }
+
+        if projectile.hostile {
+            if respawn_timer.is_some() { continue; }
+
+            let Ok(player_rect) = query_player.get_single() else { continue; };
+            if !projectile_rect.intersects(player_rect) { continue; }
+
+            damage_player_events.send(DamagePlayerEvent {
+                damage: projectile.damage,
+                knockback: Some(projectile.knockback()),
+                source: DamageSource::Projectile,
+            });
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        for (npc_entity, npc_rect) in &query_npcs {
+            if projectile.hit_entities.contains(&npc_entity) || !projectile_rect.intersects(npc_rect) { continue; }
+
+            projectile.hit_entities.push(npc_entity);
+
+            let critical = rng.gen_bool(projectile.crit_chance as f64);
+            let damage = if critical { projectile.damage * 2 } else { projectile.damage };
+
+            damage_npc_events.send(DamageNpcEvent {
+                entity: npc_entity,
+                damage,
+                knockback: projectile.knockback(),
+                critical,
+            });
+
+            projectile.piercing -= 1;
+            if projectile.piercing == 0 {
+                commands.entity(entity).despawn();
+                break;
+            }
+        }
+    }
+}
+
+pub(super) fn hit_tiles(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut Projectile, &mut Velocity, &Collisions)>
+) {
+    for (entity, mut projectile, mut velocity, collisions) in &mut query {
+        let normal = collisions.normal();
+        if projectile.stuck || normal == Vec2::ZERO { continue; }
+
+        match projectile.tile_hit {
+            TileHit::Destroy => commands.entity(entity).despawn(),
+            TileHit::Stick => {
+                projectile.stuck = true;
+                velocity.0 = Vec2::ZERO;
+                commands.entity(entity).remove::<Collider>();
+            },
+            TileHit::Bounce(restitution) => {
+                if normal.x != 0. {
+                    velocity.x = -projectile.last_velocity.x * restitution;
+                }
+
+                // Stop bouncing on the ground when the bounce is too small to be seen
+                if normal.y != 0. {
+                    let bounce = -projectile.last_velocity.y * restitution;
+                    velocity.y = if bounce.abs() > projectile.gravity * 2. { bounce } else { 0. };
+                }
+            }
+        }
+    }
+}
+
+pub(super) fn rotate(
+    mut query: Query<(&mut Transform, &Projectile, &Velocity)>
+) {
+    for (mut transform, projectile, velocity) in &mut query {
+        if projectile.stuck { continue; }
+
+        if projectile.spin != 0. {
+            transform.rotate_z(projectile.spin * -projectile.last_velocity.x.signum());
+        } else if velocity.0 != Vec2::ZERO {
+            transform.rotation = velocity_rotation(velocity.0);
+        }
+    }
+}
+
+pub(super) fn update_lifetime(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut Projectile)>
+) {
+    for (entity, mut projectile) in &mut query {
+        if projectile.lifetime.tick(time.delta()).finished() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
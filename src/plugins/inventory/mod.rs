@@ -3,18 +3,42 @@ mod resources;
 mod systems;
 mod util;
 
-use bevy::{prelude::{Plugin, App, IntoSystemConfigs, Update, FixedUpdate, OnExit, Commands, resource_exists_and_changed, resource_exists_and_equals, Vec2, not, resource_equals}, math::vec2};
+use bevy::{prelude::{Plugin, App, IntoSystemConfigs, Update, FixedUpdate, OnExit, Commands, resource_exists_and_changed, resource_exists_and_equals, Vec2, not, resource_equals, resource_exists, Res, UVec2, MouseButton}, math::vec2, input::common_conditions::input_just_pressed};
 pub(crate) use components::*;
 pub(crate) use resources::*;
 
-use crate::{common::{state::GameState, conditions::{mouse_over_ui, is_visible}}, items::{ItemStack, ItemTool, Axe, Pickaxe, ItemSeed, ItemBlock, Hammer, ItemWall}};
+use crate::{common::{state::GameState, conditions::{mouse_over_ui, is_visible}}, items::{ItemStack, Item, SlotContainer}};
 
-use super::{InGameSystemSet, world_map_view::MapViewStatus, ui::resources::Ui};
+use super::{InGameSystemSet, world_map_view::MapViewStatus, ui::resources::Ui, profile::PlayerProfile, player::RespawnTimer};
 
 const ITEM_ROTATION: f32 = 1.7;
 
 const ITEM_ANIMATION_POINTS: [Vec2; 3] = [vec2(-7.5, 11.0), vec2(6.0, 7.5), vec2(7.0, -4.0)];
 
+/// Ids of the items a new player starts with and whether they come as a full stack
+const STARTING_ITEMS: [(&str, bool); 14] = [
+    ("CopperBroadsword", false),
+    ("CopperPickaxe", false),
+    ("CopperAxe", false),
+    ("CopperHammer", false),
+    ("DirtBlock", true),
+    ("StoneBlock", true),
+    ("Wood", true),
+    ("GrassSeeds", true),
+    ("DirtWall", true),
+    ("StoneWall", true),
+    ("Torch", true),
+    ("WorkBench", false),
+    ("Chest", false),
+    ("WoodenDoor", false),
+];
+
+/// How far in tiles a crafting station can be from the player
+const CRAFTING_RANGE: UVec2 = UVec2::new(4, 3);
+
+/// How far in tiles an open chest can be from the player
+const CHEST_RANGE: UVec2 = UVec2::new(5, 4);
+
 pub struct PlayerInventoryPlugin;
 impl Plugin for PlayerInventoryPlugin {
     fn build(&self, app: &mut App) {
@@ -23,7 +47,9 @@ impl Plugin for PlayerInventoryPlugin {
 
         app.add_systems(
             FixedUpdate,
-            systems::use_item.in_set(InGameSystemSet::FixedUpdate)
+            systems::use_item
+                .run_if(not(resource_exists::<RespawnTimer>()))
+                .in_set(InGameSystemSet::FixedUpdate)
         );
 
         app.add_systems(
@@ -36,6 +62,7 @@ impl Plugin for PlayerInventoryPlugin {
                     systems::update_sprite_index,
                     systems::set_using_item_position,
                     systems::set_using_item_rotation,
+                    systems::hit_with_weapon,
                     systems::set_using_item_visibility(true),
                     systems::reset_swing_animation,
                 )
@@ -54,6 +81,7 @@ impl Plugin for PlayerInventoryPlugin {
                 (
                     systems::update_player_using_item
                         .run_if(not(mouse_over_ui))
+                        .run_if(not(resource_exists::<RespawnTimer>()))
                         .run_if(resource_equals(MapViewStatus::Closed)),
                     systems::start_swing_animation
                 ).chain(),
@@ -67,40 +95,62 @@ impl Plugin for PlayerInventoryPlugin {
                 systems::set_using_item_visibility(false),
 
                 systems::drop_item_stack,
+                systems::update_nearby_crafting_stations,
+
+                (
+                    systems::open_chest
+                        .run_if(input_just_pressed(MouseButton::Right))
+                        .run_if(not(mouse_over_ui))
+                        .run_if(not(resource_exists::<RespawnTimer>()))
+                        .run_if(resource_equals(MapViewStatus::Closed)),
+                    systems::update_open_chest.run_if(resource_exists::<OpenChest>()),
+                ).chain(),
             )
             .in_set(InGameSystemSet::Update)
         );
     }
 }
 
-fn setup(mut commands: Commands) {
+fn setup(mut commands: Commands, profile: Option<Res<PlayerProfile>>) {
     commands.init_resource::<SelectedItem>();
     commands.init_resource::<SwingItemCooldown>();
     commands.init_resource::<SwingItemCooldownMax>();
+    commands.init_resource::<NearbyCraftingStations>();
     commands.insert_resource(UseItemAnimationIndex::default());
     commands.insert_resource(PlayerUsingItem(false));
     commands.insert_resource(SwingAnimation(false));
+
+    if let Some(inventory) = profile.and_then(|profile| profile.inventory.clone()) {
+        commands.insert_resource(Inventory::from(inventory));
+        return;
+    }
     
     let mut inventory = Inventory::default();
-    inventory.add_item_stack(ItemStack::new_tool(ItemTool::Pickaxe(Pickaxe::CopperPickaxe)));
-    inventory.add_item_stack(ItemStack::new_tool(ItemTool::Axe(Axe::CopperAxe)));
-    inventory.add_item_stack(ItemStack::new_tool(ItemTool::Hammer(Hammer::CopperHammer)));
-    inventory.add_item_stack(ItemStack::new_block(ItemBlock::Dirt).with_max_stack());
-    inventory.add_item_stack(ItemStack::new_block(ItemBlock::Stone).with_max_stack());
-    inventory.add_item_stack(ItemStack::new_block(ItemBlock::Wood).with_max_stack());
-    inventory.add_item_stack(ItemStack::new_seed(ItemSeed::Grass).with_max_stack());
-    inventory.add_item_stack(ItemStack::new_wall(ItemWall::Dirt).with_max_stack());
-    inventory.add_item_stack(ItemStack::new_wall(ItemWall::Stone).with_max_stack());
+    for (id, full_stack) in STARTING_ITEMS {
+        let item = Item::from_id(id).expect("The starting items are validated on startup");
+        let item_stack = ItemStack::new(item);
+        inventory.add_item_stack(if full_stack { item_stack.with_max_stack() } else { item_stack });
+    }
 
     commands.insert_resource(inventory);
 }
 
+/// Checks that all starting items exist in the item registry
+pub(crate) fn validate_starting_items() -> Result<(), String> {
+    match STARTING_ITEMS.iter().find(|(id, _)| Item::from_id(id).is_none()) {
+        Some((id, _)) => Err(format!("The starting item \"{}\" doesn't exist", id)),
+        None => Ok(())
+    }
+}
+
 fn cleanup(mut commands: Commands) {
     commands.remove_resource::<SelectedItem>();
     commands.remove_resource::<SwingItemCooldown>();
     commands.remove_resource::<SwingItemCooldownMax>();
+    commands.remove_resource::<NearbyCraftingStations>();
     commands.remove_resource::<UseItemAnimationIndex>();
     commands.remove_resource::<PlayerUsingItem>();
     commands.remove_resource::<SwingAnimation>();
     commands.remove_resource::<Inventory>();
+    commands.remove_resource::<OpenChest>();
 }
\ No newline at end of file
@@ -1,6 +1,10 @@
-use bevy::{prelude::{Resource, Deref, DerefMut, ReflectResource}, reflect::Reflect};
+use std::cmp::Reverse;
 
-use crate::{items::{ItemStack, Stack}, plugins::ui::ingame::inventory::SLOT_COUNT_IN_ROW};
+use bevy::{prelude::{Resource, Deref, DerefMut, ReflectResource}, reflect::Reflect, utils::HashSet};
+use bevy_ecs_tilemap::tiles::TilePos;
+use serde::{Deserialize, Serialize};
+
+use crate::{items::{ItemStack, Stack, Item, ItemBehaviour, Recipe, Recipes, CraftingStation, SlotContainer, ChestContents, CHEST_SLOT_COUNT, transfer_items}, plugins::ui::ingame::inventory::SLOT_COUNT_IN_ROW};
 
 #[derive(Resource, Default, Deref, DerefMut)]
 pub(crate) struct SelectedItem(pub Option<ItemStack>);
@@ -21,10 +25,25 @@ pub(crate) struct SwingAnimation(pub bool);
 #[reflect(Resource)]
 pub(crate) struct UseItemAnimationIndex(usize);
 
+/// The crafting stations which are in reach of the player
+#[derive(Resource, Default, PartialEq, Deref, DerefMut)]
+pub(crate) struct NearbyCraftingStations(pub HashSet<CraftingStation>);
+
+/// The chest which the player has opened.
+/// Its contents are written back into the world whenever they change.
+#[derive(Resource)]
+pub(crate) struct OpenChest {
+    /// Position of the top left tile of the chest
+    pub(crate) origin: TilePos,
+    pub(crate) contents: ChestContents,
+}
+
 #[derive(Clone, Copy)]
 pub(crate) enum Slot {
     Index(usize),
-    MouseItem
+    MouseItem,
+    /// Holds the last trashed stack until another one replaces it
+    Trash
 }
 
 #[derive(Resource)]
@@ -33,6 +52,9 @@ pub(crate) struct Inventory {
     pub(crate) selected_slot: usize,
     pub(crate) previous_selected_slot: usize,
     mouse_item: Option<ItemStack>,
+    trash: Option<ItemStack>,
+    /// Favourite slots are skipped when the inventory is sorted or its items are dropped or deposited
+    favourites: [bool; 50],
 }
 
 impl Default for Inventory {
@@ -41,11 +63,88 @@ impl Default for Inventory {
             slots: [None; 50],
             selected_slot: 0,
             previous_selected_slot: 0,
-            mouse_item: None
+            mouse_item: None,
+            trash: None,
+            favourites: [false; 50],
         }
     }
 }
 
+/// Serializable representation of the [`Inventory`]
+#[derive(Serialize, Deserialize, Clone)]
+pub(crate) struct InventoryData {
+    pub(crate) slots: Vec<Option<ItemStack>>,
+    pub(crate) mouse_item: Option<ItemStack>,
+    #[serde(default)]
+    pub(crate) trash: Option<ItemStack>,
+    pub(crate) selected_slot: usize,
+    /// Indices of the favourite slots
+    #[serde(default)]
+    pub(crate) favourites: Vec<usize>,
+}
+
+impl From<&Inventory> for InventoryData {
+    fn from(inventory: &Inventory) -> Self {
+        Self {
+            slots: inventory.slots.to_vec(),
+            mouse_item: inventory.mouse_item,
+            trash: inventory.trash,
+            selected_slot: inventory.selected_slot,
+            favourites: (0..inventory.slots.len()).filter(|&index| inventory.is_favourite(index)).collect(),
+        }
+    }
+}
+
+impl From<InventoryData> for Inventory {
+    fn from(data: InventoryData) -> Self {
+        let mut inventory = Inventory::default();
+
+        for (slot, item_stack) in inventory.slots.iter_mut().zip(data.slots) {
+            *slot = item_stack;
+        }
+
+        inventory.mouse_item = data.mouse_item;
+        inventory.trash = data.trash;
+
+        for index in data.favourites {
+            if index < inventory.favourites.len() {
+                inventory.favourites[index] = true;
+            }
+        }
+
+        if data.selected_slot < SLOT_COUNT_IN_ROW {
+            inventory.selected_slot = data.selected_slot;
+        }
+
+        inventory
+    }
+}
+
+/// Position of the category of the item in a sorted inventory
+fn category_order(item: Item) -> u8 {
+    match item.behaviour() {
+        ItemBehaviour::Weapon(_) => 0,
+        ItemBehaviour::Tool(_) => 1,
+        ItemBehaviour::Block(_) => 2,
+        ItemBehaviour::Wall(_) => 3,
+        ItemBehaviour::Furniture(_) => 4,
+        ItemBehaviour::Seed(_) => 5,
+        ItemBehaviour::Material => 6,
+    }
+}
+
+impl SlotContainer for Inventory {
+    #[inline]
+    fn slots(&self) -> &[Option<ItemStack>] {
+        &self.slots
+    }
+
+    #[inline]
+    fn slots_mut(&mut self) -> &mut [Option<ItemStack>] {
+        &mut self.slots
+    }
+}
+
 impl Inventory {
     pub fn get_item(&self, slot: Slot) -> Option<ItemStack> {
         match slot {
@@ -56,6 +155,9 @@ impl Inventory {
             Slot::MouseItem => {
                 self.mouse_item
             },
+            Slot::Trash => {
+                self.trash
+            },
         }
     }
 
@@ -66,6 +168,7 @@ impl Inventory {
                 self.slots[index].as_mut()
             },
             Slot::MouseItem => self.mouse_item.as_mut(),
+            Slot::Trash => self.trash.as_mut(),
         }
     }
 
@@ -78,6 +181,9 @@ impl Inventory {
             Slot::MouseItem => {
                 self.mouse_item = Some(item_stack);
             },
+            Slot::Trash => {
+                self.trash = Some(item_stack);
+            },
         }
     }
 
@@ -89,11 +195,16 @@ impl Inventory {
                 debug_assert!((0..50).contains(&index));
                 item = self.slots[index];
                 self.slots[index] = None;
+                self.favourites[index] = false;
             },
             Slot::MouseItem => {
                 item = self.mouse_item;
                 self.mouse_item = None;
             },
+            Slot::Trash => {
+                item = self.trash;
+                self.trash = None;
+            },
         }
 
         item
@@ -134,6 +245,9 @@ impl Inventory {
             Slot::MouseItem => {
                 self.mouse_item.is_some()
             },
+            Slot::Trash => {
+                self.trash.is_some()
+            },
         }
     }
 
@@ -150,63 +264,314 @@ impl Inventory {
         Some(item_copy.with_stack(stack))
     }
 
-    // Returns the amount of items added to the inventory
-    pub fn add_item_stack(&mut self, new_item: ItemStack) -> u16 {
-        let mut remaining = new_item.stack;
-
-        for inv_item_option in self.slots.iter_mut() {
-            match inv_item_option {
-                Some(inv_item) if inv_item.item == new_item.item && inv_item.stack < inv_item.item.max_stack() => {
-                    let new_stack = inv_item.stack + remaining;
-
-                    if new_stack <= inv_item.item.max_stack() {
-                        inv_item.stack = new_stack;
-                        return new_item.stack;
-                    } else {
-                        inv_item.stack += remaining % inv_item.item.max_stack();
-                        remaining -= remaining % inv_item.item.max_stack();
-                    }
-                },
-                None => {
-                    *inv_item_option = Some(new_item);
-                    return new_item.stack;
-                },
-                _ => continue
-            }
+    /// Removes all items including the one held by the mouse and returns them
+    pub fn take_all_items(&mut self) -> Vec<ItemStack> {
+        self.favourites = [false; 50];
+
+        let mut items = self.take_items();
+        items.extend(self.mouse_item.take());
+        items
+    }
+
+    /// Removes the whole stack in the slot and returns it, a favourite item is never dropped
+    pub fn drop_item(&mut self, slot: Slot) -> Option<ItemStack> {
+        if let Slot::Index(index) = slot {
+            if self.is_favourite(index) { return None; }
+        }
+
+        let item_stack = self.get_item(slot)?;
+        self.consume_item_impl(slot, item_stack.stack)
+    }
+
+    pub fn has_ingredients(&self, recipe: &Recipe) -> bool {
+        recipe.ingredients
+            .iter()
+            .all(|ingredient| self.count_item(ingredient.item) >= ingredient.stack as u32)
+    }
+
+    /// Returns the indices of the recipes which have all ingredients in the inventory and their station nearby
+    pub fn craftable_recipes<'a>(
+        &'a self,
+        recipes: &'a Recipes,
+        stations: &'a HashSet<CraftingStation>
+    ) -> impl Iterator<Item = usize> + 'a {
+        recipes
+            .iter()
+            .enumerate()
+            .filter(|(_, recipe)| recipe.station_is_available(stations) && self.has_ingredients(recipe))
+            .map(|(index, _)| index)
+    }
+
+    /// Consumes the ingredients of the recipe and puts the output into the mouse item.
+    /// Returns `false` without changing the inventory if an ingredient is missing
+    /// or the mouse item can't be stacked with the output.
+    pub fn craft(&mut self, recipe: &Recipe) -> bool {
+        if !self.has_ingredients(recipe) { return false; }
+
+        let output = match self.mouse_item {
+            None => recipe.output,
+            Some(mouse_item) if mouse_item.item == recipe.output.item
+                && mouse_item.stack + recipe.output.stack <= mouse_item.item.max_stack() => {
+                mouse_item.with_stack(mouse_item.stack + recipe.output.stack)
+            },
+            Some(_) => return false
+        };
+
+        for ingredient in &recipe.ingredients {
+            self.remove_item_amount(ingredient.item, ingredient.stack);
+        }
+
+        self.mouse_item = Some(output);
+
+        true
+    }
+
+    /// Moves all items of the chest into the inventory
+    pub fn loot_all(&mut self, chest: &mut ChestContents) -> bool {
+        transfer_items(chest, 0..CHEST_SLOT_COUNT, self, |_, _| true)
+    }
+
+    /// Moves all items except the ones in the hotbar and the favourite ones into the chest
+    pub fn deposit_all(&mut self, chest: &mut ChestContents) -> bool {
+        let slots = SLOT_COUNT_IN_ROW..self.slots.len();
+        let favourites = self.favourites;
+        transfer_items(self, slots, chest, |index, _| !favourites[index])
+    }
+
+    /// Moves the items which the chest already has into it, the hotbar and the favourite items are left untouched
+    pub fn quick_stack(&mut self, chest: &mut ChestContents) -> bool {
+        let slots = SLOT_COUNT_IN_ROW..self.slots.len();
+        let favourites = self.favourites;
+        let stored = *chest;
+        transfer_items(self, slots, chest, |index, item_stack| !favourites[index] && stored.contains(item_stack.item))
+    }
+
+    /// Moves the whole stack in the slot into the trash, the stack which was trashed before is deleted.
+    /// A favourite item can't be trashed.
+    pub fn trash_item(&mut self, slot: Slot) -> bool {
+        if let Slot::Index(index) = slot {
+            if self.is_favourite(index) { return false; }
         }
 
-        0
+        let Some(item_stack) = self.remove_item(slot) else { return false; };
+        self.trash = Some(item_stack);
+
+        true
     }
 
-    pub fn can_be_added(&self, new_item: ItemStack) -> bool {
-        let mut remaining = new_item.stack;
+    /// Moves the trashed stack back into the inventory, the items which don't fit stay in the trash
+    pub fn restore_trash(&mut self) -> bool {
+        let Some(item_stack) = self.trash else { return false; };
+
+        let added = self.add_item_stack(item_stack);
+        if added == 0 { return false; }
 
-        for item_option in self.slots.iter() {
-            let Some(item_stack) = item_option else { return true; };
+        self.trash = (added < item_stack.stack).then(|| item_stack.with_stack(item_stack.stack - added));
 
-            if remaining == 0 { return true; }
+        true
+    }
+
+    #[inline]
+    pub fn is_favourite(&self, index: usize) -> bool {
+        self.favourites[index] && self.slots[index].is_some()
+    }
 
-            if new_item.item != item_stack.item { continue; }
-            if item_stack.stack == item_stack.item.max_stack() { continue; }
+    /// Marks the item in the slot as favourite or unmarks it. Returns `false` if the slot is empty.
+    pub fn toggle_favourite(&mut self, index: usize) -> bool {
+        if self.slots[index].is_none() { return false; }
 
-            let new_stack = item_stack.stack + remaining;
+        self.favourites[index] = !self.favourites[index];
+
+        true
+    }
+
+    /// Takes the amount of the item out of the slot and returns it
+    pub fn split_item(&mut self, slot: Slot, stack: Stack) -> Option<ItemStack> {
+        self.consume_item_impl(slot, stack)
+    }
 
-            if new_stack <= item_stack.item.max_stack() {
-                return true;
-            } else {
-                remaining -= remaining % item_stack.item.max_stack();
+    /// Moves the stack in the slot to the hotbar if it's in the main inventory and the other way round.
+    /// The stack is merged with the stacks of the same item first. Returns `false` if nothing was moved.
+    pub fn quick_move(&mut self, index: usize) -> bool {
+        let Some(item_stack) = self.slots[index] else { return false; };
+
+        let target = if index < SLOT_COUNT_IN_ROW {
+            SLOT_COUNT_IN_ROW..self.slots.len()
+        } else {
+            0..SLOT_COUNT_IN_ROW
+        };
+
+        let added = self.add_item_stack_in(item_stack, target);
+        if added == 0 { return false; }
+
+        if added == item_stack.stack {
+            self.remove_item(Slot::Index(index));
+        } else {
+            self.slots[index] = Some(item_stack.with_stack(item_stack.stack - added));
+        }
+
+        true
+    }
+
+    /// Merges the stacks of the same item and orders the main inventory by the item category.
+    /// The same items are put next to each other with the biggest stack first.
+    /// The hotbar and the favourite slots keep their items.
+    pub fn sort(&mut self) {
+        let sortable: Vec<usize> = (SLOT_COUNT_IN_ROW..self.slots.len())
+            .filter(|&index| !self.is_favourite(index))
+            .collect();
+
+        let mut items: Vec<ItemStack> = Vec::with_capacity(sortable.len());
+
+        for item_stack in sortable.iter().filter_map(|&index| self.slots[index].take()) {
+            let max_stack = item_stack.item.max_stack();
+            let mut remaining = item_stack.stack;
+
+            for stored in items.iter_mut().filter(|stored| stored.item == item_stack.item) {
+                let added = max_stack.saturating_sub(stored.stack).min(remaining);
+                stored.stack += added;
+                remaining -= added;
+            }
+
+            if remaining > 0 {
+                items.push(item_stack.with_stack(remaining));
             }
         }
 
-        false
+        items.sort_by_key(|item_stack| (category_order(item_stack.item), item_stack.item.index(), Reverse(item_stack.stack)));
+
+        for (i, &index) in sortable.iter().enumerate() {
+            // An empty slot can still have a stale favourite flag
+            self.favourites[index] = false;
+            self.slots[index] = items.get(i).copied();
+        }
     }
 
-    pub fn drop_item(&mut self, slot: Slot) -> Option<ItemStack> {
-        let item_stack = self.get_item(slot)?;
-        self.consume_item_impl(slot, item_stack.stack)
+    /// Removes the amount of the item starting from the last slot, so the hotbar is emptied last
+    fn remove_item_amount(&mut self, item: Item, mut amount: Stack) {
+        for index in (0..self.slots.len()).rev() {
+            if amount == 0 { break; }
+
+            let Some(item_stack) = self.slots[index] else { continue; };
+            if item_stack.item != item { continue; }
+
+            let removed = item_stack.stack.min(amount);
+            self.consume_item_impl(Slot::Index(index), removed);
+            amount -= removed;
+        }
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::load_registries;
+
+    use super::*;
+
+    fn item_stack(id: &str, stack: Stack) -> ItemStack {
+        ItemStack::new(Item::from_id(id).unwrap()).with_stack(stack)
+    }
+
+    /// The id and the stack of the item in the slot, so the slots can be compared in asserts
+    fn slot(inventory: &Inventory, index: usize) -> Option<(&'static str, Stack)> {
+        inventory.slots[index].map(|item_stack| (item_stack.item.definition().id.as_str(), item_stack.stack))
+    }
+
+    fn max_stack(id: &str) -> Stack {
+        Item::from_id(id).unwrap().max_stack()
+    }
+
+    #[test]
+    fn merges_into_partial_stacks() {
+        load_registries();
+
+        let max = max_stack("DirtBlock");
+        let mut inventory = Inventory::default();
+        inventory.slots[3] = Some(item_stack("DirtBlock", max - 5));
+        inventory.slots[7] = Some(item_stack("DirtBlock", max - 2));
+
+        assert_eq!(inventory.add_item_stack(item_stack("DirtBlock", 10)), 10);
+
+        assert_eq!(slot(&inventory, 3), Some(("DirtBlock", max)));
+        assert_eq!(slot(&inventory, 7), Some(("DirtBlock", max)));
+        // The rest goes into the first empty slot
+        assert_eq!(slot(&inventory, 0), Some(("DirtBlock", 3)));
+    }
+
+    #[test]
+    fn adds_what_fits_into_a_full_inventory() {
+        load_registries();
+
+        let max = max_stack("DirtBlock");
+        let mut inventory = Inventory::default();
+        inventory.slots.fill(Some(item_stack("CopperPickaxe", 1)));
+        inventory.slots[12] = Some(item_stack("DirtBlock", max - 4));
+
+        assert_eq!(inventory.add_item_stack(item_stack("DirtBlock", 10)), 4);
+        assert_eq!(slot(&inventory, 12), Some(("DirtBlock", max)));
+        assert_eq!(inventory.count_item(Item::from_id("DirtBlock").unwrap()), max as u32);
+    }
+
+    #[test]
+    fn consuming_the_whole_stack_empties_the_slot() {
+        load_registries();
+
+        let mut inventory = Inventory::default();
+        inventory.slots[2] = Some(item_stack("Wood", 3));
+        inventory.toggle_favourite(2);
+
+        let consumed = inventory.consume_item_impl(Slot::Index(2), 1).unwrap();
+        assert_eq!(consumed.stack, 1);
+        assert_eq!(slot(&inventory, 2), Some(("Wood", 2)));
+
+        let consumed = inventory.consume_item_impl(Slot::Index(2), 2).unwrap();
+        assert_eq!(consumed.stack, 2);
+        assert_eq!(slot(&inventory, 2), None);
+        assert!(!inventory.favourites[2]);
+
+        assert!(inventory.consume_item_impl(Slot::Index(2), 1).is_none());
+    }
+
+    #[test]
+    fn sort_keeps_the_hotbar_and_the_favourites() {
+        load_registries();
+
+        let mut inventory = Inventory::default();
+        inventory.slots[0] = Some(item_stack("DirtBlock", 5));
+        inventory.slots[15] = Some(item_stack("Wood", 4));
+        inventory.toggle_favourite(15);
+        inventory.slots[12] = Some(item_stack("DirtBlock", 3));
+        inventory.slots[30] = Some(item_stack("CopperPickaxe", 1));
+        inventory.slots[40] = Some(item_stack("DirtBlock", 2));
+
+        inventory.sort();
+
+        assert_eq!(slot(&inventory, 0), Some(("DirtBlock", 5)));
+        assert_eq!(slot(&inventory, 15), Some(("Wood", 4)));
+        assert!(inventory.is_favourite(15));
+
+        // The tools come before the blocks and the same items are merged
+        assert_eq!(slot(&inventory, SLOT_COUNT_IN_ROW), Some(("CopperPickaxe", 1)));
+        assert_eq!(slot(&inventory, SLOT_COUNT_IN_ROW + 1), Some(("DirtBlock", 5)));
+        assert!((SLOT_COUNT_IN_ROW + 2..inventory.slots.len()).filter(|&index| index != 15).all(|index| slot(&inventory, index).is_none()));
+    }
+
+    #[test]
+    fn quick_move_leaves_what_doesnt_fit() {
+        load_registries();
+
+        let max = max_stack("DirtBlock");
+        let mut inventory = Inventory::default();
+        inventory.slots[..SLOT_COUNT_IN_ROW].fill(Some(item_stack("CopperPickaxe", 1)));
+        inventory.slots[4] = Some(item_stack("DirtBlock", max - 4));
+        inventory.slots[20] = Some(item_stack("DirtBlock", 10));
+
+        assert!(inventory.quick_move(20));
+        assert_eq!(slot(&inventory, 4), Some(("DirtBlock", max)));
+        assert_eq!(slot(&inventory, 20), Some(("DirtBlock", 6)));
 
-    pub fn empty_slots_count(&self) -> u8 {
-        self.slots.iter().filter(|slot| slot.is_none()).count() as u8
+        // Nothing fits into the hotbar anymore
+        assert!(!inventory.quick_move(20));
+        assert_eq!(slot(&inventory, 20), Some(("DirtBlock", 6)));
     }
 }
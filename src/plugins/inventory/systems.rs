@@ -1,13 +1,15 @@
 use std::time::Duration;
 
-use bevy::{prelude::{ResMut, EventReader, KeyCode, Input, Res, With, Query, Visibility, Handle, Image, MouseButton, EventWriter, DetectChanges, Local, Transform, Quat, Commands, Vec2}, input::mouse::MouseWheel, sprite::TextureAtlasSprite, time::{Timer, TimerMode}};
+use bevy::{prelude::{ResMut, EventReader, KeyCode, Input, Res, With, Query, Visibility, Handle, Image, Assets, MouseButton, EventWriter, DetectChanges, Local, Transform, Quat, Commands, Vec2, Entity, DetectChangesMut}, input::mouse::MouseWheel, sprite::TextureAtlasSprite, time::{Timer, TimerMode}, math::vec2};
+use bevy_ecs_tilemap::tiles::TilePos;
+use rand::{thread_rng, Rng};
 
 #[cfg(feature = "debug")]
 use bevy_inspector_egui::bevy_egui::EguiContexts;
 
-use crate::{plugins::{ui::ingame::inventory::SLOT_COUNT_IN_ROW, assets::ItemAssets, cursor::position::CursorPosition, world::{events::{DigBlockEvent, SeedEvent, DigWallEvent, BreakTileEvent, PlaceTileEvent}, constants::TILE_SIZE, TileType}, player::{FaceDirection, Player, body_sprites::PlayerSpriteBody}, audio::{SoundType, AudioCommandsExt}, camera::components::MainCamera, item::ItemCommandsExt, entity::components::{EntityRect, Velocity}}, common::{helpers::{self, tile_to_world_pos}, rect::FRect}, items::{Item, ItemTool}, world::{WorldData, block::BlockType, wall::WallType}};
+use crate::{plugins::{ui::{ingame::inventory::{SLOT_COUNT_IN_ROW, components::InventoryUi}, resources::IsVisible}, assets::ItemAssets, cursor::position::CursorPosition, world::{events::{DigBlockEvent, SeedEvent, DigWallEvent, HammerBlockEvent, BreakTileEvent, PlaceTileEvent}, constants::TILE_SIZE, TileType}, player::{FaceDirection, Player, body_sprites::PlayerSpriteBody}, audio::{SoundType, AudioCommandsExt}, camera::components::MainCamera, item::ItemCommandsExt, entity::{ProjectileBuilder, ProjectileCommandsExt, components::{EntityRect, Velocity}}, npc::{Npc, DamageNpcEvent}}, common::{helpers::{self, tile_to_world_pos}, rect::FRect, BoolValue}, items::{ItemBehaviour, ToolType, WeaponType, CraftingStation}, world::{WorldData, block::BlockType, wall::WallType, furniture::{Furniture, FurnitureType}}};
 
-use super::{Inventory, SelectedItem, util::keycode_to_digit, SwingItemCooldown, ItemInHand, UseItemAnimationIndex, PlayerUsingItem, UseItemAnimationData, SwingItemCooldownMax, ITEM_ROTATION, SwingAnimation, ITEM_ANIMATION_POINTS};
+use super::{Inventory, SelectedItem, util::keycode_to_digit, SwingItemCooldown, ItemInHand, UseItemAnimationIndex, PlayerUsingItem, UseItemAnimationData, SwingItemCooldownMax, ITEM_ROTATION, SwingAnimation, ITEM_ANIMATION_POINTS, NearbyCraftingStations, CRAFTING_RANGE, OpenChest, CHEST_RANGE};
 
 pub(super) fn select_inventory_cell(
     mut commands: Commands,
@@ -94,15 +96,18 @@ pub(super) fn set_selected_item(
 }
 
 pub(super) fn use_item(
+    mut commands: Commands,
     mut inventory: ResMut<Inventory>,
     using_item: Res<PlayerUsingItem>,
     cursor_position: Res<CursorPosition<MainCamera>>,
     world_data: Res<WorldData>,
+    item_assets: Res<ItemAssets>,
     #[cfg(feature = "debug")]
     debug_config: Res<crate::plugins::debug::DebugConfiguration>,
     query_player: Query<&EntityRect, With<Player>>,
     mut dig_block_events: EventWriter<DigBlockEvent>,
     mut dig_wall_events: EventWriter<DigWallEvent>,
+    mut hammer_block_events: EventWriter<HammerBlockEvent>,
     mut break_tile_events: EventWriter<BreakTileEvent>,
     mut place_tile_events: EventWriter<PlaceTileEvent>,
     mut seed_events: EventWriter<SeedEvent>,
@@ -126,12 +131,12 @@ pub(super) fn use_item(
         if let Some(item_stack) = inventory.selected_item() {
             let tile_pos = helpers::get_tile_pos_from_world_coords(world_data.area.size(), cursor_position.world);
 
-            match item_stack.item {
-                Item::Tool(tool) => {
+            match item_stack.item.behaviour() {
+                ItemBehaviour::Tool(tool) => {
                     *use_cooldown = tool.use_cooldown();
                     
-                    match tool {
-                        ItemTool::Pickaxe(_) | ItemTool::Axe(_) => {
+                    match tool.tool_type {
+                        ToolType::Pickaxe | ToolType::Axe => {
                             if !world_data.get_block(tile_pos).is_some_and(|b| b.check_required_tool(tool)) {
                                 return;
                             }
@@ -142,6 +147,11 @@ pub(super) fn use_item(
                                     return;
                                 }
                             }
+
+                            // Don't break a block which holds an object
+                            if world_data.block_supports_furniture(tile_pos) {
+                                return;
+                            }
                             
                             if instant_break {
                                 break_tile_events.send(BreakTileEvent { tile_pos, tile_type: TileType::Block(None) });
@@ -149,7 +159,26 @@ pub(super) fn use_item(
                                 dig_block_events.send(DigBlockEvent { tile_pos, tool });
                             }
                         },
-                        ItemTool::Hammer(_) => {
+                        ToolType::Hammer => {
+                            // A hammer shapes a block and breaks a wall when there is no block
+                            if let Some(block) = world_data.get_block(tile_pos) {
+                                if !block.is_shapeable() { return; }
+
+                                // Don't shape a block which holds an object or a tree
+                                let holds_tile = tile_pos.y > 0 && world_data.get_block((tile_pos.x, tile_pos.y - 1)).is_some_and(|b| !b.is_solid());
+                                if holds_tile || world_data.block_supports_furniture(tile_pos) {
+                                    return;
+                                }
+
+                                // Like a dig, the shape only changes once per use cooldown of the tool
+                                hammer_block_events.send(HammerBlockEvent { tile_pos });
+                                return;
+                            }
+
+                            if world_data.wall_supports_furniture(tile_pos) {
+                                return;
+                            }
+
                             if instant_break {
                                 break_tile_events.send(BreakTileEvent { tile_pos, tile_type: TileType::Wall(None) });
                             } else {
@@ -158,7 +187,7 @@ pub(super) fn use_item(
                         },
                     }
                 },
-                Item::Block(item_block) => {
+                ItemBehaviour::Block(item_block) => {
                     if world_data.block_exists(tile_pos) { return; }
 
                     // Forbid placing a block inside the player 
@@ -173,7 +202,7 @@ pub(super) fn use_item(
                     place_tile_events.send(PlaceTileEvent { tile_pos, tile_type: TileType::Block(Some(block_type)) });
                     inventory.consume_item(selected_item_index);
                 },
-                Item::Wall(item_wall) => {
+                ItemBehaviour::Wall(item_wall) => {
                     if world_data.wall_exists(tile_pos) { return; }
 
                     let wall_type = WallType::from(item_wall);
@@ -182,12 +211,53 @@ pub(super) fn use_item(
                     inventory.consume_item(selected_item_index);
 
                 }
-                Item::Seed(seed) => {
+                ItemBehaviour::Seed(seed) => {
                     if !world_data.block_exists_with_type(tile_pos, BlockType::Dirt) { return; }
 
                     seed_events.send(SeedEvent { tile_pos, seed });
                     inventory.consume_item(selected_item_index);
                 }
+                ItemBehaviour::Furniture(item_furniture) => {
+                    let furniture_type = FurnitureType::from(item_furniture);
+
+                    let Some((origin, _)) = world_data.furniture_placement(furniture_type, tile_pos) else { return; };
+
+                    // Forbid placing a solid object inside the player
+                    if furniture_type.is_solid() {
+                        let size = furniture_type.size().as_vec2() * TILE_SIZE;
+                        let Vec2 { x, y } = tile_to_world_pos(origin);
+                        let rect = FRect::new(x - TILE_SIZE / 2., y + TILE_SIZE / 2., size.x, size.y);
+                        if player_rect.intersects(&rect) { return; }
+                    }
+
+                    // The object is placed with its bottom left tile at the cursor
+                    let block_type = BlockType::Furniture(Furniture::new(furniture_type, 0, 0, 0));
+
+                    place_tile_events.send(PlaceTileEvent { tile_pos, tile_type: TileType::Block(Some(block_type)) });
+                    inventory.consume_item(selected_item_index);
+                },
+                ItemBehaviour::Weapon(weapon) => {
+                    // Swords hit during the swing animation
+                    if weapon.weapon_type != WeaponType::Throwing { return; }
+
+                    *use_cooldown = item_stack.item.swing_cooldown();
+
+                    // A throwing weapon always has a projectile, it's checked when the items are loaded
+                    let projectile = weapon.projectile().unwrap();
+
+                    commands.spawn_projectile(
+                        ProjectileBuilder::from_definition(
+                            &projectile,
+                            item_assets.get_by_item(item_stack.item),
+                            player_rect.center(),
+                            cursor_position.world - player_rect.center()
+                        )
+                        .with_damage(weapon.damage(), weapon.knockback(), weapon.crit_chance())
+                    );
+
+                    inventory.consume_item(selected_item_index);
+                },
+                ItemBehaviour::Material => {}
             }
         }
     }
@@ -283,6 +353,69 @@ pub(super) fn set_using_item_rotation(
     transform.rotation = Quat::from_rotation_z(rotation * direction * ITEM_ROTATION + direction * 0.5);
 }
 
+/// Damages the NPCs which overlap the weapon in the current frame of the swing, each NPC is hit once per swing
+pub(super) fn hit_with_weapon(
+    selected_item: Res<SelectedItem>,
+    swing_cooldown: Res<SwingItemCooldown>,
+    item_assets: Res<ItemAssets>,
+    images: Res<Assets<Image>>,
+    query_player: Query<(&EntityRect, &FaceDirection), With<Player>>,
+    query_using_item: Query<&Transform, With<ItemInHand>>,
+    query_npcs: Query<(Entity, &EntityRect), With<Npc>>,
+    mut damage_npc_events: EventWriter<DamageNpcEvent>,
+    mut last_swing_cooldown: Local<u32>,
+    mut hit_entities: Local<Vec<Entity>>,
+) {
+    // The cooldown is reset to its maximum when a new swing starts
+    if **swing_cooldown > *last_swing_cooldown {
+        hit_entities.clear();
+    }
+    *last_swing_cooldown = **swing_cooldown;
+
+    let Some(item_stack) = **selected_item else { return; };
+    let ItemBehaviour::Weapon(weapon) = item_stack.item.behaviour() else { return; };
+
+    let Ok((player_rect, face_direction)) = query_player.get_single() else { return; };
+    let Ok(item_transform) = query_using_item.get_single() else { return; };
+    let Some(image) = images.get(&item_assets.get_by_item(item_stack.item)) else { return; };
+
+    let (blade_start, blade_end) = match weapon.weapon_type {
+        WeaponType::Sword => sword_blade(player_rect.center(), item_transform, image.size(), f32::from(face_direction)),
+        // Thrown weapons hit with their projectiles
+        WeaponType::Throwing => return,
+    };
+
+    let mut rng = thread_rng();
+
+    for (entity, npc_rect) in &query_npcs {
+        if hit_entities.contains(&entity) || !npc_rect.intersects_segment(blade_start, blade_end) { continue; }
+
+        hit_entities.push(entity);
+
+        let critical = rng.gen_bool(weapon.crit_chance() as f64);
+        let damage = if critical { weapon.damage() * 2 } else { weapon.damage() };
+
+        // The NPC is pushed away from the player and a bit up
+        let direction = if npc_rect.centerx < player_rect.centerx { -1. } else { 1. };
+
+        damage_npc_events.send(DamageNpcEvent {
+            entity,
+            damage,
+            knockback: vec2(direction, 0.5) * weapon.knockback(),
+            critical,
+        });
+    }
+}
+
+/// Returns the segment from the hand of the player to the tip of the sword at its current angle, so the hit area sweeps an arc over the swing
+fn sword_blade(player_center: Vec2, item_transform: &Transform, sprite_size: Vec2, direction: f32) -> (Vec2, Vec2) {
+    // The sprite is anchored at its bottom corner which is in the hand of the player, the blade runs along its diagonal
+    let origin = player_center + item_transform.translation.truncate();
+    let tip = vec2(sprite_size.x * direction, sprite_size.y);
+
+    (origin, origin + (item_transform.rotation * tip.extend(0.)).truncate())
+}
+
 pub(super) fn update_use_item_animation_index(
     mut index: ResMut<UseItemAnimationIndex>,
     swing_cooldown: Res<SwingItemCooldown>,
@@ -313,8 +446,10 @@ pub(super) fn play_swing_sound(
     swing_cooldown_max: Res<SwingItemCooldownMax>,
 ) {
     if **swing_cooldown == **swing_cooldown_max {
-        if let Some(Item::Tool(tool)) = selected_item.map(|i| i.item) {
-            commands.play_sound(SoundType::PlayerToolSwing(tool));
+        match selected_item.map(|i| i.item.behaviour()) {
+            Some(ItemBehaviour::Tool(_)) => commands.play_sound(SoundType::PlayerToolSwing),
+            Some(ItemBehaviour::Weapon(_)) => commands.play_sound(SoundType::PlayerWeaponSwing),
+            _ => {}
         }
     }
 }
@@ -333,5 +468,119 @@ pub(super) fn update_player_using_item(
 
     let pressed = input.pressed(MouseButton::Left) || input.just_pressed(MouseButton::Left);
     
-    **using_item = pressed && selected_item.is_some();
-}
\ No newline at end of file
+    **using_item = pressed && selected_item.is_some_and(|item_stack| item_stack.item.usable());
+}
+
+pub(super) fn update_nearby_crafting_stations(
+    world_data: Res<WorldData>,
+    query_player: Query<&EntityRect, With<Player>>,
+    mut nearby_stations: ResMut<NearbyCraftingStations>,
+) {
+    let Ok(player_rect) = query_player.get_single() else { return; };
+
+    let world_size = world_data.area.size();
+    let top_left = helpers::get_tile_pos_from_world_coords(world_size, Vec2::new(player_rect.left(), player_rect.top()));
+    let bottom_right = helpers::get_tile_pos_from_world_coords(world_size, Vec2::new(player_rect.right(), player_rect.bottom()));
+
+    let min_x = top_left.x.saturating_sub(CRAFTING_RANGE.x);
+    let min_y = top_left.y.saturating_sub(CRAFTING_RANGE.y);
+    let max_x = (bottom_right.x + CRAFTING_RANGE.x).min(world_size.x - 1);
+    let max_y = (bottom_right.y + CRAFTING_RANGE.y).min(world_size.y - 1);
+
+    let stations = (min_y..=max_y)
+        .flat_map(|y| (min_x..=max_x).map(move |x| (x, y)))
+        .filter_map(|tile_pos| match world_data.get_block(tile_pos)?.block_type {
+            BlockType::Furniture(furniture) => CraftingStation::from_furniture(furniture.furniture_type),
+            _ => None
+        })
+        .collect();
+
+    nearby_stations.set_if_neq(NearbyCraftingStations(stations));
+}
+
+/// Opens the chest under the cursor together with the inventory. Clicking the open chest closes it.
+pub(super) fn open_chest(
+    mut commands: Commands,
+    mut world_data: ResMut<WorldData>,
+    cursor_position: Res<CursorPosition<MainCamera>>,
+    open_chest: Option<Res<OpenChest>>,
+    mut inventory_visibility: ResMut<IsVisible<InventoryUi>>,
+    query_player: Query<&EntityRect, With<Player>>,
+) {
+    let Ok(player_rect) = query_player.get_single() else { return; };
+
+    let tile_pos = helpers::get_tile_pos_from_world_coords(world_data.area.size(), cursor_position.world);
+
+    let Some(BlockType::Furniture(furniture)) = world_data.get_block(tile_pos).map(|b| b.block_type) else { return; };
+    if furniture.furniture_type != FurnitureType::Chest { return; }
+
+    let origin = furniture.origin(tile_pos);
+    if !chest_in_reach(player_rect, origin) { return; }
+
+    if let Some(open_chest) = open_chest {
+        world_data.chests.insert(open_chest.origin, open_chest.contents);
+        commands.remove_resource::<OpenChest>();
+
+        if open_chest.origin == origin {
+            commands.play_sound(SoundType::MenuClose);
+            return;
+        }
+    }
+
+    commands.insert_resource(OpenChest {
+        origin,
+        contents: world_data.chests.get(&origin).copied().unwrap_or_default(),
+    });
+
+    *inventory_visibility = IsVisible::visible();
+
+    commands.play_sound(SoundType::MenuOpen);
+}
+
+/// Stores the contents of the open chest in the world.
+/// The chest is closed when the inventory gets hidden, the player walks away or the chest is broken.
+pub(super) fn update_open_chest(
+    mut commands: Commands,
+    mut world_data: ResMut<WorldData>,
+    open_chest: Res<OpenChest>,
+    inventory_visibility: Res<IsVisible<InventoryUi>>,
+    query_player: Query<&EntityRect, With<Player>>,
+) {
+    let chest_exists = matches!(
+        world_data.get_block(open_chest.origin).map(|b| b.block_type),
+        Some(BlockType::Furniture(furniture)) if furniture.furniture_type == FurnitureType::Chest
+    );
+
+    // The contents of a broken chest have already been dropped
+    if !chest_exists {
+        commands.remove_resource::<OpenChest>();
+        return;
+    }
+
+    if open_chest.is_changed() {
+        world_data.chests.insert(open_chest.origin, open_chest.contents);
+    }
+
+    let in_reach = query_player.get_single().is_ok_and(|player_rect| chest_in_reach(player_rect, open_chest.origin));
+
+    if !in_reach || !inventory_visibility.value() {
+        commands.remove_resource::<OpenChest>();
+        commands.play_sound(SoundType::MenuClose);
+    }
+}
+
+fn chest_in_reach(player_rect: &FRect, origin: TilePos) -> bool {
+    let size = FurnitureType::Chest.size().as_vec2() * TILE_SIZE;
+    let Vec2 { x, y } = tile_to_world_pos(origin);
+    let chest_rect = FRect::new(x - TILE_SIZE / 2., y + TILE_SIZE / 2., size.x, size.y);
+
+    let reach = CHEST_RANGE.as_vec2() * TILE_SIZE * 2.;
+    let reach_rect = FRect::new_center(
+        player_rect.centerx,
+        player_rect.centery,
+        player_rect.width() + reach.x,
+        player_rect.height() + reach.y
+    );
+
+    reach_rect.intersects(&chest_rect)
+}
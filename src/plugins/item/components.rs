@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use bevy::{prelude::{Component, DerefMut, Deref}, time::Timer};
 
 use crate::items::ItemStack;
@@ -11,4 +13,8 @@ pub(crate) struct DroppedItem {
 pub(super) struct Following;
 
 #[derive(Component, Deref, DerefMut)]
-pub(super) struct GrabTimer(pub(super) Timer);
\ No newline at end of file
+pub(super) struct GrabTimer(pub(super) Timer);
+
+/// How long the item has been lying on the ground
+#[derive(Component, Default, Deref, DerefMut)]
+pub(super) struct ItemAge(pub(super) Duration);
\ No newline at end of file
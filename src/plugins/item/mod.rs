@@ -1,13 +1,13 @@
-use bevy::{prelude::{Plugin, App, World, default, Vec2, Transform, Update, IntoSystemConfigs, FixedUpdate, Commands, apply_deferred, Assets, Image}, ecs::system::Command, sprite::SpriteBundle, ui::Interaction, time::Timer};
+use bevy::{prelude::{Plugin, App, World, default, Vec2, Transform, Update, IntoSystemConfigs, FixedUpdate, Commands, apply_deferred, Assets, Image, not, resource_exists}, ecs::system::Command, sprite::SpriteBundle, ui::Interaction, time::Timer};
 
 use crate::{items::ItemStack, common::rect::FRect, language::{LocalizedText, keys::ItemStringKey, args}};
 
-use self::components::{DroppedItem, GrabTimer};
+use self::components::{DroppedItem, GrabTimer, ItemAge};
 
-use super::{assets::ItemAssets, world::{constants::TILE_SIZE, WORLD_RENDER_LAYER}, InGameSystemSet, cursor::components::Hoverable, entity::{EntitySet, components::{EntityRect, Velocity}}};
+use super::{assets::ItemAssets, world::WORLD_RENDER_LAYER, InGameSystemSet, DespawnOnGameExit, cursor::components::Hoverable, entity::{EntitySet, components::{EntityRect, Velocity, Collider}}, player::RespawnTimer};
 
 mod systems;
-mod components;
+pub(crate) mod components;
 pub(super) struct ItemPlugin;
 impl Plugin for ItemPlugin {
     fn build(&self, app: &mut App) {
@@ -15,9 +15,11 @@ impl Plugin for ItemPlugin {
             FixedUpdate,
             (
                 (
+                    systems::despawn_items,
+                    apply_deferred,
                     systems::stack_items,
                     apply_deferred,
-                    systems::follow_player,
+                    systems::follow_player.run_if(not(resource_exists::<RespawnTimer>())),
                 )
                 .chain(),
 
@@ -25,7 +27,6 @@ impl Plugin for ItemPlugin {
                     systems::gravity,
                     systems::air_resistance,
                 ),
-                systems::detect_collisions,
             )
             .chain()
             .before(EntitySet::UpdateEntityRect)
@@ -43,11 +44,9 @@ impl Plugin for ItemPlugin {
     }
 }
 
-const STACK_RANGE: f32 = 1.5 * TILE_SIZE;
 const GRAVITY: f32 = 0.1;
 const MAX_VERTICAL_SPEED: f32 = 7.;
 const MAX_HORIZONTAL_SPEED: f32 = 5.;
-const GRAB_RANGE: f32 = 5.25 * TILE_SIZE;
 
 
 struct SpawnDroppedItemCommand {
@@ -75,11 +74,14 @@ impl Command for SpawnDroppedItemCommand {
             },
             EntityRect(FRect::new_center(self.position.x, self.position.y, size.x, size.y)),
             Velocity(self.velocity),
+            Collider::default(),
             Hoverable::SimpleText(item_hoverable_text(self.item_stack)),
             Interaction::default(),
             DroppedItem {
                 item_stack: self.item_stack
             },
+            ItemAge::default(),
+            DespawnOnGameExit,
             WORLD_RENDER_LAYER
         ));
 
@@ -1,8 +1,10 @@
+use std::time::Duration;
+
 use bevy::{prelude::{Query, With, Res, Commands, Entity, Transform, Changed, Without, ResMut, Local, FixedTime, Vec3, Vec2}, ecs::query::Has};
 
-use crate::{common::rect::FRect, plugins::{item::{GRAVITY, MAX_VERTICAL_SPEED}, world::constants::TILE_SIZE, cursor::components::Hoverable, inventory::Inventory, player::Player, audio::{AudioCommandsExt, SoundType}, entity::components::{EntityRect, Velocity}}, world::WorldData};
+use crate::{common::rect::FRect, items::SlotContainer, plugins::{item::{GRAVITY, MAX_VERTICAL_SPEED}, cursor::components::Hoverable, inventory::Inventory, player::{Player, PickupRange}, audio::{AudioCommandsExt, SoundType}, entity::components::{EntityRect, Velocity, Collider}, config::DroppedItemsConfig, world::constants::TILE_SIZE}};
 
-use super::{STACK_RANGE, item_hoverable_text, GRAB_RANGE, MAX_HORIZONTAL_SPEED};
+use super::{item_hoverable_text, MAX_HORIZONTAL_SPEED};
 use super::components::*;
 
 pub(super) fn gravity(
@@ -44,93 +46,52 @@ pub(super) fn rotate_item(
     }
 }
 
-pub(super) fn detect_collisions(
-    world_data: Res<WorldData>,
-    mut query: Query<(&mut Velocity, &mut EntityRect), (With<DroppedItem>, Without<Following>)>
+pub(super) fn despawn_items(
+    time: Res<FixedTime>,
+    config: Res<DroppedItemsConfig>,
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut ItemAge), (With<DroppedItem>, Without<Following>)>,
+    mut remaining: Local<Vec<(Entity, Duration)>>
 ) {
-    for (mut velocity, mut item_rect) in &mut query {
-        let pos = item_rect.center();
-        let next_position = pos + velocity.0;
-
-        let next_rect = FRect::new_center(next_position.x, next_position.y, item_rect.width(), item_rect.height());
-
-        let left = (item_rect.left() / TILE_SIZE) - 1.;
-        let right = (item_rect.right() / TILE_SIZE) + 2.;
-        let mut top = (item_rect.top().abs() / TILE_SIZE) - 1.;
-        let bottom = (item_rect.bottom().abs() / TILE_SIZE) + 2.;
-
-        top = top.max(0.);
-
-        let left_u32 = left as u32;
-        let right_u32 = right as u32;
-        let top_u32 = top as u32;
-        let bottom_u32 = bottom as u32;
-
-        for x in left_u32..right_u32 {
-            for y in top_u32..bottom_u32 {
-                if y >= world_data.area.height() || world_data.solid_block_exists((x, y)) {
-                    let tile_rect = FRect::new_center(
-                        x as f32 * TILE_SIZE + TILE_SIZE / 2.,
-                        -(y as f32 * TILE_SIZE + TILE_SIZE / 2.),
-                        TILE_SIZE,
-                        TILE_SIZE
-                    );
-
-                    if next_rect.intersects(&tile_rect) {
-                        let delta_x = tile_rect.centerx - item_rect.centerx;
-                        let delta_y = tile_rect.centery - item_rect.centery;
-
-                        if delta_x.abs() > delta_y.abs() {
-                            if delta_x < 0. {
-                                // If the item's left side is more to the left than the tile's right side then move the item right.
-                                if item_rect.left() <= tile_rect.right() {
-                                    velocity.x = 0.;
-                                    item_rect.centerx = tile_rect.right() + item_rect.half_width();
-                                }
-                            } else {
-                                // If the item's right side is more to the right than the tile's left side then move the item left.
-                                if item_rect.right() >= tile_rect.left() {
-                                    velocity.x = 0.;
-                                    item_rect.centerx = tile_rect.left() - item_rect.half_width();
-                                }
-                            }
-                        } else {
-                            // Checking for collisions again with an offset to workaround the bug when the item stuck in a wall.
-                            if FRect::new(next_rect.left() + 2.0, next_rect.top(), item_rect.width() - 4.0, item_rect.height()).intersects(&tile_rect) {
-                                if delta_y > 0. {
-                                    // If the item's top side is higher than the tile's bottom side then move the item down.
-                                    if item_rect.top() >= tile_rect.bottom() {
-                                        velocity.y = 0.;
-                                        item_rect.centery = tile_rect.bottom() - item_rect.half_height();
-                                    }
-                                } else {
-                                    // If the item's bottom side is lower than the tile's top side then move the item up
-                                    if item_rect.bottom() <= tile_rect.top() {
-                                        velocity.y = 0.;
-                                        item_rect.centery = tile_rect.top() + item_rect.half_height();
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
-            }
+    remaining.clear();
+
+    let lifetime = Duration::try_from_secs_f32(config.lifetime.max(0.)).unwrap_or(Duration::MAX);
+
+    for (entity, mut age) in &mut query {
+        age.0 += time.period;
+
+        if age.0 >= lifetime {
+            commands.entity(entity).despawn();
+        } else {
+            remaining.push((entity, age.0));
+        }
+    }
+
+    if remaining.len() > config.max_count {
+        // The oldest items are despawned first
+        remaining.sort_unstable_by(|(_, a), (_, b)| b.cmp(a));
+
+        let excess = remaining.len() - config.max_count;
+        for (entity, _) in remaining.drain(..excess) {
+            commands.entity(entity).despawn();
         }
     }
 }
 
 pub(super) fn stack_items(
     mut commands: Commands,
-    mut query: Query<(Entity, &mut EntityRect, &mut Velocity, &mut DroppedItem), Without<Following>>,
+    config: Res<DroppedItemsConfig>,
+    mut query: Query<(Entity, &mut EntityRect, &mut Velocity, &mut DroppedItem, &mut ItemAge), Without<Following>>,
     mut stacked: Local<Vec<Entity>>
 ) {
     stacked.clear();
 
+    let merge_range = config.merge_radius * TILE_SIZE * 2.;
     let mut combinations = query.iter_combinations_mut();
 
     while let Some([
-        (entity, mut rect, mut velocity, mut item),
-        (other_entity, other_rect, other_velocity, mut other_item),
+        (entity, mut rect, mut velocity, mut item, mut age),
+        (other_entity, other_rect, other_velocity, mut other_item, other_age),
     ]) = combinations.fetch_next() {
         if stacked.contains(&entity) || stacked.contains(&other_entity) { continue; }
 
@@ -141,7 +102,7 @@ pub(super) fn stack_items(
         if item_stack.item != other_item_stack.item { continue; }
         if item_stack.stack >= item_max_stack { continue; }
 
-        let stack_rect = FRect::new_center(rect.centerx, rect.centery, STACK_RANGE, STACK_RANGE);
+        let stack_rect = FRect::new_center(rect.centerx, rect.centery, merge_range, merge_range);
         if !stack_rect.intersects(&other_rect) { continue; }
 
         if item_stack.stack + other_item_stack.stack > item_max_stack {
@@ -156,6 +117,8 @@ pub(super) fn stack_items(
         velocity.0 = (velocity.0 + other_velocity.0) / 2.;
         rect.centerx = (rect.centerx + other_rect.centerx) / 2.;
         rect.centery = (rect.centery + other_rect.centery) / 2.;
+        // The merged stack lives as long as the younger one
+        age.0 = age.0.min(other_age.0);
 
         if other_item.item_stack.stack == 0 {
             commands.entity(other_entity).despawn();
@@ -177,21 +140,21 @@ pub(super) fn follow_player(
     time: Res<FixedTime>,
     mut commands: Commands,
     mut inventory: ResMut<Inventory>,
-    query_player: Query<&EntityRect, With<Player>>,
-    mut query_items: Query<(Entity, &EntityRect, &DroppedItem, &mut Velocity, Has<Following>, Option<&mut GrabTimer>)>
+    query_player: Query<(&EntityRect, &PickupRange), With<Player>>,
+    mut query_items: Query<(Entity, &EntityRect, &mut DroppedItem, &mut Velocity, Has<Following>, Option<&mut GrabTimer>)>
 ) {
-    let player_rect = query_player.single();
+    let (player_rect, pickup_range) = query_player.single();
+    let player_grab_rect = FRect::new_center(player_rect.centerx, player_rect.centery, pickup_range.0, pickup_range.0);
 
-    for (entity, item_rect, dropped_item, mut velocity, is_following, grab_timer_opt) in &mut query_items {
+    for (entity, item_rect, mut dropped_item, mut velocity, is_following, grab_timer_opt) in &mut query_items {
         if let Some(mut grab_timer) = grab_timer_opt {
             if !grab_timer.tick(time.period).finished() { continue; }
         }
 
-        let player_grab_rect = FRect::new_center(player_rect.centerx, player_rect.centery, GRAB_RANGE, GRAB_RANGE);
-
-        if !player_grab_rect.intersects(item_rect) || !inventory.can_be_added(dropped_item.item_stack) {
+        // The items which don't fit into the inventory stay on the ground
+        if !player_grab_rect.intersects(item_rect) || inventory.free_space(dropped_item.item_stack.item) == 0 {
             if is_following {
-                commands.entity(entity).remove::<Following>();
+                commands.entity(entity).remove::<Following>().insert(Collider::default());
             }
             continue;
         }
@@ -199,8 +162,16 @@ pub(super) fn follow_player(
         let distance = (player_rect.center() - item_rect.center()).length();
 
         if distance <= 16. {
-            inventory.add_item_stack(dropped_item.item_stack);
-            commands.entity(entity).despawn();
+            let added = inventory.add_item_stack(dropped_item.item_stack);
+            let remaining = dropped_item.item_stack.stack - added;
+
+            if remaining == 0 {
+                commands.entity(entity).despawn();
+            } else {
+                dropped_item.item_stack.stack = remaining;
+                commands.entity(entity).remove::<Following>().insert(Collider::default());
+            }
+
             commands.play_sound(SoundType::ItemGrab);
             continue;
         }
@@ -209,8 +180,9 @@ pub(super) fn follow_player(
 
         velocity.0 = direction * 4.;
 
+        // The item flies through the tiles to the player
         if !is_following {
-            commands.entity(entity).insert(Following);
+            commands.entity(entity).insert(Following).remove::<Collider>();
         }
     }
 }
\ No newline at end of file
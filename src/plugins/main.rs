@@ -5,7 +5,7 @@ use bevy_ecs_tilemap::prelude::TilemapRenderSettings;
 
 use crate::{common::{systems::despawn_with, state::{GameState, MenuState}}, lighting::LightingPlugin, parallax::ParallaxPlugin, animation::TweeningPlugin, language::plugin::LanguagePlugin};
 
-use super::{InGameSystemSet, MenuSystemSet, DespawnOnGameExit, audio::AudioPlugin, cursor::CursorPlugin, camera::CameraPlugin, background::BackgroundPlugin, ui::UiPlugin, world::WorldPlugin, inventory::PlayerInventoryPlugin, fps::FpsPlugin, player::PlayerPlugin, slider::SliderPlugin, assets::AssetsPlugin, entity::EntityPlugin, world_map_view::WorldMapViewPlugin};
+use super::{InGameSystemSet, MenuSystemSet, DespawnOnGameExit, audio::AudioPlugin, cursor::CursorPlugin, camera::CameraPlugin, background::BackgroundPlugin, ui::UiPlugin, world::WorldPlugin, inventory::PlayerInventoryPlugin, fps::FpsPlugin, player::PlayerPlugin, slider::SliderPlugin, assets::AssetsPlugin, entity::EntityPlugin, world_map_view::WorldMapViewPlugin, profile::ProfilePlugin, npc::NpcPlugin};
 
 pub(crate) struct MainPlugin;
 impl Plugin for MainPlugin {
@@ -30,7 +30,9 @@ impl Plugin for MainPlugin {
             PlayerInventoryPlugin,
             FpsPlugin,
             PlayerPlugin,
-            WorldMapViewPlugin
+            NpcPlugin,
+            WorldMapViewPlugin,
+            ProfilePlugin
         ));
 
         #[cfg(feature = "debug")] {
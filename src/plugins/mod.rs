@@ -15,8 +15,10 @@ pub(crate) mod audio;
 pub(crate) mod slider;
 pub(crate) mod particles;
 pub(crate) mod item;
+pub(crate) mod npc;
 pub(crate) mod entity;
 pub(crate) mod world_map_view;
+pub(crate) mod profile;
 
 #[cfg(feature = "debug")]
 pub(crate) mod debug;
@@ -0,0 +1,30 @@
+use bevy::{prelude::{Component, Deref, DerefMut}, time::Timer};
+
+use crate::npcs::NpcType;
+
+#[derive(Component)]
+pub(crate) struct Npc {
+    pub(crate) npc_type: NpcType
+}
+
+/// The state of the AI of an NPC
+#[derive(Component, Default)]
+pub(super) struct NpcAi {
+    /// The number of ticks since the NPC has started its current action
+    pub(super) timer: u32,
+    /// The number of actions the NPC has done, e.g. the number of hops of a slime
+    pub(super) counter: u32,
+    /// The horizontal direction the NPC is moving in, -1 or 1
+    pub(super) direction: f32,
+    /// The number of ticks until the NPC can shoot again
+    pub(super) attack_cooldown: u32,
+}
+
+#[derive(Component, Deref, DerefMut)]
+pub(super) struct NpcAnimationTimer(pub(super) Timer);
+
+#[derive(Component)]
+pub(super) struct HealthBar;
+
+#[derive(Component)]
+pub(super) struct HealthBarFill;
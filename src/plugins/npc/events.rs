@@ -0,0 +1,12 @@
+use bevy::prelude::{Event, Entity, Vec2};
+
+/// Takes the amount of health from the NPC, the NPC dies and drops its loot when there is no health left
+#[derive(Event, Clone, Copy)]
+pub(crate) struct DamageNpcEvent {
+    pub(crate) entity: Entity,
+    pub(crate) damage: i32,
+    /// The velocity the NPC is pushed with, reduced by its knockback resistance
+    pub(crate) knockback: Vec2,
+    /// Whether the hit is a critical one, it's shown differently
+    pub(crate) critical: bool,
+}
@@ -0,0 +1,93 @@
+mod components;
+mod events;
+mod systems;
+
+pub(crate) use components::*;
+pub(crate) use events::*;
+
+use std::time::Duration;
+
+use bevy::{prelude::{Plugin, App, Update, FixedUpdate, IntoSystemConfigs, Vec2, Color, not, resource_exists}, time::common_conditions::on_timer};
+
+use super::{InGameSystemSet, entity::EntitySet, player::RespawnTimer, world::constants::TILE_SIZE};
+
+/// The maximum number of NPCs which can be alive at the same time
+const MAX_NPCS: usize = 8;
+/// The probability of an NPC to spawn on each spawn attempt
+const SPAWN_CHANCE: f64 = 0.35;
+const SPAWN_INTERVAL: Duration = Duration::from_secs(1);
+/// The horizontal distance in tiles from the player where NPCs spawn, so they appear outside of the screen
+const SPAWN_MIN_DISTANCE: i32 = 62;
+const SPAWN_MAX_DISTANCE: i32 = 84;
+/// The number of tiles above and below the player which are searched for the ground to spawn an NPC on
+const SPAWN_SEARCH_HEIGHT: i32 = 30;
+/// NPCs which get this many tiles away from the player are despawned
+const DESPAWN_DISTANCE: f32 = 120. * TILE_SIZE;
+
+const GRAVITY: f32 = 0.3;
+const MAX_FALL_SPEED: f32 = 10.;
+
+const ANIMATION_FRAME_TIME: Duration = Duration::from_millis(150);
+
+/// The number of ticks a slime waits on the ground before it hops
+const SLIME_HOP_DELAY: u32 = 60;
+const SLIME_FRICTION: f32 = 0.2;
+const SLIME_HOP_VELOCITY: Vec2 = Vec2::new(2.5, 5.);
+/// Every third hop is a high one
+const SLIME_HIGH_HOP_VELOCITY: Vec2 = Vec2::new(1.5, 7.);
+
+const WALKER_MAX_SPEED: f32 = 1.5;
+const WALKER_ACCELERATION: f32 = 0.07;
+const WALKER_JUMP_SPEED: f32 = 5.5;
+
+const FLYER_MAX_SPEED: f32 = 3.;
+const FLYER_ACCELERATION: f32 = 0.05;
+const FLYER_BOUNCE_SPEED: f32 = 1.5;
+
+/// The velocity the player is pushed away from an NPC with on contact
+const CONTACT_KNOCKBACK: Vec2 = Vec2::new(3., 4.);
+
+const LAVA_DAMAGE: i32 = 50;
+const LAVA_IMMUNITY_TIME: Duration = Duration::from_millis(500);
+
+const DAMAGE_NUMBER_COLOR: Color = Color::rgb(1., 0.6, 0.2);
+const DAMAGE_NUMBER_CRIT_COLOR: Color = Color::rgb(1., 0.3, 0.1);
+
+const HEALTH_BAR_WIDTH: f32 = 28.;
+const HEALTH_BAR_HEIGHT: f32 = 4.;
+
+pub(crate) struct NpcPlugin;
+impl Plugin for NpcPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<DamageNpcEvent>();
+
+        app.add_systems(
+            FixedUpdate,
+            (
+                systems::update_ai,
+                systems::shoot,
+                systems::gravity,
+            )
+            .chain()
+            .before(EntitySet::UpdateEntityRect)
+            .in_set(InGameSystemSet::FixedUpdate)
+        );
+
+        app.add_systems(
+            Update,
+            (
+                systems::spawn_npcs.run_if(on_timer(SPAWN_INTERVAL)),
+                systems::despawn_far_npcs,
+                systems::contact_damage.run_if(not(resource_exists::<RespawnTimer>())),
+                (
+                    systems::lava_damage,
+                    systems::damage_npc,
+                    systems::update_health_bars,
+                )
+                .chain(),
+                systems::animate,
+            )
+            .in_set(InGameSystemSet::Update)
+        );
+    }
+}
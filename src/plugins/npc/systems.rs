@@ -0,0 +1,411 @@
+use bevy::{prelude::{Commands, Query, Res, With, Without, Changed, Entity, EventReader, EventWriter, Transform, Vec2, Color, Visibility, Children, BuildChildren, DespawnRecursiveExt, Name, SpriteBundle, SpriteSheetBundle, TextureAtlasSprite, Sprite, default}, sprite::Anchor, time::{Time, Timer, TimerMode}, math::vec2};
+use rand::{thread_rng, Rng};
+
+use crate::{
+    common::{rect::FRect, math::move_towards, helpers::{get_tile_pos_from_world_coords, set_visibility}},
+    items::ItemStack,
+    npcs::{NpcType, AiType, SpawnLayer, pick_npc_to_spawn},
+    plugins::{
+        DespawnOnGameExit,
+        assets::{NpcAssets, FontAssets},
+        entity::{spawn_damage_number, ProjectileBuilder, ProjectileCommandsExt, components::{EntityRect, Velocity, Health, Immunity, Collider, Collisions}},
+        item::ItemCommandsExt,
+        player::{Player, DamagePlayerEvent, DamageSource, RespawnTimer},
+        world::{WORLD_RENDER_LAYER, constants::TILE_SIZE, time::GameTime}
+    },
+    world::{WorldData, liquid::LiquidType},
+    NPC_LAYER
+};
+
+use super::*;
+
+pub(super) fn spawn_npcs(
+    mut commands: Commands,
+    world_data: Res<WorldData>,
+    game_time: Res<GameTime>,
+    npc_assets: Res<NpcAssets>,
+    query_player: Query<&EntityRect, With<Player>>,
+    query_npcs: Query<(), With<Npc>>,
+) {
+    if query_npcs.iter().count() >= MAX_NPCS { return; }
+
+    let Ok(player_rect) = query_player.get_single() else { return; };
+
+    let mut rng = thread_rng();
+
+    if !rng.gen_bool(SPAWN_CHANCE) { return; }
+
+    let player_x = (player_rect.centerx / TILE_SIZE) as i32;
+    let player_y = (-player_rect.centery / TILE_SIZE) as i32;
+
+    let side = if rng.gen_bool(0.5) { 1 } else { -1 };
+    let x = player_x + side * rng.gen_range(SPAWN_MIN_DISTANCE..=SPAWN_MAX_DISTANCE);
+
+    let playable_area = world_data.playable_area;
+    if x < playable_area.min.x as i32 || x >= playable_area.max.x as i32 { return; }
+
+    // The NPC spawns on a solid tile which has two free tiles above it
+    let ground_y = (player_y - SPAWN_SEARCH_HEIGHT..player_y + SPAWN_SEARCH_HEIGHT)
+        .filter(|&y| y >= playable_area.min.y as i32 + 2 && y < playable_area.max.y as i32)
+        .find(|&y| {
+            let (x, y) = (x as u32, y as u32);
+
+            world_data.solid_block_exists((x, y))
+                && !world_data.solid_block_exists((x, y - 1))
+                && !world_data.solid_block_exists((x, y - 2))
+                && world_data.get_liquid((x, y - 1)).is_none()
+        });
+
+    let Some(ground_y) = ground_y else { return; };
+
+    let layer = get_spawn_layer(&world_data, ground_y as usize);
+
+    let Some(npc_type) = pick_npc_to_spawn(&mut rng, layer, game_time.is_day) else { return; };
+
+    let height = npc_type.definition().size[1];
+    let position = vec2(
+        x as f32 * TILE_SIZE + TILE_SIZE / 2.,
+        -(ground_y as f32 * TILE_SIZE) + height / 2.
+    );
+
+    spawn_npc(&mut commands, &npc_assets, npc_type, position, -side as f32);
+}
+
+fn get_spawn_layer(world_data: &WorldData, y: usize) -> SpawnLayer {
+    if y < world_data.layer.underground {
+        SpawnLayer::Surface
+    } else if y < world_data.layer.cavern {
+        SpawnLayer::Underground
+    } else {
+        SpawnLayer::Cavern
+    }
+}
+
+fn spawn_npc(commands: &mut Commands, npc_assets: &NpcAssets, npc_type: NpcType, position: Vec2, direction: f32) {
+    let definition = npc_type.definition();
+    let size = Vec2::from_array(definition.size);
+    let frame_height = definition.frame_size[1];
+
+    commands
+        .spawn((
+            Name::new(definition.id.clone()),
+            SpriteSheetBundle {
+                texture_atlas: npc_assets.get_by_npc(npc_type),
+                sprite: TextureAtlasSprite {
+                    color: definition.color.map_or(Color::WHITE, |[r, g, b, a]| Color::rgba(r, g, b, a)),
+                    ..default()
+                },
+                transform: Transform::from_xyz(position.x, position.y, NPC_LAYER),
+                ..default()
+            },
+            Npc { npc_type },
+            NpcAi { direction, ..default() },
+            NpcAnimationTimer(Timer::new(ANIMATION_FRAME_TIME, TimerMode::Repeating)),
+            Health::new(definition.max_health),
+            EntityRect(FRect::new_center(position.x, position.y, size.x, size.y)),
+            Velocity::default(),
+            Collider::default(),
+            Collisions::default(),
+            DespawnOnGameExit,
+            WORLD_RENDER_LAYER
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn((
+                    HealthBar,
+                    SpriteBundle {
+                        sprite: Sprite {
+                            color: Color::rgba(0.1, 0.1, 0.1, 0.8),
+                            custom_size: Some(vec2(HEALTH_BAR_WIDTH, HEALTH_BAR_HEIGHT)),
+                            ..default()
+                        },
+                        transform: Transform::from_xyz(0., -(frame_height / 2. + HEALTH_BAR_HEIGHT), 0.1),
+                        visibility: Visibility::Hidden,
+                        ..default()
+                    },
+                    WORLD_RENDER_LAYER
+                ))
+                .with_children(|parent| {
+                    parent.spawn((
+                        HealthBarFill,
+                        SpriteBundle {
+                            sprite: Sprite {
+                                color: Color::GREEN,
+                                custom_size: Some(vec2(HEALTH_BAR_WIDTH - 2., HEALTH_BAR_HEIGHT - 2.)),
+                                anchor: Anchor::CenterLeft,
+                                ..default()
+                            },
+                            transform: Transform::from_xyz(-(HEALTH_BAR_WIDTH - 2.) / 2., 0., 0.1),
+                            ..default()
+                        },
+                        WORLD_RENDER_LAYER
+                    ));
+                });
+        });
+}
+
+pub(super) fn despawn_far_npcs(
+    mut commands: Commands,
+    query_player: Query<&EntityRect, With<Player>>,
+    query_npcs: Query<(Entity, &EntityRect), With<Npc>>,
+) {
+    let Ok(player_rect) = query_player.get_single() else { return; };
+
+    for (entity, npc_rect) in &query_npcs {
+        let distance = (npc_rect.center() - player_rect.center()).abs();
+
+        if distance.max_element() > DESPAWN_DISTANCE {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+pub(super) fn update_ai(
+    respawn_timer: Option<Res<RespawnTimer>>,
+    query_player: Query<&EntityRect, With<Player>>,
+    mut query_npcs: Query<(&Npc, &mut NpcAi, &mut Velocity, &EntityRect, &Collisions)>,
+) {
+    // The NPCs lose their target while the player is dead
+    let player_center = query_player.get_single().ok()
+        .filter(|_| respawn_timer.is_none())
+        .map(|player_rect| player_rect.center());
+
+    for (npc, mut ai, mut velocity, npc_rect, collisions) in &mut query_npcs {
+        let target = player_center.map(|center| center - npc_rect.center());
+
+        match npc.npc_type.definition().ai {
+            AiType::Slime => slime_ai(&mut ai, &mut velocity, collisions, target),
+            AiType::Walker => walker_ai(&mut ai, &mut velocity, collisions, target),
+            AiType::Flyer => flyer_ai(&mut ai, &mut velocity, collisions, target),
+        }
+    }
+}
+
+/// Waits on the ground and then hops towards the target
+fn slime_ai(ai: &mut NpcAi, velocity: &mut Velocity, collisions: &Collisions, target: Option<Vec2>) {
+    if !collisions.bottom { return; }
+
+    velocity.x = move_towards(velocity.x, 0., SLIME_FRICTION);
+
+    ai.timer += 1;
+    if ai.timer < SLIME_HOP_DELAY { return; }
+
+    ai.timer = 0;
+    ai.counter += 1;
+
+    if let Some(target) = target {
+        ai.direction = target.x.signum();
+    }
+
+    let hop_velocity = if ai.counter % 3 == 0 { SLIME_HIGH_HOP_VELOCITY } else { SLIME_HOP_VELOCITY };
+
+    velocity.0 = vec2(hop_velocity.x * ai.direction, hop_velocity.y);
+}
+
+/// Walks towards the target and jumps when a wall is in the way
+fn walker_ai(ai: &mut NpcAi, velocity: &mut Velocity, collisions: &Collisions, target: Option<Vec2>) {
+    // Don't turn around constantly when the target is right above or below
+    if let Some(target) = target.filter(|target| target.x.abs() > TILE_SIZE) {
+        ai.direction = target.x.signum();
+    }
+
+    velocity.x = move_towards(velocity.x, ai.direction * WALKER_MAX_SPEED, WALKER_ACCELERATION);
+
+    let is_blocked = (ai.direction < 0. && collisions.left) || (ai.direction > 0. && collisions.right);
+
+    if is_blocked && collisions.bottom {
+        velocity.y = WALKER_JUMP_SPEED;
+    }
+}
+
+/// Accelerates towards the target and bounces off the tiles
+fn flyer_ai(ai: &mut NpcAi, velocity: &mut Velocity, collisions: &Collisions, target: Option<Vec2>) {
+    let acceleration = match target {
+        Some(target) => target.normalize_or_zero() * FLYER_ACCELERATION,
+        None => vec2(ai.direction * FLYER_ACCELERATION, 0.),
+    };
+
+    velocity.0 = (velocity.0 + acceleration).clamp_length_max(FLYER_MAX_SPEED);
+
+    let normal = collisions.normal();
+    if normal.x != 0. { velocity.x = normal.x * FLYER_BOUNCE_SPEED; }
+    if normal.y != 0. { velocity.y = normal.y * FLYER_BOUNCE_SPEED; }
+
+    if velocity.x != 0. {
+        ai.direction = velocity.x.signum();
+    }
+}
+
+/// Shoots the projectiles of the NPCs with a ranged attack at the player when they are in range
+pub(super) fn shoot(
+    mut commands: Commands,
+    npc_assets: Res<NpcAssets>,
+    respawn_timer: Option<Res<RespawnTimer>>,
+    query_player: Query<&EntityRect, With<Player>>,
+    mut query_npcs: Query<(&Npc, &mut NpcAi, &EntityRect)>,
+) {
+    let player_center = query_player.get_single().ok()
+        .filter(|_| respawn_timer.is_none())
+        .map(|player_rect| player_rect.center());
+
+    for (npc, mut ai, npc_rect) in &mut query_npcs {
+        let Some(attack) = &npc.npc_type.definition().attack else { continue; };
+
+        if ai.attack_cooldown > 0 {
+            ai.attack_cooldown -= 1;
+            continue;
+        }
+
+        let Some(player_center) = player_center else { continue; };
+
+        let direction = player_center - npc_rect.center();
+        if direction.length() > attack.range * TILE_SIZE { continue; }
+
+        // An NPC with an attack always has a projectile sprite
+        let texture = npc_assets.get_projectile_by_npc(npc.npc_type).unwrap();
+
+        commands.spawn_projectile(
+            ProjectileBuilder::from_definition(&attack.projectile, texture, npc_rect.center(), direction)
+                .with_damage(attack.damage, attack.knockback, 0.)
+                .hostile()
+        );
+
+        ai.attack_cooldown = attack.interval;
+    }
+}
+
+pub(super) fn gravity(
+    mut query: Query<(&Npc, &mut Velocity)>
+) {
+    for (npc, mut velocity) in &mut query {
+        if !npc.npc_type.definition().ai.has_gravity() { continue; }
+
+        velocity.y = (velocity.y - GRAVITY).max(-MAX_FALL_SPEED);
+    }
+}
+
+/// Damages the player and pushes them away when they touch an NPC
+pub(super) fn contact_damage(
+    query_player: Query<&EntityRect, (With<Player>, Without<Immunity>)>,
+    query_npcs: Query<(&Npc, &EntityRect)>,
+    mut damage_events: EventWriter<DamagePlayerEvent>,
+) {
+    let Ok(player_rect) = query_player.get_single() else { return; };
+
+    // The player is hit by one NPC at a time
+    let hit = query_npcs.iter().find(|(npc, npc_rect)| {
+        npc.npc_type.definition().damage > 0 && npc_rect.intersects(player_rect)
+    });
+
+    let Some((npc, npc_rect)) = hit else { return; };
+
+    let direction = if player_rect.centerx < npc_rect.centerx { -1. } else { 1. };
+
+    damage_events.send(DamagePlayerEvent {
+        damage: npc.npc_type.definition().damage,
+        knockback: Some(vec2(CONTACT_KNOCKBACK.x * direction, CONTACT_KNOCKBACK.y)),
+        source: DamageSource::Npc,
+    });
+}
+
+/// Burns the NPCs which get into lava
+pub(super) fn lava_damage(
+    mut commands: Commands,
+    world_data: Res<WorldData>,
+    query_npcs: Query<(Entity, &EntityRect), (With<Npc>, Without<Immunity>)>,
+    mut damage_events: EventWriter<DamageNpcEvent>,
+) {
+    for (entity, npc_rect) in &query_npcs {
+        let tile_pos = get_tile_pos_from_world_coords(world_data.area.size(), npc_rect.center());
+
+        let in_lava = world_data.get_liquid(tile_pos).is_some_and(|liquid| liquid.liquid_type == LiquidType::Lava);
+
+        if in_lava {
+            damage_events.send(DamageNpcEvent { entity, damage: LAVA_DAMAGE, knockback: Vec2::ZERO, critical: false });
+            commands.entity(entity).insert(Immunity(Timer::new(LAVA_IMMUNITY_TIME, TimerMode::Once)));
+        }
+    }
+}
+
+pub(super) fn damage_npc(
+    mut commands: Commands,
+    fonts: Res<FontAssets>,
+    mut damage_events: EventReader<DamageNpcEvent>,
+    mut query_npcs: Query<(&Npc, &mut Health, &mut Velocity, &EntityRect)>,
+) {
+    let mut rng = thread_rng();
+
+    for event in damage_events.iter() {
+        let Ok((npc, mut health, mut velocity, npc_rect)) = query_npcs.get_mut(event.entity) else { continue; };
+
+        // The NPC has already been killed by another hit in this frame
+        if health.is_dead() { continue; }
+
+        let definition = npc.npc_type.definition();
+
+        health.damage(event.damage);
+
+        let color = if event.critical { DAMAGE_NUMBER_CRIT_COLOR } else { DAMAGE_NUMBER_COLOR };
+        let position = vec2(npc_rect.centerx, npc_rect.top());
+        spawn_damage_number(&mut commands, &fonts, position, event.damage, color, event.critical);
+
+        if event.knockback != Vec2::ZERO {
+            velocity.0 = event.knockback * (1. - definition.knockback_resistance);
+        }
+
+        if !health.is_dead() { continue; }
+
+        commands.entity(event.entity).despawn_recursive();
+
+        for loot in &definition.loot {
+            if !rng.gen_bool(loot.chance as f64) { continue; }
+
+            let stack = rng.gen_range(loot.min..=loot.max);
+
+            commands.spawn_dropped_item(
+                npc_rect.center(),
+                vec2(rng.gen_range(-2f32..=2.), rng.gen_range(1f32..=3.)),
+                ItemStack::new(loot.item).with_stack(stack),
+                None
+            );
+        }
+    }
+}
+
+/// Shows the health bar of an NPC once it has been damaged
+pub(super) fn update_health_bars(
+    query_npcs: Query<(&Health, &Children), (With<Npc>, Changed<Health>)>,
+    mut query_health_bar: Query<(&mut Visibility, &Children), With<HealthBar>>,
+    mut query_health_bar_fill: Query<(&mut Transform, &mut Sprite), With<HealthBarFill>>,
+) {
+    for (health, children) in &query_npcs {
+        let fraction = health.current as f32 / health.max as f32;
+
+        for &child in children {
+            let Ok((visibility, bar_children)) = query_health_bar.get_mut(child) else { continue; };
+
+            set_visibility(visibility, health.current < health.max);
+
+            for &bar_child in bar_children {
+                let Ok((mut transform, mut sprite)) = query_health_bar_fill.get_mut(bar_child) else { continue; };
+
+                transform.scale.x = fraction;
+                sprite.color = Color::rgb(1. - fraction, fraction, 0.);
+            }
+        }
+    }
+}
+
+pub(super) fn animate(
+    time: Res<Time>,
+    mut query: Query<(&Npc, &NpcAi, &mut NpcAnimationTimer, &mut TextureAtlasSprite)>
+) {
+    for (npc, ai, mut timer, mut sprite) in &mut query {
+        // The sprites face right
+        sprite.flip_x = ai.direction < 0.;
+
+        if timer.tick(time.delta()).just_finished() {
+            sprite.index = (sprite.index + 1) % npc.npc_type.definition().frames;
+        }
+    }
+}
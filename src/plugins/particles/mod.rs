@@ -4,7 +4,9 @@ pub(crate) mod components;
 use bevy::{prelude::{Plugin, App, FixedUpdate, Vec2, World, Transform, GlobalTransform, Visibility, ComputedVisibility, Commands, IntoSystemConfigs, Update, UVec2, Vec4}, ecs::system::Command, utils::default, sprite::TextureAtlasSprite, time::Time, render::view::RenderLayers};
 use rand::{thread_rng, Rng};
 
-use crate::{lighting::types::LightSource, world::{block::BlockType, wall::WallType}};
+use serde::Deserialize;
+
+use crate::lighting::types::LightSource;
 
 use self::components::{ParticleBundle, ParticleData};
 
@@ -36,30 +38,18 @@ impl Plugin for ParticlePlugin {
     }
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Deserialize)]
 pub(crate) enum Particle {
     Dirt,
     Stone,
     Grass,
-    Wood
-}
-
-impl Particle {
-    pub(crate) const fn get_by_block(block_type: BlockType) -> Option<Self> {
-        match block_type {
-            BlockType::Dirt => Some(Particle::Dirt),
-            BlockType::Stone => Some(Particle::Stone),
-            BlockType::Grass => Some(Particle::Grass),
-            BlockType::Tree(_) | BlockType::Wood => Some(Particle::Wood)
-        }
-    }
-
-    pub(crate) const fn get_by_wall(wall_type: WallType) -> Option<Self> {
-        match wall_type {
-            WallType::Dirt => Some(Particle::Dirt),
-            WallType::Stone => Some(Particle::Stone),
-        }
-    }
+    Wood,
+    CopperOre,
+    IronOre,
+    SilverOre,
+    GoldOre,
+    Sand,
+    Snow,
 }
 
 pub(crate) struct ParticleBuilder {
@@ -214,6 +204,12 @@ fn get_particle_index(particle: Particle, variant: u8) -> usize {
         Particle::Stone => 1,
         Particle::Grass => 2,
         Particle::Wood => 7,
+        Particle::IronOre => 8,
+        Particle::CopperOre => 9,
+        Particle::GoldOre => 10,
+        Particle::SilverOre => 11,
+        Particle::Sand => 32,
+        Particle::Snow => 51,
     };
     let variant = variant as usize;
 
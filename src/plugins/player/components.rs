@@ -1,8 +1,8 @@
 use bevy::{prelude::{Name, SpatialBundle, Transform, Component, Bundle}, utils::default};
 
-use crate::{common::{state::MovementState, rect::FRect}, PLAYER_LAYER, plugins::entity::components::{EntityRect, Velocity}};
+use crate::{common::{state::MovementState, rect::FRect}, PLAYER_LAYER, plugins::{entity::components::{EntityRect, Velocity, Health, Mana, Collider, Collisions}, world::constants::TILE_SIZE}};
 
-use super::{InputAxis, WALKING_ANIMATION_MAX_INDEX, PLAYER_HEIGHT, PLAYER_WIDTH};
+use super::{InputAxis, WALKING_ANIMATION_MAX_INDEX, PLAYER_HEIGHT, PLAYER_WIDTH, PLAYER_MAX_HEALTH, PLAYER_MAX_MANA, PLAYER_MANA_REGEN, PLAYER_PICKUP_RANGE};
 
 #[cfg(feature = "debug")]
 use bevy::prelude::{ReflectComponent, Reflect};
@@ -10,6 +10,16 @@ use bevy::prelude::{ReflectComponent, Reflect};
 #[derive(Component, Default)]
 pub(crate) struct Player;
 
+/// Size of the area around the player in which dropped items start flying to the player
+#[derive(Component, Clone, Copy)]
+pub(crate) struct PickupRange(pub(crate) f32);
+
+impl Default for PickupRange {
+    fn default() -> Self {
+        Self(PLAYER_PICKUP_RANGE)
+    }
+}
+
 #[derive(Default, PartialEq, Eq, Clone, Copy, Component)]
 #[cfg_attr(feature = "debug", derive(Reflect))]
 #[cfg_attr(feature = "debug", reflect(Component))]
@@ -100,6 +110,11 @@ pub(super) struct PlayerBundle {
     pub(super) face_direction: FaceDirection,
     pub(super) velocity: Velocity,
     pub(super) rect: EntityRect,
+    pub(super) health: Health,
+    pub(super) mana: Mana,
+    pub(super) pickup_range: PickupRange,
+    pub(super) collider: Collider,
+    pub(super) collisions: Collisions,
     pub(super) spatial: SpatialBundle
 }
 
@@ -108,6 +123,8 @@ impl PlayerBundle {
         Self {
             spatial: SpatialBundle::from_transform(Transform::from_xyz(x, y, PLAYER_LAYER)),
             rect: EntityRect(FRect::new_center(x, y, PLAYER_WIDTH, PLAYER_HEIGHT)),
+            // The player walks onto single blocks
+            collider: Collider { step_height: TILE_SIZE },
             ..default()
         }
     }
@@ -122,7 +139,12 @@ impl Default for PlayerBundle {
             face_direction: Default::default(),
             spatial: Default::default(),
             velocity: Default::default(),
-            rect: Default::default()
+            rect: Default::default(),
+            health: Health::new(PLAYER_MAX_HEALTH),
+            mana: Mana::new(PLAYER_MAX_MANA, PLAYER_MANA_REGEN),
+            pickup_range: Default::default(),
+            collider: Default::default(),
+            collisions: Default::default(),
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,27 @@
+use bevy::prelude::{Event, Vec2};
+
+/// What damaged the player
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum DamageSource {
+    Npc,
+    Projectile,
+    Fall,
+    Lava,
+}
+
+impl DamageSource {
+    /// Whether the damage is a hit, which the player's immunity protects from
+    #[inline]
+    pub(crate) const fn is_hit(self) -> bool {
+        matches!(self, Self::Npc | Self::Projectile)
+    }
+}
+
+/// Takes the amount of health from the player, the player dies when there is no health left
+#[derive(Event, Clone, Copy)]
+pub(crate) struct DamagePlayerEvent {
+    pub(crate) damage: i32,
+    /// The velocity the player is pushed with
+    pub(crate) knockback: Option<Vec2>,
+    pub(crate) source: DamageSource,
+}
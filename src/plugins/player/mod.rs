@@ -1,4 +1,5 @@
 mod components;
+mod events;
 mod resources;
 mod systems;
 mod utils;
@@ -6,12 +7,14 @@ pub(crate) mod body_sprites;
 
 use resources::*;
 pub(crate) use components::*;
+pub(crate) use events::*;
+pub(crate) use resources::RespawnTimer;
 
-use crate::{common::{state::{GameState, MovementState}, helpers::tile_to_world_pos, systems::{component_equals, despawn_with}}, plugins::player::utils::simple_animation, world::WorldData};
+use crate::{common::{state::{GameState, MovementState}, systems::{component_equals, despawn_with}}, plugins::player::utils::{simple_animation, get_spawn_position}, world::WorldData};
 use std::time::Duration;
 use bevy::{prelude::*, time::{Timer, TimerMode, common_conditions::on_timer}, math::vec2, input::InputSystem};
 
-use super::{assets::PlayerAssets, world::constants::TILE_SIZE, inventory::UseItemAnimationData, InGameSystemSet, entity::EntitySet, world_map_view::MapViewStatus};
+use super::{assets::PlayerAssets, world::constants::{WORLD_FILE_NAME, TILE_SIZE}, inventory::UseItemAnimationData, InGameSystemSet, entity::EntitySet, world_map_view::MapViewStatus, profile::PlayerProfile};
 
 #[cfg(feature = "debug")]
 use crate::plugins::debug::DebugConfiguration;
@@ -36,9 +39,34 @@ const JUMP_SPEED: f32 = 5.01;
 pub(crate) const MAX_WALK_SPEED: f32 = 3.;
 pub(crate) const MAX_FALL_SPEED: f32 = 10.;
 
+pub(crate) const PLAYER_MAX_HEALTH: i32 = 100;
+pub(crate) const PLAYER_MAX_MANA: i32 = 20;
+/// Mana points the player restores per second
+const PLAYER_MANA_REGEN: f32 = 2.;
+
+/// The default size of the area around the player in which dropped items are picked up
+const PLAYER_PICKUP_RANGE: f32 = 5.25 * TILE_SIZE;
+
+/// The distance in tiles the player can fall without taking damage
+const SAFE_FALL_DISTANCE: f32 = 25.;
+const FALL_DAMAGE_PER_TILE: f32 = 10.;
+
+const LAVA_DAMAGE: i32 = 40;
+/// The time between two burns of the player in lava
+const LAVA_DAMAGE_INTERVAL: Duration = Duration::from_millis(500);
+
+const RESPAWN_TIME: Duration = Duration::from_secs(5);
+
+const DAMAGE_NUMBER_COLOR: Color = Color::rgb(1., 0.25, 0.25);
+
+/// The time the player can't be damaged after taking damage
+const IMMUNITY_TIME: Duration = Duration::from_millis(660);
+
 pub(crate) struct PlayerPlugin;
 impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
+        app.add_event::<DamagePlayerEvent>();
+
         app.add_systems(OnEnter(GameState::InGame), (setup, spawn_player));
         app.add_systems(OnExit(GameState::InGame), (cleanup, despawn_with::<Player>));
 
@@ -64,7 +92,10 @@ impl Plugin for PlayerPlugin {
                 simple_animation::<IdleAnimationData>.run_if(component_equals::<Player, _>(MovementState::Idle)),
                 simple_animation::<FlyingAnimationData>.run_if(component_equals::<Player, _>(MovementState::Flying)),
                 systems::spawn_particles_on_walk.run_if(on_timer(Duration::from_secs_f32(1. / 20.))),
-                systems::spawn_particles_grounded
+                systems::spawn_particles_grounded,
+                systems::lava_damage.run_if(on_timer(LAVA_DAMAGE_INTERVAL)),
+                systems::damage_player,
+                systems::respawn_player.run_if(resource_exists::<RespawnTimer>()),
             )
             .in_set(InGameSystemSet::Update)
         );
@@ -92,20 +123,19 @@ impl Plugin for PlayerPlugin {
                     ).chain()
                 )
                 .before(EntitySet::UpdateEntityRect),
-
-                systems::detect_collisions
-                    .after(EntitySet::UpdateEntityRect)
-                    .before(EntitySet::MoveEntity),
             )
+            .run_if(not(resource_exists::<RespawnTimer>()))
             .in_set(InGameSystemSet::FixedUpdate)
         );
 
         app.add_systems(
             PostUpdate,
             (
+                systems::fall_damage,
                 systems::reset_fallstart,
                 systems::update_movement_state
             )
+            .chain()
             .in_set(InGameSystemSet::PostUpdate)
         );
 
@@ -131,7 +161,6 @@ impl Plugin for PlayerPlugin {
 }
 
 fn setup(mut commands: Commands) {
-    commands.init_resource::<Collisions>();
     commands.init_resource::<PlayerData>();
     commands.insert_resource(InputAxis::default());
     commands.insert_resource(MovementAnimationIndex::default());
@@ -139,8 +168,8 @@ fn setup(mut commands: Commands) {
 }
 
 fn cleanup(mut commands: Commands) {
-    commands.remove_resource::<Collisions>();
     commands.remove_resource::<PlayerData>();
+    commands.remove_resource::<RespawnTimer>();
     commands.remove_resource::<InputAxis>();
     commands.remove_resource::<MovementAnimationIndex>();
     commands.remove_resource::<MovementAnimationTimer>();
@@ -149,11 +178,12 @@ fn cleanup(mut commands: Commands) {
 fn spawn_player(
     mut commands: Commands,
     player_assets: Res<PlayerAssets>,
-    world_data: Res<WorldData>
+    world_data: Res<WorldData>,
+    profile: Option<Res<PlayerProfile>>
 ) {
-    let spawn_point = tile_to_world_pos(world_data.spawn_point) 
-        + TILE_SIZE / 2.
-        + vec2(PLAYER_HALF_WIDTH, PLAYER_HALF_HEIGHT);
+    let spawn_point = profile
+        .and_then(|profile| profile.position(WORLD_FILE_NAME))
+        .unwrap_or_else(|| get_spawn_position(&world_data));
 
     commands
         .spawn(PlayerBundle::new(spawn_point.x, spawn_point.y))
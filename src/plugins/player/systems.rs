@@ -1,20 +1,21 @@
-use std::f32::consts::PI;
+use std::{f32::consts::PI, time::Duration};
 
-use bevy::{prelude::*, sprite::Anchor};
+use bevy::{prelude::*, sprite::Anchor, ecs::query::Has};
 use rand::{thread_rng, Rng};
 
 use crate::{
     plugins::{
         world::{constants::TILE_SIZE, WORLD_RENDER_LAYER},
-        inventory::{ItemInHand, SwingAnimation}, particles::{ParticleCommandsExt, Particle, PARTICLE_SIZE, ParticleBuilder}, entity::components::{EntityRect, Velocity},
+        inventory::{ItemInHand, SwingAnimation, Inventory}, particles::{ParticleCommandsExt, PARTICLE_SIZE, ParticleBuilder}, entity::{spawn_damage_number, components::{EntityRect, Velocity, Health, Mana, Immunity, Collisions}},
+        item::ItemCommandsExt, profile::PlayerProfile, assets::FontAssets,
     },
-    common::{math::{move_towards, map_range_usize}, state::MovementState, rect::FRect, helpers::{random_point_cone, random_point_circle}}, world::WorldData,
+    common::{math::{move_towards, map_range_usize}, state::MovementState, helpers::{random_point_cone, random_point_circle, get_tile_pos_from_world_coords}}, world::{WorldData, liquid::LiquidType},
 };
 
 #[cfg(feature = "debug")]
 use bevy_inspector_egui::bevy_egui::EguiContexts;
 
-use super::{*, utils::get_fall_distance, body_sprites::{ChangeFlip, PlayerSpriteBody}};
+use super::{*, utils::{get_fall_distance, get_spawn_position}, body_sprites::{ChangeFlip, PlayerSpriteBody}};
 
 pub(super) fn horizontal_movement(
     axis: Res<InputAxis>,
@@ -40,9 +41,8 @@ pub(super) fn horizontal_movement(
 
 pub(super) fn update_jump(
     input: Res<Input<KeyCode>>,
-    collisions: Res<Collisions>,
     mut player_data: ResMut<PlayerData>,
-    mut query_player: Query<&mut Velocity, With<Player>>,
+    mut query_player: Query<(&mut Velocity, &Collisions), With<Player>>,
     mut jump: Local<i32>,
     #[cfg(feature = "debug")] mut egui: EguiContexts
 ) {
@@ -52,7 +52,7 @@ pub(super) fn update_jump(
     #[cfg(feature = "debug")]
     if ctx.wants_keyboard_input() { return; }
 
-    let Ok(mut velocity) = query_player.get_single_mut() else { return; };
+    let Ok((mut velocity, collisions)) = query_player.get_single_mut() else { return; };
     
     // TODO: Call just_pressed instead when https://github.com/bevyengine/bevy/issues/6183 is fixed
     if input.pressed(KeyCode::Space) && collisions.bottom {
@@ -77,11 +77,10 @@ pub(super) fn update_jump(
 }
 
 pub(super) fn gravity(
-    collisions: Res<Collisions>,
     mut player_data: ResMut<PlayerData>,
-    mut query_player: Query<(&mut Velocity, &EntityRect), With<Player>>
+    mut query_player: Query<(&mut Velocity, &EntityRect, &Collisions), With<Player>>
 ) {
-    let Ok((mut velocity, position)) = query_player.get_single_mut() else { return; };
+    let Ok((mut velocity, position, collisions)) = query_player.get_single_mut() else { return; };
 
     const DIRECTION: f32 = -1.0;
 
@@ -100,155 +99,11 @@ pub(super) fn gravity(
     }
 }
 
-pub(super) fn detect_collisions(
-    world_data: Res<WorldData>,
-    mut collisions: ResMut<Collisions>,
-    mut player_data: ResMut<PlayerData>,
-    mut query_player: Query<(&mut EntityRect, &mut Velocity, &FaceDirection), With<Player>>,
-    #[cfg(feature = "debug")]
-    mut gizmos: Gizmos,
-    #[cfg(feature = "debug")]
-    debug_config: Res<DebugConfiguration>,
-) {
-    let Ok((
-        mut player_rect, mut velocity, face_direction
-    )) = query_player.get_single_mut() else { return; };
-
-    let pos = player_rect.center();
-    let next_position = pos + velocity.0;
-
-    let next_rect = FRect::new_center(next_position.x, next_position.y, player_rect.width(), player_rect.height());
-
-    let left = (player_rect.left() / TILE_SIZE) - 1.;
-    let right = (player_rect.right() / TILE_SIZE) + 2.;
-    let mut top = (player_rect.top().abs() / TILE_SIZE) - 1.;
-    let bottom = (player_rect.bottom().abs() / TILE_SIZE) + 2.;
-
-    top = top.max(0.);
-
-    let left_u32 = left as u32;
-    let right_u32 = right as u32;
-    let top_u32 = top as u32;
-    let bottom_u32 = bottom as u32;
-
-    let mut new_collisions = Collisions::default();
-    player_data.ground = None;
-
-    'outer: for x in left_u32..right_u32 {
-        for y in top_u32..bottom_u32 {
-            if y >= world_data.playable_area.max.y || world_data.solid_block_exists((x, y)) {
-                let tile_rect = FRect::new_center(
-                    x as f32 * TILE_SIZE + TILE_SIZE / 2.,
-                    -(y as f32 * TILE_SIZE + TILE_SIZE / 2.),
-                    TILE_SIZE,
-                    TILE_SIZE
-                );
-
-                if next_rect.intersects(&tile_rect) {
-                    let delta_x = tile_rect.centerx - player_rect.centerx;
-                    let delta_y = if player_rect.centery < tile_rect.centery {
-                        player_rect.top().abs() + (tile_rect.top() + tile_rect.height() / 2.)
-                    } else {
-                        player_rect.bottom().abs() + (tile_rect.bottom() - tile_rect.height() / 2.)
-                    };
-
-                    if delta_x.abs() > delta_y.abs() {
-                        // Step up
-                        {
-                            // Check if there is a space of 3 blocks to move the player up
-                            let is_enough_space = !world_data.solid_block_exists((x, y - 1))
-                                && !world_data.solid_block_exists((x, y - 2))
-                                && !world_data.solid_block_exists((x, y - 3));
-
-                            // Check if the tile is on the same level as player's legs
-                            let is_bottom_tile = tile_rect.top() <= player_rect.bottom() + TILE_SIZE + GRAVITY
-                                && tile_rect.top() > player_rect.bottom() + GRAVITY;
-
-                            let direction = f32::from(face_direction);
-
-                            if is_enough_space && is_bottom_tile && direction == delta_x.signum() && velocity.x.abs() > 0. {
-                                new_collisions.bottom = true;
-                                velocity.y = 1.;
-                                velocity.x = velocity.x.abs().max(0.5) * direction;
-                                break 'outer;
-                            }
-                        }
-
-                        if delta_x < 0. {
-                            if player_rect.left() <= tile_rect.right() {
-                                new_collisions.left = true;
-
-                                velocity.x = 0.;
-                                // If the player's left side is more to the left than the tile's right side then move the player right.
-                                player_rect.centerx += tile_rect.right() - player_rect.left();
-                            }
-
-                            #[cfg(feature = "debug")]
-                            if debug_config.show_collisions {
-                                tile_rect.draw_right_side(&mut gizmos, Color::BLUE);
-                            }
-                        } else {
-                            if player_rect.right() >= tile_rect.left() {
-                                new_collisions.right = true;
-
-                                velocity.x = 0.;
-                                // If the player's right side is more to the right than the tile's left side then move the player left.
-                                player_rect.centerx += tile_rect.left() - player_rect.right();
-                            }
-
-                            #[cfg(feature = "debug")]
-                            if debug_config.show_collisions {
-                                tile_rect.draw_left_side(&mut gizmos, Color::GREEN);
-                            }
-                        }
-                    } else {
-                        // Checking for collisions again with an offset to workaround the bug when the player stuck in a wall.
-                        if FRect::new(next_rect.left() + 2.0, next_rect.top(), PLAYER_WIDTH - 4.0, PLAYER_HEIGHT).intersects(&tile_rect) {
-                            if delta_y > 0. {
-                                if player_rect.top() >= tile_rect.bottom() {
-                                    new_collisions.top = true;
-                                    
-                                    velocity.y = 0.;
-                                    // If the player's top side is higher than the tile's bottom side then move the player down.
-                                    player_rect.centery += tile_rect.bottom() - player_rect.top();
-                                }
-
-                                #[cfg(feature = "debug")]
-                                if debug_config.show_collisions {
-                                    tile_rect.draw_bottom_side(&mut gizmos, Color::YELLOW);
-                                }
-                            } else {
-                                if player_rect.bottom() <= tile_rect.top() {
-                                    new_collisions.bottom = true;
-                                    player_data.jumping = false;
-                                    player_data.ground = world_data.get_block((x, y)).map(|b| b.block_type);
-
-                                    velocity.y = 0.;
-                                    // If the player's bottom side is lower than the tile's top side then move the player up
-                                    player_rect.centery += tile_rect.top() - player_rect.bottom();
-                                }
-
-                                #[cfg(feature = "debug")]
-                                if debug_config.show_collisions {
-                                    tile_rect.draw_top_side(&mut gizmos, Color::RED);
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-        }
-    }
-
-    *collisions = new_collisions;
-}
-
 pub(super) fn update_movement_state(
-    collisions: Res<Collisions>,
     player_data: Res<PlayerData>,
-    mut query: Query<(&EntityRect, &Velocity, &mut MovementState), With<Player>>,
+    mut query: Query<(&EntityRect, &Velocity, &Collisions, &mut MovementState), With<Player>>,
 ) {
-    let Ok((player_rect, velocity, mut movement_state)) = query.get_single_mut() else { return; };
+    let Ok((player_rect, velocity, collisions, mut movement_state)) = query.get_single_mut() else { return; };
 
     let fall_distance = get_fall_distance(player_rect.bottom(), player_data.fall_start);
 
@@ -261,17 +116,17 @@ pub(super) fn update_movement_state(
 
 pub(super) fn spawn_particles_on_walk(
     mut commands: Commands,
-    player_data: Res<PlayerData>,
-    query_player: Query<(&MovementState, &FaceDirection, &Velocity, &EntityRect), With<Player>>,
+    query_player: Query<(&MovementState, &FaceDirection, &Velocity, &EntityRect, &Collisions), With<Player>>,
 ) {
-    let (movement_state, face_direction, velocity, rect) = query_player.single();
+    let (movement_state, face_direction, velocity, rect, collisions) = query_player.single();
 
     if *movement_state != MovementState::Walking { return; }
 
-    let Some(ground_block) = player_data.ground else { return; };
+    let Some(ground_block) = collisions.ground else { return; };
     if !ground_block.dusty() { return; }
     
-    let particle = Particle::get_by_block(ground_block).unwrap();
+    // A dusty block always has a particle, it's checked when the tiles are loaded
+    let particle = ground_block.definition().particle.unwrap();
     
     let direction = match face_direction {
         FaceDirection::Left => vec2(1., 0.),
@@ -298,14 +153,13 @@ pub(super) fn spawn_particles_on_walk(
 
 pub(super) fn spawn_particles_grounded(
     mut commands: Commands,
-    collisions: Res<Collisions>,
     player_data: Res<PlayerData>,
-    query_player: Query<&EntityRect, With<Player>>,
+    query_player: Query<(&EntityRect, &Collisions), With<Player>>,
     mut prev_grounded: Local<bool>
 ) {
-    let rect = query_player.single();
+    let (rect, collisions) = query_player.single();
 
-    let Some(ground_block) = player_data.ground else {
+    let Some(ground_block) = collisions.ground else {
         *prev_grounded = collisions.bottom;
         return;
     };
@@ -315,7 +169,8 @@ pub(super) fn spawn_particles_grounded(
         return;
     }
 
-    let particle = Particle::get_by_block(ground_block).unwrap();
+    // A dusty block always has a particle, it's checked when the tiles are loaded
+    let particle = ground_block.definition().particle.unwrap();
 
     let fall_distance = get_fall_distance(rect.bottom(), player_data.fall_start);
 
@@ -442,12 +297,138 @@ pub(super) fn walking_animation(
     });
 }
 
+pub(super) fn fall_damage(
+    player_data: Res<PlayerData>,
+    query_player: Query<(&EntityRect, &Collisions), With<Player>>,
+    mut damage_events: EventWriter<DamagePlayerEvent>,
+) {
+    let Ok((rect, collisions)) = query_player.get_single() else { return; };
+
+    if !collisions.bottom { return; }
+
+    let fall_distance = get_fall_distance(rect.bottom(), player_data.fall_start) / TILE_SIZE;
+
+    if fall_distance > SAFE_FALL_DISTANCE {
+        let damage = ((fall_distance - SAFE_FALL_DISTANCE) * FALL_DAMAGE_PER_TILE) as i32;
+        damage_events.send(DamagePlayerEvent { damage, knockback: None, source: DamageSource::Fall });
+    }
+}
+
+/// Burns the player while they are in lava
+pub(super) fn lava_damage(
+    world_data: Res<WorldData>,
+    query_player: Query<&EntityRect, With<Player>>,
+    mut damage_events: EventWriter<DamagePlayerEvent>,
+) {
+    let Ok(rect) = query_player.get_single() else { return; };
+
+    let tile_pos = get_tile_pos_from_world_coords(world_data.area.size(), rect.center());
+
+    if world_data.get_liquid(tile_pos).is_some_and(|liquid| liquid.liquid_type == LiquidType::Lava) {
+        damage_events.send(DamagePlayerEvent { damage: LAVA_DAMAGE, knockback: None, source: DamageSource::Lava });
+    }
+}
+
+pub(super) fn damage_player(
+    mut commands: Commands,
+    fonts: Res<FontAssets>,
+    mut inventory: ResMut<Inventory>,
+    profile: Option<Res<PlayerProfile>>,
+    mut damage_events: EventReader<DamagePlayerEvent>,
+    mut query_player: Query<(Entity, &mut Health, &mut Velocity, &mut Visibility, &EntityRect, Has<Immunity>), With<Player>>,
+) {
+    let mut damage = 0;
+    let mut hit_damage = 0;
+    let mut knockback = None;
+
+    for event in damage_events.iter() {
+        if event.source.is_hit() {
+            hit_damage += event.damage;
+            knockback = event.knockback.or(knockback);
+        } else {
+            damage += event.damage;
+        }
+    }
+
+    let Ok((entity, mut health, mut velocity, mut visibility, rect, is_immune)) = query_player.get_single_mut() else { return; };
+
+    // The immunity only protects from hits, falling and lava still damage the player
+    if is_immune {
+        knockback = None;
+    } else {
+        damage += hit_damage;
+    }
+
+    if damage <= 0 || health.is_dead() { return; }
+
+    health.damage(damage);
+
+    spawn_damage_number(&mut commands, &fonts, vec2(rect.centerx, rect.top()), damage, DAMAGE_NUMBER_COLOR, false);
+
+    if !health.is_dead() {
+        if let Some(knockback) = knockback {
+            velocity.0 = knockback;
+        }
+
+        commands.entity(entity).insert(Immunity(Timer::new(IMMUNITY_TIME, TimerMode::Once)));
+        return;
+    }
+
+    velocity.0 = Vec2::ZERO;
+    *visibility = Visibility::Hidden;
+
+    commands.insert_resource(RespawnTimer(Timer::new(RESPAWN_TIME, TimerMode::Once)));
+
+    if profile.is_some_and(|profile| profile.drop_items_on_death) {
+        let mut rng = thread_rng();
+
+        for item_stack in inventory.take_all_items() {
+            commands.spawn_dropped_item(
+                rect.center(),
+                vec2(rng.gen_range(-2f32..=2.), rng.gen_range(1f32..=4.)),
+                item_stack,
+                Some(Timer::new(Duration::from_secs_f32(1.5), TimerMode::Once))
+            );
+        }
+    }
+}
+
+pub(super) fn respawn_player(
+    mut commands: Commands,
+    time: Res<Time>,
+    world_data: Res<WorldData>,
+    mut respawn_timer: ResMut<RespawnTimer>,
+    mut player_data: ResMut<PlayerData>,
+    mut query_player: Query<(&mut Health, &mut Mana, &mut EntityRect, &mut Velocity, &mut Visibility), With<Player>>,
+) {
+    if !respawn_timer.tick(time.delta()).just_finished() { return; }
+
+    commands.remove_resource::<RespawnTimer>();
+
+    let Ok((mut health, mut mana, mut rect, mut velocity, mut visibility)) = query_player.get_single_mut() else { return; };
+
+    let spawn_position = get_spawn_position(&world_data);
+
+    rect.centerx = spawn_position.x;
+    rect.centery = spawn_position.y;
+
+    health.restore();
+    mana.restore();
+    velocity.0 = Vec2::ZERO;
+    *visibility = Visibility::Inherited;
+    *player_data = PlayerData::default();
+}
+
+/// Ends the fall and the jump of the player when they land
 pub(super) fn reset_fallstart(
-    collisions: Res<Collisions>,
-    mut player_data: ResMut<PlayerData>
+    mut player_data: ResMut<PlayerData>,
+    query_player: Query<&Collisions, With<Player>>,
 ) {
+    let Ok(collisions) = query_player.get_single() else { return; };
+
     if collisions.bottom {
         player_data.fall_start = None;
+        player_data.jumping = false;
     }
 }
 
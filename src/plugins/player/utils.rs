@@ -1,8 +1,8 @@
-use bevy::{prelude::{Query, With, Component, Res}, sprite::TextureAtlasSprite};
+use bevy::{prelude::{Query, With, Component, Res, Vec2}, sprite::TextureAtlasSprite, math::vec2};
 
-use crate::plugins::inventory::{UseItemAnimationData, SwingAnimation};
+use crate::{plugins::{inventory::{UseItemAnimationData, SwingAnimation}, world::constants::TILE_SIZE}, common::helpers::tile_to_world_pos, world::WorldData};
 
-use super::{AnimationData, body_sprites::PlayerSpriteBody};
+use super::{AnimationData, body_sprites::PlayerSpriteBody, PLAYER_HALF_WIDTH, PLAYER_HALF_HEIGHT};
 
 pub(super) fn simple_animation<C: AnimationData + Component>(
     swing_animation: Res<SwingAnimation>,
@@ -18,4 +18,11 @@ pub(super) fn simple_animation<C: AnimationData + Component>(
 #[inline]
 pub(super) fn get_fall_distance(position: f32, fall_start: Option<f32>) -> f32 {
     fall_start.map(|fs| (position - fs).abs()).unwrap_or(0.)
-}
\ No newline at end of file
+}
+
+/// The position of the player's center when the player stands at the world's spawn point
+pub(super) fn get_spawn_position(world_data: &WorldData) -> Vec2 {
+    tile_to_world_pos(world_data.spawn_point)
+        + TILE_SIZE / 2.
+        + vec2(PLAYER_HALF_WIDTH, PLAYER_HALF_HEIGHT)
+}
@@ -0,0 +1,264 @@
+use std::{fs::{File, OpenOptions}, io::{BufReader, BufWriter, Write}, error::Error, collections::HashMap, path::Path};
+
+use bevy::{prelude::{Plugin, App, IntoSystemConfigs, Update, Res, ResMut, Resource, OnEnter, OnExit, Query, With, Commands, Vec2, on_event}, window::WindowCloseRequested};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::common::state::GameState;
+
+use super::{inventory::{Inventory, InventoryData}, player::Player, entity::components::EntityRect, world::constants::WORLD_FILE_NAME, InGameSystemSet};
+
+const PROFILE_FILENAME: &str = "player.json";
+
+/// The profile is written here first and then moved over the old one, so a failed write doesn't destroy it
+const PROFILE_TEMP_FILENAME: &str = "player.json.tmp";
+
+/// A profile which can't be read is moved here so that it isn't overwritten on exit.
+/// A number is appended when the file already exists, so older backups are kept.
+const PROFILE_BACKUP_FILENAME: &str = "player.json.bak";
+
+/// Version of the profile format.
+/// 0 is the format before the item registry, which stored the items as enums like `{"Block": "Dirt"}`.
+const PROFILE_VERSION: u64 = 1;
+
+#[derive(Resource, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub(crate) struct PlayerProfile {
+    #[serde(default)]
+    pub(crate) version: u64,
+    pub(crate) name: String,
+    pub(crate) inventory: Option<InventoryData>,
+    /// The last position of the player in each world, keyed by the world file name
+    pub(crate) positions: HashMap<String, [f32; 2]>,
+    /// Whether the player drops all items from the inventory on death
+    #[serde(default)]
+    pub(crate) drop_items_on_death: bool,
+    /// Set when the profile file couldn't be read nor moved away, it is left untouched then
+    #[serde(skip)]
+    keep_file: bool,
+}
+
+impl Default for PlayerProfile {
+    fn default() -> Self {
+        Self {
+            version: PROFILE_VERSION,
+            name: String::from("Player"),
+            inventory: None,
+            positions: HashMap::new(),
+            drop_items_on_death: false,
+            keep_file: false,
+        }
+    }
+}
+
+impl PlayerProfile {
+    pub(crate) fn position(&self, world_name: &str) -> Option<Vec2> {
+        self.positions.get(world_name).map(|&position| Vec2::from_array(position))
+    }
+}
+
+pub(crate) struct ProfilePlugin;
+impl Plugin for ProfilePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::WorldLoading), setup);
+
+        // Leaving to the menu exits the state, closing the window quits the app without exiting it,
+        // so every exit takes exactly one of these paths
+        app.add_systems(OnExit(GameState::InGame), on_exit);
+        app.add_systems(
+            Update,
+            on_exit
+                .run_if(on_event::<WindowCloseRequested>())
+                .in_set(InGameSystemSet::Update)
+        );
+    }
+}
+
+fn setup(mut commands: Commands) {
+    let profile = match load_profile() {
+        Ok(profile) => profile,
+        Err(err) => {
+            eprintln!("Failed to load the player profile: {}", err);
+
+            let mut profile = PlayerProfile::default();
+
+            let backup_filename = free_backup_filename();
+
+            match std::fs::rename(PROFILE_FILENAME, &backup_filename) {
+                Ok(()) => eprintln!("The player profile was moved to {}", backup_filename),
+                Err(err) => {
+                    eprintln!("Failed to move the player profile, it won't be saved: {}", err);
+                    profile.keep_file = true;
+                }
+            }
+
+            profile
+        }
+    };
+
+    commands.insert_resource(profile);
+}
+
+fn on_exit(
+    mut profile: ResMut<PlayerProfile>,
+    inventory: Res<Inventory>,
+    query_player: Query<&EntityRect, With<Player>>,
+) {
+    if profile.keep_file { return; }
+
+    profile.inventory = Some(InventoryData::from(inventory.as_ref()));
+
+    if let Ok(player_rect) = query_player.get_single() {
+        profile.positions.insert(WORLD_FILE_NAME.to_owned(), player_rect.center().to_array());
+    }
+
+    if let Err(err) = save_profile(&profile) {
+        eprintln!("Failed to save the player profile: {}", err);
+    }
+}
+
+fn load_profile() -> Result<PlayerProfile, Box<dyn Error>> {
+    let file = match OpenOptions::new().read(true).open(PROFILE_FILENAME) {
+        Ok(file) => file,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(PlayerProfile::default()),
+        Err(err) => return Err(err.into())
+    };
+
+    let reader = BufReader::new(file);
+
+    parse_profile(serde_json::from_reader(reader)?)
+}
+
+fn parse_profile(mut value: Value) -> Result<PlayerProfile, Box<dyn Error>> {
+    let version = value.get("Version").and_then(Value::as_u64).unwrap_or(0);
+
+    if version > PROFILE_VERSION {
+        return Err(format!("Unsupported profile version {}", version).into());
+    }
+
+    if version == 0 {
+        migrate_legacy_items(&mut value);
+    }
+
+    let mut profile: PlayerProfile = serde_json::from_value(value)?;
+    profile.version = PROFILE_VERSION;
+
+    Ok(profile)
+}
+
+/// Replaces the items stored as enums with their ids in the registry
+fn migrate_legacy_items(profile: &mut Value) {
+    let Some(inventory) = profile.get_mut("Inventory").and_then(Value::as_object_mut) else { return; };
+
+    for (key, value) in inventory.iter_mut() {
+        let item_stacks: Vec<&mut Value> = match key.as_str() {
+            "slots" => value.as_array_mut().into_iter().flatten().collect(),
+            "mouse_item" => vec![value],
+            _ => continue
+        };
+
+        for item_stack in item_stacks {
+            let Some(item) = item_stack.get_mut("item") else { continue; };
+
+            if let Some(id) = legacy_item_id(item) {
+                *item = Value::String(id);
+            }
+        }
+    }
+}
+
+fn legacy_item_id(item: &Value) -> Option<String> {
+    let (kind, value) = item.as_object()?.iter().next()?;
+
+    let name = match value {
+        Value::String(name) => name.as_str(),
+        // Tools were stored as {"Tool": {"Pickaxe": "CopperPickaxe"}}
+        Value::Object(tool) => tool.values().next()?.as_str()?,
+        _ => return None
+    };
+
+    let id = match (kind.as_str(), name) {
+        ("Tool", _) | ("Block", "Wood" | "CopperOre" | "IronOre" | "SilverOre" | "GoldOre") => name.to_owned(),
+        ("Block", _) => format!("{}Block", name),
+        ("Wall", _) => format!("{}Wall", name),
+        ("Seed", _) => format!("{}Seeds", name),
+        ("Furniture", "Workbench") => String::from("WorkBench"),
+        ("Furniture", "Door") => String::from("WoodenDoor"),
+        ("Furniture", _) => name.to_owned(),
+        _ => return None
+    };
+
+    Some(id)
+}
+
+/// Returns the first backup file name which isn't taken
+fn free_backup_filename() -> String {
+    std::iter::once(PROFILE_BACKUP_FILENAME.to_owned())
+        .chain((1..).map(|number| format!("{}.{}", PROFILE_BACKUP_FILENAME, number)))
+        .find(|filename| !Path::new(filename).exists())
+        .unwrap()
+}
+
+fn save_profile(profile: &PlayerProfile) -> Result<(), Box<dyn Error>> {
+    let file = File::create(PROFILE_TEMP_FILENAME)?;
+    let mut writer = BufWriter::new(file);
+
+    serde_json::to_writer(&mut writer, profile)?;
+    writer.flush()?;
+    writer.get_ref().sync_all()?;
+
+    std::fs::rename(PROFILE_TEMP_FILENAME, PROFILE_FILENAME)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::{items::Item, test_utils::load_registries};
+
+    use super::*;
+
+    #[test]
+    fn migrates_legacy_items() {
+        load_registries();
+
+        let legacy = json!({
+            "Name": "Player",
+            "Inventory": {
+                "slots": [
+                    { "item": { "Tool": { "Pickaxe": "CopperPickaxe" } }, "stack": 1 },
+                    { "item": { "Block": "Dirt" }, "stack": 25 },
+                    { "item": { "Block": "Wood" }, "stack": 3 },
+                    { "item": { "Wall": "Stone" }, "stack": 4 },
+                    { "item": { "Seed": "Grass" }, "stack": 5 },
+                    { "item": { "Furniture": "Door" }, "stack": 1 },
+                    null
+                ],
+                "mouse_item": { "item": { "Furniture": "Workbench" }, "stack": 2 },
+                "selected_slot": 1
+            },
+            "Positions": {}
+        });
+
+        let profile = parse_profile(legacy).unwrap();
+        assert_eq!(profile.version, PROFILE_VERSION);
+
+        let inventory = profile.inventory.unwrap();
+        let ids: Vec<Option<&str>> = inventory.slots.iter()
+            .map(|slot| slot.map(|item_stack| item_stack.item.definition().id.as_str()))
+            .collect();
+
+        assert_eq!(ids, [Some("CopperPickaxe"), Some("DirtBlock"), Some("Wood"), Some("StoneWall"), Some("GrassSeeds"), Some("WoodenDoor"), None]);
+        assert_eq!(inventory.slots[1].unwrap().stack, 25);
+        assert!(inventory.mouse_item.is_some_and(|item_stack| item_stack.item == Item::from_id("WorkBench").unwrap()));
+        assert_eq!(inventory.selected_slot, 1);
+    }
+
+    #[test]
+    fn rejects_newer_versions() {
+        let profile = json!({ "Version": PROFILE_VERSION + 1, "Name": "Player", "Positions": {} });
+        assert!(parse_profile(profile).is_err());
+    }
+}
@@ -0,0 +1,176 @@
+use autodefault::autodefault;
+use bevy::{prelude::{Plugin, App, Update, IntoSystemConfigs, Commands, Entity, Name, NodeBundle, ImageBundle, TextBundle, BuildChildren, Component, Query, With, Changed, Color}, ui::{Style, FlexDirection, AlignItems, UiRect, Val, BackgroundColor}, text::{Text, TextStyle, TextAlignment}};
+
+use crate::{plugins::{assets::{UiAssets, FontAssets}, player::{Player, PLAYER_MAX_HEALTH, PLAYER_MAX_MANA}, entity::components::{Health, Mana}, InGameSystemSet}, language::{keys::UIStringKey, LocalizedText, args}};
+
+const HEALTH_PER_HEART: i32 = 20;
+const HEART_SIZE: f32 = 22.;
+
+const MANA_PER_STAR: i32 = 20;
+const STAR_SIZE: f32 = 22.;
+
+/// The opacity of a heart which has no health left in it
+const EMPTY_HEART_ALPHA: f32 = 0.3;
+
+#[derive(Component)]
+struct LifeText;
+
+/// A heart in the health bar, holds the index of the heart from the left
+#[derive(Component)]
+struct Heart(i32);
+
+/// A star in the mana bar next to the hearts, holds the index of the star from the top
+#[derive(Component)]
+struct ManaStar(i32);
+
+pub(in crate::plugins::ui) struct HealthUiPlugin;
+impl Plugin for HealthUiPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                update_life_text,
+                update_hearts,
+                update_mana_stars,
+            )
+            .in_set(InGameSystemSet::Update)
+        );
+    }
+}
+
+#[autodefault]
+pub(in crate::plugins::ui) fn spawn_health_bar(
+    commands: &mut Commands,
+    ui_assets: &UiAssets,
+    fonts: &FontAssets,
+) -> Entity {
+    commands
+        .spawn((
+            Name::new("Health Bar"),
+            NodeBundle {
+                style: Style {
+                    flex_direction: FlexDirection::Row,
+                    align_items: AlignItems::FlexEnd,
+                    padding: UiRect::all(Val::Px(5.)),
+                },
+            }
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                    },
+                })
+                .with_children(|life| {
+                    life.spawn((
+                        Name::new("Life Text"),
+                        LifeText,
+                        LocalizedText::new(UIStringKey::Life, "{}: {}/{}", args![PLAYER_MAX_HEALTH, PLAYER_MAX_HEALTH]),
+                        TextBundle {
+                            style: Style {
+                                margin: UiRect::bottom(Val::Px(2.)),
+                            },
+                            text: Text::from_section(
+                                String::new(),
+                                TextStyle {
+                                    font: fonts.andy_bold.clone_weak(),
+                                    font_size: 20.,
+                                    color: Color::WHITE,
+                                }
+                            ).with_alignment(TextAlignment::Center),
+                        }
+                    ));
+
+                    life
+                        .spawn(NodeBundle {
+                            style: Style {
+                                flex_direction: FlexDirection::Row,
+                            },
+                        })
+                        .with_children(|hearts| {
+                            for index in 0..(PLAYER_MAX_HEALTH / HEALTH_PER_HEART) {
+                                hearts.spawn((
+                                    Name::new(format!("Heart #{}", index)),
+                                    Heart(index),
+                                    ImageBundle {
+                                        style: Style {
+                                            width: Val::Px(HEART_SIZE),
+                                            height: Val::Px(HEART_SIZE),
+                                            margin: UiRect::horizontal(Val::Px(1.)),
+                                        },
+                                        image: ui_assets.heart.clone_weak().into(),
+                                    }
+                                ));
+                            }
+                        });
+                });
+
+            parent
+                .spawn((
+                    Name::new("Mana Bar"),
+                    NodeBundle {
+                        style: Style {
+                            flex_direction: FlexDirection::Column,
+                            margin: UiRect::left(Val::Px(4.)),
+                        },
+                    }
+                ))
+                .with_children(|stars| {
+                    for index in 0..(PLAYER_MAX_MANA / MANA_PER_STAR) {
+                        stars.spawn((
+                            Name::new(format!("Mana Star #{}", index)),
+                            ManaStar(index),
+                            ImageBundle {
+                                style: Style {
+                                    width: Val::Px(STAR_SIZE),
+                                    height: Val::Px(STAR_SIZE),
+                                    margin: UiRect::vertical(Val::Px(1.)),
+                                },
+                                image: ui_assets.mana.clone_weak().into(),
+                            }
+                        ));
+                    }
+                });
+        })
+        .id()
+}
+
+fn update_life_text(
+    query_player: Query<&Health, (With<Player>, Changed<Health>)>,
+    mut query_text: Query<&mut LocalizedText, With<LifeText>>,
+) {
+    let Ok(health) = query_player.get_single() else { return; };
+    let Ok(mut localized_text) = query_text.get_single_mut() else { return; };
+
+    *localized_text = LocalizedText::new(UIStringKey::Life, "{}: {}/{}", args![health.current, health.max]);
+}
+
+fn update_hearts(
+    query_player: Query<&Health, (With<Player>, Changed<Health>)>,
+    mut query_hearts: Query<(&Heart, &mut BackgroundColor)>,
+) {
+    let Ok(health) = query_player.get_single() else { return; };
+
+    for (heart, mut background_color) in &mut query_hearts {
+        // The hearts are filled from the left, the last one which isn't empty may be filled partially
+        let fill = (health.current - heart.0 * HEALTH_PER_HEART) as f32 / HEALTH_PER_HEART as f32;
+
+        background_color.0 = Color::WHITE.with_a(EMPTY_HEART_ALPHA + (1. - EMPTY_HEART_ALPHA) * fill.clamp(0., 1.));
+    }
+}
+
+fn update_mana_stars(
+    query_player: Query<&Mana, (With<Player>, Changed<Mana>)>,
+    mut query_stars: Query<(&ManaStar, &mut BackgroundColor)>,
+) {
+    let Ok(mana) = query_player.get_single() else { return; };
+
+    for (star, mut background_color) in &mut query_stars {
+        // The stars are filled from the top like the hearts are from the left
+        let fill = (mana.current - (star.0 * MANA_PER_STAR) as f32) / MANA_PER_STAR as f32;
+
+        background_color.0 = Color::WHITE.with_a(EMPTY_HEART_ALPHA + (1. - EMPTY_HEART_ALPHA) * fill.clamp(0., 1.));
+    }
+}
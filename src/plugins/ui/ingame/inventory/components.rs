@@ -31,4 +31,32 @@ pub(super) struct SlotIndex(pub usize);
 pub(super) struct SlotItemImage(pub Handle<Image>);
 
 #[derive(Component, Default, PartialEq, Eq)]
-pub(super) struct ItemAmount(pub Stack);
\ No newline at end of file
+pub(super) struct ItemAmount(pub Stack);
+
+#[derive(Component)]
+pub(super) struct CraftingList(pub Vec<usize>);
+
+#[derive(Component)]
+pub(super) struct RecipeSlot(pub usize);
+
+/// The container which holds the item shown in a slot
+#[derive(Component, Clone, Copy, Default, PartialEq, Eq)]
+pub(super) enum SlotOwner {
+    #[default]
+    Inventory,
+    Chest,
+    Trash,
+}
+
+#[derive(Component)]
+pub(super) struct ChestUi;
+
+#[derive(Component)]
+pub(super) struct SortInventoryButton;
+
+#[derive(Component, Clone, Copy)]
+pub(super) enum ChestButton {
+    LootAll,
+    DepositAll,
+    QuickStack,
+}
@@ -1,9 +1,9 @@
 pub(in crate::plugins::ui) mod systems;
 pub(crate) mod components;
 
-use bevy::prelude::{Plugin, App, IntoSystemConfigs, resource_exists_and_changed, resource_added, Update, Condition};
+use bevy::{prelude::{Plugin, App, IntoSystemConfigs, resource_exists_and_changed, resource_added, resource_exists, Update, Condition, MouseButton}, input::common_conditions::input_just_pressed};
 
-use crate::{common::{systems::{bind_visibility2_to, bind_not_visibility_to}, conditions::is_visible}, plugins::{inventory::Inventory, InGameSystemSet, ui::{resources::{IsVisible, Ui}, SettingsMenu}}};
+use crate::{common::{systems::{bind_visibility2_to, bind_not_visibility_to}, conditions::is_visible}, items::CHEST_SLOT_COUNT, plugins::{inventory::{Inventory, NearbyCraftingStations, OpenChest}, InGameSystemSet, ui::{resources::{IsVisible, Ui}, SettingsMenu}}};
 
 use self::{systems::return_mouse_item_back_to_inventory, components::InventoryUi};
 
@@ -13,8 +13,13 @@ const HOTBAR_SLOT_SIZE: f32 = 40.;
 const INVENTORY_SLOT_SIZE: f32 = HOTBAR_SLOT_SIZE * 1.1;
 const HOTBAR_SLOT_SIZE_SELECTED: f32 = HOTBAR_SLOT_SIZE * 1.3;
 
+/// Number of recipes in a column of the crafting panel
+const CRAFTING_COLUMN_SLOTS: usize = 5;
+
 pub(crate) const SLOT_COUNT_IN_ROW: usize = 10;
 
+const CHEST_ROWS: usize = CHEST_SLOT_COUNT / SLOT_COUNT_IN_ROW;
+
 pub(in crate::plugins::ui) struct InventoryUiPlugin;
 impl Plugin for InventoryUiPlugin {
     fn build(&self, app: &mut App) {
@@ -43,12 +48,26 @@ impl Plugin for InventoryUiPlugin {
                     ).chain()
                 )
                 .run_if(
-                    resource_exists_and_changed::<Inventory>().or_else(resource_added::<Inventory>())
+                    resource_exists_and_changed::<Inventory>()
+                        .or_else(resource_added::<Inventory>())
+                        .or_else(resource_exists_and_changed::<OpenChest>())
                 ),
 
+                systems::update_chest_ui_display,
+
+                systems::update_crafting_list
+                    .run_if(
+                        resource_exists_and_changed::<Inventory>()
+                            .or_else(resource_exists_and_changed::<NearbyCraftingStations>())
+                    ),
+
                 (
                     systems::take_item,
                     systems::put_item,
+                    systems::craft_item,
+                    systems::split_item.run_if(input_just_pressed(MouseButton::Right)),
+                    systems::sort_inventory,
+                    systems::use_chest_button.run_if(resource_exists::<OpenChest>()),
                 )
                 .run_if(is_visible::<InventoryUi>),
 
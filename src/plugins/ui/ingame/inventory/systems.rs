@@ -1,9 +1,12 @@
+use std::time::Duration;
+
 use autodefault::autodefault;
-use bevy::{prelude::{Commands, Name, NodeBundle, BuildChildren, TextBundle, Color, Entity, ImageBundle, default, ChildBuilder, Handle, Image, Visibility, With, Res, Query, DetectChanges, Changed, ResMut, DetectChangesMut, Without, Ref, Vec2}, ui::{Style, FlexDirection, UiRect, Val, AlignSelf, AlignItems, JustifyContent, Interaction, BackgroundColor, FocusPolicy, AlignContent, PositionType, UiImage, widget::UiImageSize}, text::{Text, TextStyle, TextAlignment}};
+use bevy::{prelude::{Commands, Name, NodeBundle, BuildChildren, TextBundle, Color, Entity, ImageBundle, default, ChildBuilder, Handle, Image, Visibility, With, Res, Query, DetectChanges, Changed, ResMut, DetectChangesMut, Without, Ref, Vec2, DespawnRecursiveExt, Button, Component, Input, KeyCode}, ecs::query::Has, time::{Timer, TimerMode}, ui::{Style, FlexDirection, FlexWrap, UiRect, Val, AlignSelf, AlignItems, JustifyContent, Interaction, BackgroundColor, FocusPolicy, AlignContent, PositionType, UiImage, Display, widget::UiImageSize}, text::{Text, TextStyle, TextAlignment}};
+use interpolation::EaseFunction;
 
-use crate::{plugins::{assets::{UiAssets, FontAssets, InventoryItemAssets}, cursor::components::Hoverable, inventory::{Inventory, Slot}, ui::{components::PreviousInteraction, resources::IsVisible}, audio::{AudioCommandsExt, SoundType}}, language::{keys::{LanguageStringKey, UIStringKey, ItemStringKey}, LocalizedText, args}, common::{extensions::EntityCommandsExtensions, BoolValue, helpers}};
+use crate::{plugins::{assets::{UiAssets, FontAssets, InventoryItemAssets}, cursor::components::Hoverable, inventory::{Inventory, Slot, NearbyCraftingStations, OpenChest}, ui::{components::PreviousInteraction, resources::IsVisible}, audio::{AudioCommandsExt, SoundType}, item::ItemCommandsExt, player::Player, entity::components::EntityRect}, language::{keys::{LanguageStringKey, UIStringKey, ItemStringKey}, LocalizedText, Localize, args}, common::{extensions::EntityCommandsExtensions, BoolValue, helpers, lens::TextFontSizeLens}, items::{Recipe, Recipes, ItemStack, SlotContainer, transfer_items}, animation::{Tween, RepeatStrategy, Animator}};
 
-use super::{components::*, INVENTORY_ROWS, SLOT_COUNT_IN_ROW, HOTBAR_SLOT_SIZE, INVENTORY_SLOT_SIZE, HOTBAR_SLOT_SIZE_SELECTED};
+use super::{components::*, INVENTORY_ROWS, CHEST_ROWS, SLOT_COUNT_IN_ROW, HOTBAR_SLOT_SIZE, INVENTORY_SLOT_SIZE, HOTBAR_SLOT_SIZE_SELECTED, CRAFTING_COLUMN_SLOTS};
 
 #[autodefault]
 pub(crate) fn spawn_inventory_ui(
@@ -49,40 +52,131 @@ pub(crate) fn spawn_inventory_ui(
 
             children
                 .spawn((
-                    Name::new("Hotbar"),
-                    HotbarUi,
-                    Interaction::default(),
+                    Name::new("Storage"),
                     NodeBundle {
                         style: Style {
-                            align_items: AlignItems::Center,
-                            justify_content: JustifyContent::Center,
-                        }
+                            align_items: AlignItems::FlexStart,
+                        },
                     }
                 ))
                 .with_children(|children| {
-                    for i in 0..SLOT_COUNT_IN_ROW {
-                        spawn_inventory_slot(
-                            children,
-                            ui_assets.inventory_background.clone_weak(),
-                            true,
-                            i,
-                            fonts,
-                        );
-                    }
+                    children
+                        .spawn(NodeBundle {
+                            style: Style {
+                                flex_direction: FlexDirection::Column,
+                                align_items: AlignItems::FlexStart,
+                            },
+                        })
+                        .with_children(|children| {
+                            children
+                                .spawn((
+                                    Name::new("Hotbar"),
+                                    HotbarUi,
+                                    Interaction::default(),
+                                    NodeBundle {
+                                        style: Style {
+                                            align_items: AlignItems::Center,
+                                            justify_content: JustifyContent::Center,
+                                        }
+                                    }
+                                ))
+                                .with_children(|children| {
+                                    for i in 0..SLOT_COUNT_IN_ROW {
+                                        spawn_inventory_slot(
+                                            children,
+                                            ui_assets.inventory_background.clone_weak(),
+                                            true,
+                                            i,
+                                            SlotOwner::Inventory,
+                                            fonts,
+                                        );
+                                    }
+                                });
+
+                            children
+                                .spawn((
+                                    Name::new("Inventory"),
+                                    InventoryUi,
+                                    Interaction::default(),
+                                    NodeBundle {
+                                        style: Style {
+                                            flex_direction: FlexDirection::Column,
+                                            align_items: AlignItems::Center,
+                                            justify_content: JustifyContent::Center,
+                                            margin: UiRect {
+                                                top: Val::Px(2.),
+                                                ..default()
+                                            }
+                                        },
+                                        visibility: Visibility::Hidden,
+                                    }
+                                ))
+                                .with_children(|children| {
+                                    // Starting from 1 because hotbar takes the first row
+                                    for j in 1..=INVENTORY_ROWS {
+                                        children.spawn((
+                                            Name::new(format!("Row #{}", j)),
+                                            NodeBundle {
+                                                style: Style {
+                                                    margin: UiRect::vertical(Val::Px(2.)),
+                                                    align_items: AlignItems::Center,
+                                                    justify_content: JustifyContent::Center,
+                                                },
+                                            }
+                                        )).with_children(|children| {
+                                            for i in 0..SLOT_COUNT_IN_ROW {
+                                                let index = (j * SLOT_COUNT_IN_ROW) + i;
+
+                                                spawn_inventory_slot(
+                                                    children,
+                                                    ui_assets.inventory_background.clone_weak(),
+                                                    false,
+                                                    index,
+                                                    SlotOwner::Inventory,
+                                                    fonts,
+                                                );
+                                            }
+                                        });
+                                    }
+
+                                    children
+                                        .spawn(NodeBundle {
+                                            style: Style {
+                                                width: Val::Percent(100.),
+                                                margin: UiRect::vertical(Val::Px(2.)),
+                                                align_items: AlignItems::Center,
+                                                justify_content: JustifyContent::SpaceBetween,
+                                            },
+                                        })
+                                        .with_children(|children| {
+                                            spawn_text_button(children, fonts, "SortInventoryButton", UIStringKey::SortInventory, SortInventoryButton);
+
+                                            spawn_inventory_slot(
+                                                children,
+                                                ui_assets.inventory_background.clone_weak(),
+                                                false,
+                                                0,
+                                                SlotOwner::Trash,
+                                                fonts,
+                                            );
+                                        });
+                                });
+                        });
+
+                    spawn_chest_ui(children, ui_assets, fonts);
                 });
 
             children
                 .spawn((
-                    Name::new("Inventory"),
+                    Name::new("Crafting"),
                     InventoryUi,
                     Interaction::default(),
                     NodeBundle {
                         style: Style {
                             flex_direction: FlexDirection::Column,
-                            align_items: AlignItems::Center,
-                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::FlexStart,
                             margin: UiRect {
-                                top: Val::Px(2.),
+                                top: Val::Px(10.),
                                 ..default()
                             }
                         },
@@ -90,41 +184,264 @@ pub(crate) fn spawn_inventory_ui(
                     }
                 ))
                 .with_children(|children| {
-                    // Starting from 1 because hotbar takes the first row
-                    for j in 1..=INVENTORY_ROWS {
-                        children.spawn((
-                            Name::new(format!("Row #{}", j)),
-                            NodeBundle {
-                                style: Style {
-                                    margin: UiRect::vertical(Val::Px(2.)),
-                                    align_items: AlignItems::Center,
-                                    justify_content: JustifyContent::Center,
+                    children.spawn((
+                        TextBundle {
+                            style: Style {
+                                margin: UiRect::horizontal(Val::Px(2.)),
+                            },
+                            text: Text::from_section(
+                                String::new(),
+                                TextStyle {
+                                    font: fonts.andy_bold.clone_weak(),
+                                    font_size: 24.,
+                                    color: Color::WHITE,
                                 },
-                            }
-                        )).with_children(|children| {
-                            for i in 0..SLOT_COUNT_IN_ROW {
-                                let index = (j * SLOT_COUNT_IN_ROW) + i;
-
-                                spawn_inventory_slot(
-                                    children,
-                                    ui_assets.inventory_background.clone_weak(),
-                                    false,
-                                    index,
-                                    fonts,
-                                );
-                            }
-                        });
-                    }
+                            ),
+                        },
+                        LocalizedText::from(UIStringKey::Crafting),
+                    ));
+
+                    children.spawn((
+                        Name::new("CraftingList"),
+                        CraftingList(Vec::new()),
+                        NodeBundle {
+                            style: Style {
+                                flex_direction: FlexDirection::Column,
+                                flex_wrap: FlexWrap::Wrap,
+                                align_content: AlignContent::FlexStart,
+                                max_height: Val::Px((INVENTORY_SLOT_SIZE + 4.) * CRAFTING_COLUMN_SLOTS as f32),
+                            },
+                        }
+                    ));
                 });
         })
         .id()
 }
 
+#[autodefault]
+fn spawn_chest_ui(
+    children: &mut ChildBuilder<'_, '_, '_>,
+    ui_assets: &UiAssets,
+    fonts: &FontAssets,
+) {
+    children
+        .spawn((
+            Name::new("Chest"),
+            ChestUi,
+            InventoryUi,
+            Interaction::default(),
+            NodeBundle {
+                style: Style {
+                    display: Display::None,
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::FlexStart,
+                    margin: UiRect::left(Val::Px(20.)),
+                },
+                visibility: Visibility::Hidden,
+            }
+        ))
+        .with_children(|children| {
+            children.spawn((
+                TextBundle {
+                    style: Style {
+                        margin: UiRect::horizontal(Val::Px(2.)),
+                    },
+                    text: Text::from_section(
+                        String::new(),
+                        TextStyle {
+                            font: fonts.andy_bold.clone_weak(),
+                            font_size: 24.,
+                            color: Color::WHITE,
+                        },
+                    ),
+                },
+                LocalizedText::from(UIStringKey::Chest),
+            ));
+
+            children
+                .spawn(NodeBundle {
+                    style: Style {
+                        align_items: AlignItems::FlexStart,
+                    },
+                })
+                .with_children(|children| {
+                    children
+                        .spawn(NodeBundle {
+                            style: Style {
+                                flex_direction: FlexDirection::Column,
+                            },
+                        })
+                        .with_children(|children| {
+                            for j in 0..CHEST_ROWS {
+                                children.spawn((
+                                    Name::new(format!("Row #{}", j)),
+                                    NodeBundle {
+                                        style: Style {
+                                            margin: UiRect::vertical(Val::Px(2.)),
+                                            align_items: AlignItems::Center,
+                                            justify_content: JustifyContent::Center,
+                                        },
+                                    }
+                                )).with_children(|children| {
+                                    for i in 0..SLOT_COUNT_IN_ROW {
+                                        spawn_inventory_slot(
+                                            children,
+                                            ui_assets.inventory_background.clone_weak(),
+                                            false,
+                                            (j * SLOT_COUNT_IN_ROW) + i,
+                                            SlotOwner::Chest,
+                                            fonts,
+                                        );
+                                    }
+                                });
+                            }
+                        });
+
+                    children
+                        .spawn(NodeBundle {
+                            style: Style {
+                                flex_direction: FlexDirection::Column,
+                                align_items: AlignItems::FlexStart,
+                                margin: UiRect::left(Val::Px(10.)),
+                            },
+                        })
+                        .with_children(|children| {
+                            spawn_text_button(children, fonts, "LootAllButton", UIStringKey::LootAll, ChestButton::LootAll);
+                            spawn_text_button(children, fonts, "DepositAllButton", UIStringKey::DepositAll, ChestButton::DepositAll);
+                            spawn_text_button(children, fonts, "QuickStackButton", UIStringKey::QuickStack, ChestButton::QuickStack);
+                        });
+                });
+        });
+}
+
+fn spawn_text_button(
+    children: &mut ChildBuilder<'_, '_, '_>,
+    fonts: &FontAssets,
+    name: &'static str,
+    key: UIStringKey,
+    marker: impl Component,
+) {
+    let tween = Tween::new(
+        EaseFunction::QuadraticInOut,
+        RepeatStrategy::MirroredRepeat,
+        Duration::from_millis(150),
+        TextFontSizeLens {
+            start: 22.,
+            end: 26.,
+        },
+    );
+
+    children.spawn((
+        TextBundle {
+            style: Style {
+                margin: UiRect::vertical(Val::Px(2.)),
+                height: Val::Px(26.),
+                ..default()
+            },
+            text: Text::from_section(
+                String::new(),
+                TextStyle {
+                    font: fonts.andy_bold.clone_weak(),
+                    font_size: 22.,
+                    color: Color::WHITE,
+                },
+            ),
+            ..default()
+        },
+        Name::new(name),
+        Interaction::default(),
+        PreviousInteraction::default(),
+        Animator::new(tween),
+        LocalizedText::from(key),
+        marker,
+        Button,
+    ));
+}
+
+#[autodefault]
+fn spawn_recipe_slot(
+    children: &mut ChildBuilder<'_, '_, '_>,
+    ui_assets: &UiAssets,
+    item_assets: &InventoryItemAssets,
+    fonts: &FontAssets,
+    index: usize,
+    recipe: &Recipe,
+) {
+    children
+        .spawn((
+            Name::new(format!("Recipe #{}", index)),
+            RecipeSlot(index),
+            Hoverable::SimpleText(recipe_hoverable_text(recipe)),
+            Interaction::default(),
+            PreviousInteraction::default(),
+            ImageBundle {
+                style: Style {
+                    margin: UiRect::all(Val::Px(2.)),
+                    width: Val::Px(INVENTORY_SLOT_SIZE),
+                    height: Val::Px(INVENTORY_SLOT_SIZE),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                },
+                image: ui_assets.inventory_background.clone_weak().into(),
+                background_color: BackgroundColor(Color::WHITE.with_a(0.8)),
+            }
+        ))
+        .with_children(|c| {
+            c.spawn(ImageBundle {
+                style: Style {
+                    max_width: Val::Px(32.),
+                    max_height: Val::Px(32.),
+                },
+                image: item_assets.get_by_item(recipe.output.item).into(),
+                focus_policy: FocusPolicy::Pass,
+            });
+
+            if recipe.output.stack > 1 {
+                c.spawn(TextBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        left: Val::Px(5.),
+                        bottom: Val::Px(2.5),
+                    },
+                    focus_policy: FocusPolicy::Pass,
+                    text: Text::from_section(
+                        recipe.output.stack.to_string(),
+                        TextStyle {
+                            font: fonts.andy_regular.clone_weak(),
+                            font_size: 16.,
+                            color: Color::WHITE,
+                        },
+                    ),
+                });
+            }
+        });
+}
+
+/// The name of the output followed by the ingredients, each on a new line
+fn recipe_hoverable_text(recipe: &Recipe) -> LocalizedText {
+    let mut format = String::from("{}");
+    let mut args: Vec<Box<dyn Localize>> = Vec::with_capacity(1 + recipe.ingredients.len() * 2);
+
+    if recipe.output.stack > 1 {
+        format.push_str(" ({})");
+        args.push(Box::new(recipe.output.stack));
+    }
+
+    for ingredient in &recipe.ingredients {
+        format.push_str("\n{} ({})");
+        args.push(Box::new(ItemStringKey::get_by_item(ingredient.item)));
+        args.push(Box::new(ingredient.stack));
+    }
+
+    LocalizedText::new(ItemStringKey::get_by_item(recipe.output.item), format, args.into())
+}
+
 fn spawn_inventory_slot(
     children: &mut ChildBuilder<'_, '_, '_>,
     slot_background: Handle<Image>,
     hotbar_slot: bool,
     index: usize,
+    owner: SlotOwner,
     fonts: &FontAssets,
 ) {
     let size = if hotbar_slot { HOTBAR_SLOT_SIZE } else { INVENTORY_SLOT_SIZE };
@@ -134,6 +451,7 @@ fn spawn_inventory_slot(
             Hoverable::None,
             Name::new(format!("Slot #{}", index)),
             SlotIndex(index),
+            owner,
             Interaction::default(),
             PreviousInteraction::default(),
             InventorySlot,
@@ -157,6 +475,7 @@ fn spawn_inventory_slot(
         .with_children(|c| {
             c.spawn((
                 SlotIndex(index),
+                owner,
                 SlotItemImage::default(),
                 ImageBundle {
                     focus_policy: FocusPolicy::Pass,
@@ -206,6 +525,7 @@ fn spawn_inventory_slot(
                 // Item stack
                 c.spawn((
                     SlotIndex(index),
+                    owner,
                     ItemAmount::default(),
                     TextBundle {
                         style: Style {
@@ -232,7 +552,7 @@ pub(super) fn update_slot_size(
     inventory: Res<Inventory>,
     visibility: Res<IsVisible<InventoryUi>>,
     mut hotbar_slots: Query<(&SlotIndex, &mut Style), (With<HotbarSlot>, Without<SlotItemImage>)>,
-    mut item_images: Query<(&SlotIndex, Ref<UiImageSize>, &mut Style), (Without<HotbarSlot>, With<SlotItemImage>)>
+    mut item_images: Query<(&SlotIndex, &SlotOwner, Ref<UiImageSize>, &mut Style), (Without<HotbarSlot>, With<SlotItemImage>)>
 ) {
     let visibility_changed = visibility.is_changed();
     let selected_slot_changed = inventory.selected_slot != inventory.previous_selected_slot;
@@ -253,10 +573,10 @@ pub(super) fn update_slot_size(
         }
     }
 
-    for (slot_index, image_size, mut style) in &mut item_images {
+    for (slot_index, owner, image_size, mut style) in &mut item_images {
         if !visibility_changed && !selected_slot_changed && !image_size.is_changed() { continue; }
 
-        let selected = slot_index.0 == inventory.selected_slot;
+        let selected = *owner == SlotOwner::Inventory && slot_index.0 == inventory.selected_slot;
         let image_size = image_size.size().min(Vec2::splat(32.));
         // The maximum size of an item image is 32px
         if !(image_size.x > 0. && image_size.y > 0.) { continue; }
@@ -299,12 +619,16 @@ pub(super) fn update_slot_background_image(
     inventory: Res<Inventory>,
     ui_assets: Res<UiAssets>,
     visibility: Res<IsVisible<InventoryUi>>,
-    mut hotbar_slots: Query<(&SlotIndex, &mut UiImage), With<HotbarSlot>>,
+    mut slots: Query<(&SlotIndex, &SlotOwner, Has<HotbarSlot>, &mut UiImage), With<InventorySlot>>,
 ) {
-    for (slot_index, mut image) in &mut hotbar_slots {
-        let selected = slot_index.0 == inventory.selected_slot;
+    for (slot_index, owner, hotbar_slot, mut image) in &mut slots {
+        if *owner != SlotOwner::Inventory { continue; }
+
+        let selected = hotbar_slot && slot_index.0 == inventory.selected_slot;
         let texture = if selected && !visibility.value() {
             &ui_assets.selected_inventory_background
+        } else if inventory.is_favourite(slot_index.0) {
+            &ui_assets.favourite_inventory_background
         } else {
             &ui_assets.inventory_background
         };
@@ -315,10 +639,11 @@ pub(super) fn update_slot_background_image(
 
 pub(super) fn update_hoverable(
     inventory: Res<Inventory>,
-    mut hotbar_slots: Query<(&SlotIndex, &mut Hoverable), With<InventorySlot>>
+    open_chest: Option<Res<OpenChest>>,
+    mut hotbar_slots: Query<(&SlotIndex, &SlotOwner, &mut Hoverable), With<InventorySlot>>
 ) {
-    for (slot_index, mut hoverable) in &mut hotbar_slots {
-        if let Some(item) = inventory.get_item(Slot::Index(slot_index.0)) {
+    for (slot_index, owner, mut hoverable) in &mut hotbar_slots {
+        if let Some(item) = slot_item(*owner, slot_index.0, &inventory, open_chest.as_deref()) {
             let item_key = LanguageStringKey::Items(ItemStringKey::get_by_item(item.item));
 
             let name = if item.stack > 1 {
@@ -328,6 +653,8 @@ pub(super) fn update_hoverable(
             };
 
             *hoverable = Hoverable::SimpleText(name);
+        } else if *owner == SlotOwner::Trash {
+            *hoverable = Hoverable::SimpleText(LocalizedText::from(UIStringKey::Trash));
         } else {
             *hoverable = Hoverable::None;
         }
@@ -373,12 +700,12 @@ pub(super) fn update_selected_item_name_text(
 
 pub(super) fn update_slot_item_image(
     inventory: Res<Inventory>,
+    open_chest: Option<Res<OpenChest>>,
     item_assets: Res<InventoryItemAssets>,
-    mut item_images: Query<(&SlotIndex, &mut SlotItemImage)>,
+    mut item_images: Query<(&SlotIndex, &SlotOwner, &mut SlotItemImage)>,
 ) {
-    for (slot_index, mut slot_image) in &mut item_images {
-        let image = inventory
-            .get_item(Slot::Index(slot_index.0))
+    for (slot_index, owner, mut slot_image) in &mut item_images {
+        let image = slot_item(*owner, slot_index.0, &inventory, open_chest.as_deref())
             .map(|item_stack| item_assets.get_by_item(item_stack.item))
             .unwrap_or_default();
 
@@ -396,10 +723,11 @@ pub(super) fn update_slot_image(
 
 pub(super) fn update_item_amount(
     inventory: Res<Inventory>,
-    mut query: Query<(&SlotIndex, &mut ItemAmount)>,
+    open_chest: Option<Res<OpenChest>>,
+    mut query: Query<(&SlotIndex, &SlotOwner, &mut ItemAmount)>,
 ) {
-    for (slot_index, mut item_stack) in &mut query {
-        let stack = inventory.get_item(Slot::Index(slot_index.0))
+    for (slot_index, owner, mut item_stack) in &mut query {
+        let stack = slot_item(*owner, slot_index.0, &inventory, open_chest.as_deref())
             .map(|item_stack| item_stack.stack)
             .unwrap_or(0);
 
@@ -418,17 +746,53 @@ pub(super) fn update_item_amount_text(
     }
 }
 
+/// Takes the item in the clicked slot into the mouse.
+/// Shift-click moves the item to the open chest or between the hotbar and the main inventory,
+/// Ctrl-click trashes the item and Alt-click marks it as favourite.
 pub(super) fn take_item(
     mut commands: Commands,
+    input: Res<Input<KeyCode>>,
     mut inventory: ResMut<Inventory>,
-    query_slot: Query<(&Interaction, &PreviousInteraction, &SlotIndex)>
+    mut open_chest: Option<ResMut<OpenChest>>,
+    query_slot: Query<(&Interaction, &PreviousInteraction, &SlotIndex, &SlotOwner)>
 ) {
     if inventory.is_changed() { return; }
 
+    let shift = input.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    let alt = input.any_pressed([KeyCode::AltLeft, KeyCode::AltRight]);
+    let ctrl = input.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]);
+
     if !inventory.item_exists(Slot::MouseItem) {
-        for (interaction, previous_interaction, index) in &query_slot {
+        for (interaction, previous_interaction, index, owner) in &query_slot {
             if *interaction == Interaction::Pressed && previous_interaction.0 != Interaction::Pressed {
-                if let Some(item_stack) = inventory.remove_item(Slot::Index(index.0)) {
+                if alt {
+                    if *owner == SlotOwner::Inventory && inventory.toggle_favourite(index.0) {
+                        commands.play_sound(SoundType::MenuTick);
+                    }
+                    continue;
+                }
+
+                if ctrl {
+                    if trash_item(&mut inventory, open_chest.as_deref_mut(), *owner, index.0) {
+                        commands.play_sound(SoundType::ItemGrab);
+                    }
+                    continue;
+                }
+
+                if shift {
+                    if quick_move_item(&mut inventory, open_chest.as_deref_mut(), *owner, index.0) {
+                        commands.play_sound(SoundType::ItemGrab);
+                    }
+                    continue;
+                }
+
+                let item_stack = match owner {
+                    SlotOwner::Inventory => inventory.remove_item(Slot::Index(index.0)),
+                    SlotOwner::Chest => open_chest.as_mut().and_then(|chest| chest.contents.take_slot(index.0)),
+                    SlotOwner::Trash => inventory.remove_item(Slot::Trash),
+                };
+
+                if let Some(item_stack) = item_stack {
                     inventory.set_item(Slot::MouseItem, item_stack);
                     commands.play_sound(SoundType::ItemGrab);
                 }
@@ -440,20 +804,205 @@ pub(super) fn take_item(
 pub(super) fn put_item(
     mut commands: Commands,
     mut inventory: ResMut<Inventory>,
-    query_slot: Query<(&Interaction, &PreviousInteraction, &SlotIndex)>
+    mut open_chest: Option<ResMut<OpenChest>>,
+    query_slot: Query<(&Interaction, &PreviousInteraction, &SlotIndex, &SlotOwner)>
 ) {
     if inventory.is_changed() { return; }
 
     if let Some(mouse_item) = inventory.get_item(Slot::MouseItem) {
-        for (interaction, previous_interaction, index) in &query_slot {
+        for (interaction, previous_interaction, index, owner) in &query_slot {
             if *interaction == Interaction::Pressed && previous_interaction.0 != Interaction::Pressed {
-                if let Some(item) = inventory.get_item(Slot::Index(index.0)) {
+                // The mouse item is swapped with the item in the slot
+                let slot_item = match owner {
+                    SlotOwner::Inventory => {
+                        let slot_item = inventory.get_item(Slot::Index(index.0));
+                        inventory.set_item(Slot::Index(index.0), mouse_item);
+                        slot_item
+                    },
+                    SlotOwner::Chest => {
+                        let Some(chest) = open_chest.as_mut() else { continue; };
+                        let slot_item = chest.contents.get_slot(index.0);
+                        chest.contents.set_slot(index.0, Some(mouse_item));
+                        slot_item
+                    },
+                    // The trashed stack is replaced instead of being swapped with the mouse item
+                    SlotOwner::Trash => {
+                        inventory.trash_item(Slot::MouseItem);
+                        commands.play_sound(SoundType::ItemGrab);
+                        continue;
+                    },
+                };
+
+                if let Some(item) = slot_item {
                     inventory.set_item(Slot::MouseItem, item);
                 } else {
                     inventory.remove_item(Slot::MouseItem);
                 }
-                
-                inventory.set_item(Slot::Index(index.0), mouse_item);
+
+                commands.play_sound(SoundType::ItemGrab);
+            }
+        }
+    }
+}
+
+/// Moves the whole stack in the slot to the other container.
+/// Without an open chest the stack moves between the hotbar and the main inventory.
+fn quick_move_item(inventory: &mut Inventory, open_chest: Option<&mut OpenChest>, owner: SlotOwner, index: usize) -> bool {
+    match (owner, open_chest) {
+        (SlotOwner::Inventory, Some(chest)) => {
+            !inventory.is_favourite(index) && transfer_items(inventory, index..index + 1, &mut chest.contents, |_, _| true)
+        },
+        (SlotOwner::Inventory, None) => inventory.quick_move(index),
+        (SlotOwner::Chest, Some(chest)) => transfer_items(&mut chest.contents, index..index + 1, inventory, |_, _| true),
+        (SlotOwner::Chest, None) => false,
+        (SlotOwner::Trash, _) => inventory.restore_trash(),
+    }
+}
+
+/// Moves the whole stack in the slot into the trash
+fn trash_item(inventory: &mut Inventory, open_chest: Option<&mut OpenChest>, owner: SlotOwner, index: usize) -> bool {
+    match owner {
+        SlotOwner::Inventory => inventory.trash_item(Slot::Index(index)),
+        SlotOwner::Chest => {
+            let Some(item_stack) = open_chest.and_then(|chest| chest.contents.take_slot(index)) else { return false; };
+            inventory.set_item(Slot::Trash, item_stack);
+            true
+        },
+        SlotOwner::Trash => false,
+    }
+}
+
+/// Right click takes half of the stack in the hovered slot into the mouse,
+/// or one more item when the mouse already holds the same item
+pub(super) fn split_item(
+    mut commands: Commands,
+    mut inventory: ResMut<Inventory>,
+    mut open_chest: Option<ResMut<OpenChest>>,
+    query_slot: Query<(&Interaction, &SlotIndex, &SlotOwner)>
+) {
+    let Some((_, index, owner)) = query_slot.iter().find(|(interaction, ..)| **interaction == Interaction::Hovered) else { return; };
+    let Some(item_stack) = slot_item(*owner, index.0, &inventory, open_chest.as_deref()) else { return; };
+
+    let mouse_item = inventory.get_item(Slot::MouseItem);
+
+    let amount = match mouse_item {
+        None => (item_stack.stack + 1) / 2,
+        Some(mouse_item) if mouse_item.item == item_stack.item && mouse_item.stack < mouse_item.item.max_stack() => 1,
+        Some(_) => return
+    };
+
+    let taken = match owner {
+        SlotOwner::Inventory => inventory.split_item(Slot::Index(index.0), amount),
+        SlotOwner::Chest => open_chest.as_mut().and_then(|chest| chest.contents.split_slot(index.0, amount)),
+        SlotOwner::Trash => inventory.split_item(Slot::Trash, amount),
+    };
+
+    let Some(taken) = taken else { return; };
+
+    let new_mouse_item = match mouse_item {
+        Some(mouse_item) => mouse_item.with_stack(mouse_item.stack + taken.stack),
+        None => taken
+    };
+
+    inventory.set_item(Slot::MouseItem, new_mouse_item);
+    commands.play_sound(SoundType::ItemGrab);
+}
+
+pub(super) fn sort_inventory(
+    mut commands: Commands,
+    mut inventory: ResMut<Inventory>,
+    query_button: Query<(&Interaction, &PreviousInteraction), With<SortInventoryButton>>
+) {
+    for (interaction, previous_interaction) in &query_button {
+        if *interaction == Interaction::Pressed && previous_interaction.0 != Interaction::Pressed {
+            inventory.sort();
+            commands.play_sound(SoundType::ItemGrab);
+        }
+    }
+}
+
+/// The item in the slot of the inventory or of the open chest
+fn slot_item(owner: SlotOwner, index: usize, inventory: &Inventory, open_chest: Option<&OpenChest>) -> Option<ItemStack> {
+    match owner {
+        SlotOwner::Inventory => inventory.get_item(Slot::Index(index)),
+        SlotOwner::Chest => open_chest.and_then(|chest| chest.contents.get_slot(index)),
+        SlotOwner::Trash => inventory.get_item(Slot::Trash),
+    }
+}
+
+pub(super) fn update_crafting_list(
+    mut commands: Commands,
+    inventory: Res<Inventory>,
+    recipes: Res<Recipes>,
+    nearby_stations: Res<NearbyCraftingStations>,
+    ui_assets: Res<UiAssets>,
+    item_assets: Res<InventoryItemAssets>,
+    fonts: Res<FontAssets>,
+    mut query_crafting_list: Query<(Entity, &mut CraftingList)>,
+) {
+    let Ok((entity, mut crafting_list)) = query_crafting_list.get_single_mut() else { return; };
+
+    let craftable: Vec<usize> = inventory.craftable_recipes(&recipes, &nearby_stations).collect();
+
+    if crafting_list.0 == craftable { return; }
+
+    commands.entity(entity)
+        .despawn_descendants()
+        .with_children(|children| {
+            for &index in &craftable {
+                spawn_recipe_slot(children, &ui_assets, &item_assets, &fonts, index, &recipes[index]);
+            }
+        });
+
+    crafting_list.0 = craftable;
+}
+
+pub(super) fn craft_item(
+    mut commands: Commands,
+    mut inventory: ResMut<Inventory>,
+    recipes: Res<Recipes>,
+    query_recipe_slot: Query<(&Interaction, &PreviousInteraction, &RecipeSlot)>
+) {
+    for (interaction, previous_interaction, recipe_slot) in &query_recipe_slot {
+        if *interaction == Interaction::Pressed
+            && previous_interaction.0 != Interaction::Pressed
+            && inventory.craft(&recipes[recipe_slot.0])
+        {
+            commands.play_sound(SoundType::ItemGrab);
+        }
+    }
+}
+
+pub(super) fn update_chest_ui_display(
+    open_chest: Option<Res<OpenChest>>,
+    mut query_chest_ui: Query<&mut Style, With<ChestUi>>
+) {
+    let display = if open_chest.is_some() { Display::Flex } else { Display::None };
+
+    for mut style in &mut query_chest_ui {
+        if style.display != display {
+            style.display = display;
+        }
+    }
+}
+
+pub(super) fn use_chest_button(
+    mut commands: Commands,
+    mut inventory: ResMut<Inventory>,
+    mut open_chest: ResMut<OpenChest>,
+    query_button: Query<(&Interaction, &PreviousInteraction, &ChestButton)>
+) {
+    for (interaction, previous_interaction, button) in &query_button {
+        if *interaction == Interaction::Pressed && previous_interaction.0 != Interaction::Pressed {
+            let chest = &mut open_chest.contents;
+
+            let moved = match button {
+                ChestButton::LootAll => inventory.loot_all(chest),
+                ChestButton::DepositAll => inventory.deposit_all(chest),
+                ChestButton::QuickStack => inventory.quick_stack(chest),
+            };
+
+            if moved {
                 commands.play_sound(SoundType::ItemGrab);
             }
         }
@@ -463,11 +1012,25 @@ pub(super) fn put_item(
 pub(super) fn return_mouse_item_back_to_inventory(
     mut commands: Commands,
     mut inventory: ResMut<Inventory>,
-    inventory_visible: Res<IsVisible<InventoryUi>>
+    inventory_visible: Res<IsVisible<InventoryUi>>,
+    query_player: Query<&EntityRect, With<Player>>
 ) {
     if !*inventory_visible && inventory.item_exists(Slot::MouseItem) {
         let item = inventory.remove_item(Slot::MouseItem).unwrap();
-        inventory.add_item_stack(item);
+        let added = inventory.add_item_stack(item);
+
+        // What doesn't fit into the inventory is dropped instead of being lost
+        if added < item.stack {
+            if let Ok(player_rect) = query_player.get_single() {
+                commands.spawn_dropped_item(
+                    player_rect.center(),
+                    Vec2::new(0., 2.),
+                    item.with_stack(item.stack - added),
+                    Some(Timer::new(Duration::from_secs_f32(1.5), TimerMode::Once))
+                );
+            }
+        }
+
         commands.play_sound(SoundType::ItemGrab);
     }
 }
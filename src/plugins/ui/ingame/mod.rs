@@ -1,2 +1,3 @@
 pub(crate) mod inventory;
+pub(in crate::plugins::ui) mod health;
 pub(in crate::plugins::ui) mod settings;
\ No newline at end of file
@@ -9,7 +9,7 @@ use crate::common::{state::GameState, systems::{bind_visibility_to, despawn_with
 
 use self::{
     components::{MainUiContainer, MusicVolumeSliderOutput, SoundVolumeSliderOutput, MusicVolumeSlider, SoundVolumeSlider},
-    ingame::{inventory::{systems::spawn_inventory_ui, InventoryUiPlugin, components::InventoryUi}, settings::{systems::spawn_ingame_settings_button, InGameSettingsUiPlugin}},
+    ingame::{inventory::{systems::spawn_inventory_ui, InventoryUiPlugin, components::InventoryUi}, settings::{systems::spawn_ingame_settings_button, InGameSettingsUiPlugin}, health::{spawn_health_bar, HealthUiPlugin}},
     menu::MenuPlugin, systems::{play_sound_on_hover, update_previous_interaction}, resources::{IsVisible, SettingsMenu, Ui, Cursor},
 };
 
@@ -26,7 +26,7 @@ pub(crate) struct MouseOverUi(pub(crate) bool);
 pub(crate) struct UiPlugin;
 impl Plugin for UiPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins((InventoryUiPlugin, InGameSettingsUiPlugin, MenuPlugin));
+        app.add_plugins((InventoryUiPlugin, InGameSettingsUiPlugin, HealthUiPlugin, MenuPlugin));
 
         app.insert_resource(IsVisible::<Ui>::visible());
         app.init_resource::<MouseOverUi>();
@@ -163,18 +163,7 @@ fn spawn_ui_container(
     let inventory = spawn_inventory_ui(&mut commands, &ui_assets, &font_assets);
     let settings_btn = spawn_ingame_settings_button(&mut commands, &font_assets);
 
-    // TODO: Make a health bar in the feature, stub for now
-    let health_bar = commands
-        .spawn(NodeBundle {
-            style: Style {
-                width: Val::Px(10.),
-                height: Val::Px(2.),
-                ..default()
-            },
-            ..default()
-        })
-        .insert(Name::new("Stub"))
-        .id();
+    let health_bar = spawn_health_bar(&mut commands, &ui_assets, &font_assets);
 
     commands
         .entity(left_id)
@@ -12,4 +12,5 @@ pub(super) const CHUNK_SIZE_U: u32 = CHUNK_SIZE as u32;
 pub(super) const CHUNKMAP_SIZE: TilemapSize = TilemapSize {
     x: CHUNK_SIZE as u32,
     y: CHUNK_SIZE as u32,
-};
\ No newline at end of file
+};
+pub(crate) const WORLD_FILE_NAME: &str = "world.world";
@@ -23,7 +23,13 @@ pub(crate) struct DigWallEvent {
     pub(crate) tool: ItemTool
 }
 
+/// Changes the shape of the block to the next one
 #[derive(Event)]
+pub(crate) struct HammerBlockEvent {
+    pub(crate) tile_pos: TilePos,
+}
+
+#[derive(Event, Clone, Copy)]
 pub(crate) struct PlaceTileEvent {
     pub(crate) tile_pos: TilePos,
     pub(crate) tile_type: TileType,
@@ -46,6 +52,12 @@ pub(crate) struct TileRemovedEvent {
     pub(crate) tile_type: TileType
 }
 
+/// The liquid in the tile has appeared, moved or changed its level
+#[derive(Event, Clone, Copy)]
+pub(crate) struct LiquidChangedEvent {
+    pub(crate) tile_pos: TilePos,
+}
+
 #[derive(Event)]
 pub(crate) struct UpdateWallEvent {
     pub(crate) tile_pos: TilePos,
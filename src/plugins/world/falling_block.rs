@@ -0,0 +1,181 @@
+use bevy::{prelude::{Plugin, App, Update, FixedUpdate, IntoSystemConfigs, Component, Commands, Query, Res, ResMut, EventReader, EventWriter, Entity, Transform, Rect, Name, With, ParamSet, Vec2, default}, sprite::{SpriteBundle, Sprite}};
+use bevy_ecs_tilemap::{tiles::{TilePos, TileStorage, TileTextureIndex}, helpers::square_grid::neighbors::Neighbors};
+
+use crate::{
+    plugins::{InGameSystemSet, DespawnOnGameExit, assets::BlockAssets, audio::{AudioCommandsExt, SoundType}, entity::{EntitySet, components::{EntityRect, Velocity, Collider, Collisions}}, item::{ItemCommandsExt, components::DroppedItem}},
+    world::{WorldData, block::{Block, BlockType}, chunk::{Chunk, ChunkType}},
+    common::{rect::FRect, helpers::tile_to_world_pos},
+    items::ItemStack,
+    TILES_LAYER
+};
+
+use super::{
+    constants::TILE_SIZE,
+    events::{UpdateNeighborsEvent, TileRemovedEvent, PlaceTileEvent},
+    resources::ChunkManager,
+    TileType, WORLD_RENDER_LAYER
+};
+
+const GRAVITY: f32 = 0.3;
+// Must stay below the tile size so a falling block can't skip over a tile
+const MAX_FALL_SPEED: f32 = 10.;
+
+pub(super) struct FallingBlockPlugin;
+impl Plugin for FallingBlockPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, start_falling.in_set(InGameSystemSet::Update));
+
+        app.add_systems(
+            FixedUpdate,
+            (gravity, land)
+                .chain()
+                .before(EntitySet::UpdateEntityRect)
+                .in_set(InGameSystemSet::FixedUpdate)
+        );
+    }
+}
+
+#[derive(Component)]
+pub(crate) struct FallingBlock {
+    pub(crate) block_type: BlockType
+}
+
+/// Turns the gravity-affected blocks which have lost the tile under them into falling blocks
+fn start_falling(
+    mut commands: Commands,
+    mut world_data: ResMut<WorldData>,
+    block_assets: Res<BlockAssets>,
+    mut query_chunk: Query<(&Chunk, &mut TileStorage)>,
+    mut update_neighbors_events: ParamSet<(EventReader<UpdateNeighborsEvent>, EventWriter<UpdateNeighborsEvent>)>,
+    mut tile_removed_events: EventWriter<TileRemovedEvent>,
+) {
+    // The block above the one which has fallen is checked on the next frame
+    let positions: Vec<TilePos> = update_neighbors_events.p0().iter()
+        .flat_map(|&UpdateNeighborsEvent { tile_pos }| {
+            [Some(tile_pos), tile_pos.y.checked_sub(1).map(|y| TilePos::new(tile_pos.x, y))]
+        })
+        .flatten()
+        .collect();
+
+    for tile_pos in positions {
+        let Some(&block) = world_data.get_block(tile_pos) else { continue; };
+
+        if !block.is_affected_by_gravity() { continue; }
+
+        let below = (tile_pos.x, tile_pos.y + 1);
+        if below.1 as usize >= world_data.height() || world_data.solid_block_exists(below) { continue; }
+
+        world_data.remove_block(tile_pos);
+
+        ChunkManager::remove(&mut commands, &mut query_chunk, tile_pos, ChunkType::from(block.block_type));
+        ChunkManager::remove(&mut commands, &mut query_chunk, tile_pos, ChunkType::Cracks);
+
+        spawn_falling_block(&mut commands, &block_assets, block, tile_pos);
+
+        tile_removed_events.send(TileRemovedEvent { tile_pos, tile_type: TileType::Block(Some(block.block_type)) });
+        update_neighbors_events.p1().send(UpdateNeighborsEvent { tile_pos });
+    }
+}
+
+fn spawn_falling_block(commands: &mut Commands, block_assets: &BlockAssets, block: Block, tile_pos: TilePos) {
+    // The block is drawn without any connections to its neighbors
+    let no_neighbors = Neighbors {
+        east: None, north_east: None, north: None, north_west: None,
+        west: None, south_west: None, south: None, south_east: None,
+    };
+    let index = Block::get_sprite_index(&no_neighbors, &block);
+
+    let x = (index % 16) as f32 * (TILE_SIZE + 2.);
+    let y = (index / 16) as f32 * (TILE_SIZE + 2.);
+
+    let position = tile_to_world_pos(tile_pos);
+
+    commands.spawn((
+        Name::new(format!("Falling {:?}", block.block_type)),
+        SpriteBundle {
+            texture: block_assets.tiles.clone_weak(),
+            sprite: Sprite {
+                rect: Some(Rect::new(x, y, x + TILE_SIZE, y + TILE_SIZE)),
+                ..default()
+            },
+            transform: Transform::from_xyz(position.x, position.y, TILES_LAYER + 0.5),
+            ..default()
+        },
+        FallingBlock { block_type: block.block_type },
+        EntityRect(FRect::new_center(position.x, position.y, TILE_SIZE, TILE_SIZE)),
+        Velocity::default(),
+        Collider::default(),
+        Collisions::default(),
+        DespawnOnGameExit,
+        WORLD_RENDER_LAYER
+    ));
+}
+
+fn gravity(mut query: Query<&mut Velocity, With<FallingBlock>>) {
+    for mut velocity in &mut query {
+        velocity.y = (velocity.y - GRAVITY).max(-MAX_FALL_SPEED);
+    }
+}
+
+/// Places the falling blocks back into the world when they land on a solid tile.
+/// The dropped items in the tile where a block lands get buried under it.
+fn land(
+    mut commands: Commands,
+    mut world_data: ResMut<WorldData>,
+    mut query_chunk: Query<(&Chunk, &mut TileStorage, Entity)>,
+    mut query_tile: Query<&mut TileTextureIndex>,
+    query_falling_block: Query<(Entity, &FallingBlock, &EntityRect, &Collisions)>,
+    query_dropped_items: Query<(Entity, &EntityRect), With<DroppedItem>>,
+    mut place_tile_events: EventWriter<PlaceTileEvent>,
+    mut update_neighbors_events: EventWriter<UpdateNeighborsEvent>,
+) {
+    for (entity, falling_block, rect, collisions) in &query_falling_block {
+        if !collisions.bottom { continue; }
+
+        commands.entity(entity).despawn();
+
+        let tile_pos = TilePos::new((rect.centerx / TILE_SIZE) as u32, (-rect.centery / TILE_SIZE) as u32);
+        let block_type = falling_block.block_type;
+
+        // The tile is taken by something else, so the block falls apart into an item
+        if world_data.block_exists(tile_pos) {
+            if let Some(item) = block_type.dropped_item() {
+                commands.spawn_dropped_item(tile_to_world_pos(tile_pos), Vec2::ZERO, ItemStack::new(item), None);
+            }
+            continue;
+        }
+
+        let block = Block::from(block_type);
+
+        // The block is placed right away so the next falling block lands on top of it
+        world_data.set_block(tile_pos, block);
+
+        let neighbors = world_data
+            .get_block_neighbors(tile_pos, block_type.is_solid())
+            .map_ref(|b| b.block_type);
+
+        let index = Block::get_sprite_index(&neighbors, &block);
+
+        ChunkManager::spawn_block(&mut commands, &mut query_chunk, tile_pos, &block, index);
+
+        if world_data.get_liquid(tile_pos).is_some() {
+            let changed = world_data.displace_liquid(tile_pos);
+            ChunkManager::update_liquids(&mut commands, &mut query_chunk, &mut query_tile, &world_data, &changed);
+        }
+
+        let tile_position = tile_to_world_pos(tile_pos);
+        let tile_rect = FRect::new_center(tile_position.x, tile_position.y, TILE_SIZE, TILE_SIZE);
+
+        for (item_entity, item_rect) in &query_dropped_items {
+            if tile_rect.contains(item_rect.center()) {
+                commands.entity(item_entity).despawn();
+            }
+        }
+
+        commands.play_sound(SoundType::BlockHit(block_type));
+
+        // The tile already exists, so the event only updates the lighting and the world map
+        place_tile_events.send(PlaceTileEvent { tile_pos, tile_type: TileType::Block(Some(block_type)) });
+        update_neighbors_events.send(UpdateNeighborsEvent { tile_pos });
+    }
+}
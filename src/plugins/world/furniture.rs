@@ -0,0 +1,154 @@
+use bevy::{prelude::{Plugin, App, Update, IntoSystemConfigs, Commands, Query, Res, ResMut, EventWriter, Entity, With, ParamSet, MouseButton, Vec2}, input::common_conditions::input_just_pressed};
+use bevy_ecs_tilemap::tiles::{TilePos, TileStorage, TileTextureIndex};
+
+use crate::{
+    plugins::{InGameSystemSet, audio::{AudioCommandsExt, SoundType}, camera::components::MainCamera, cursor::position::CursorPosition, entity::components::EntityRect, player::Player},
+    world::{WorldData, block::{Block, BlockType}, chunk::{Chunk, ChunkType}, furniture::{Furniture, FurnitureType, FURNITURE_TEXTURE_WIDTH}},
+    common::{rect::FRect, helpers::{get_tile_pos_from_world_coords, tile_to_world_pos}}
+};
+
+use super::{
+    constants::TILE_SIZE,
+    events::{UpdateNeighborsEvent, TileRemovedEvent, PlaceTileEvent},
+    resources::ChunkManager,
+    TileType
+};
+
+pub(super) struct FurniturePlugin;
+impl Plugin for FurniturePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            toggle_door
+                .run_if(input_just_pressed(MouseButton::Right))
+                .in_set(InGameSystemSet::Update)
+        );
+    }
+}
+
+/// Sets all tiles of an object which has its top left tile at `origin` and spawns their entities
+pub(super) fn spawn_furniture(
+    commands: &mut Commands,
+    world_data: &mut WorldData,
+    query_chunk: &mut Query<(&Chunk, &mut TileStorage, Entity)>,
+    query_tile: &mut Query<&mut TileTextureIndex>,
+    furniture_type: FurnitureType,
+    style: u8,
+    origin: TilePos,
+) {
+    for (tile_pos, furniture) in Furniture::tiles(furniture_type, style, origin) {
+        let block = Block::from(BlockType::Furniture(furniture));
+
+        world_data.set_block(tile_pos, block);
+
+        let index = furniture.texture_atlas_pos().to_2d_index(FURNITURE_TEXTURE_WIDTH);
+
+        ChunkManager::spawn_block(commands, query_chunk, tile_pos, &block, index);
+
+        // A solid object displaces the liquid
+        if furniture_type.is_solid() && world_data.get_liquid(tile_pos).is_some() {
+            let changed = world_data.displace_liquid(tile_pos);
+            ChunkManager::update_liquids(commands, query_chunk, query_tile, world_data, &changed);
+        }
+    }
+}
+
+/// Removes all tiles of the object which the tile at the position belongs to and returns the position of its top left tile
+pub(super) fn remove_furniture(
+    commands: &mut Commands,
+    world_data: &mut WorldData,
+    query_chunk: &mut Query<(&Chunk, &mut TileStorage)>,
+    furniture: Furniture,
+    tile_pos: TilePos,
+) -> TilePos {
+    let origin = furniture.origin(tile_pos);
+
+    for (tile_pos, _) in Furniture::tiles(furniture.furniture_type, furniture.style, origin) {
+        world_data.remove_block(tile_pos);
+
+        ChunkManager::remove(commands, query_chunk, tile_pos, ChunkType::Furniture);
+        ChunkManager::remove(commands, query_chunk, tile_pos, ChunkType::Cracks);
+    }
+
+    origin
+}
+
+/// Opens and closes the door under the cursor.
+/// A door opens away from the player if there is enough space for it, otherwise it opens towards the player.
+fn toggle_door(
+    mut commands: Commands,
+    mut world_data: ResMut<WorldData>,
+    cursor_position: Res<CursorPosition<MainCamera>>,
+    query_player: Query<&EntityRect, With<Player>>,
+    mut query_chunk: ParamSet<(Query<(&Chunk, &mut TileStorage)>, Query<(&Chunk, &mut TileStorage, Entity)>)>,
+    mut query_tile: Query<&mut TileTextureIndex>,
+    mut update_neighbors_events: EventWriter<UpdateNeighborsEvent>,
+    mut tile_removed_events: EventWriter<TileRemovedEvent>,
+    mut place_tile_events: EventWriter<PlaceTileEvent>,
+) {
+    let Ok(player_rect) = query_player.get_single() else { return; };
+
+    let tile_pos = get_tile_pos_from_world_coords(world_data.area.size(), cursor_position.world);
+
+    let Some(BlockType::Furniture(furniture)) = world_data.get_block(tile_pos).map(|b| b.block_type) else { return; };
+
+    let origin = furniture.origin(tile_pos);
+    let height = furniture.furniture_type.size().y;
+
+    let column_is_free = |x: u32| -> bool {
+        (x as usize) < world_data.width() && (0..height).all(|y| !world_data.block_exists((x, origin.y + y)))
+    };
+
+    let (new_type, new_style, new_origin) = match furniture.furniture_type {
+        FurnitureType::DoorClosed => {
+            let open_right = (TilePos::new(origin.x, origin.y), 0);
+            let open_left = origin.x.checked_sub(1).map(|x| (TilePos::new(x, origin.y), 1));
+
+            let player_on_left = player_rect.centerx < tile_to_world_pos(origin).x;
+
+            let (preferred, other) = if player_on_left {
+                (Some(open_right), open_left)
+            } else {
+                (open_left, Some(open_right))
+            };
+
+            // The leaf of the door takes the column next to the hinge
+            let is_free = |&(new_origin, style): &(TilePos, u8)| {
+                let leaf_x = if style == 0 { new_origin.x + 1 } else { new_origin.x };
+                column_is_free(leaf_x)
+            };
+
+            let Some((new_origin, style)) = preferred.filter(is_free).or(other.filter(is_free)) else { return; };
+
+            (FurnitureType::DoorOpen, style, new_origin)
+        },
+        FurnitureType::DoorOpen => {
+            let hinge = TilePos::new(origin.x + furniture.style as u32, origin.y);
+
+            // Don't close the door on the player
+            let Vec2 { x, y } = tile_to_world_pos(hinge);
+            let door_rect = FRect::new(x - TILE_SIZE / 2., y + TILE_SIZE / 2., TILE_SIZE, height as f32 * TILE_SIZE);
+            if player_rect.intersects(&door_rect) { return; }
+
+            (FurnitureType::DoorClosed, 0, hinge)
+        },
+        _ => return
+    };
+
+    remove_furniture(&mut commands, &mut world_data, &mut query_chunk.p0(), furniture, tile_pos);
+
+    for (tile_pos, furniture) in Furniture::tiles(furniture.furniture_type, furniture.style, origin) {
+        tile_removed_events.send(TileRemovedEvent { tile_pos, tile_type: TileType::Block(Some(BlockType::Furniture(furniture))) });
+        update_neighbors_events.send(UpdateNeighborsEvent { tile_pos });
+    }
+
+    spawn_furniture(&mut commands, &mut world_data, &mut query_chunk.p1(), &mut query_tile, new_type, new_style, new_origin);
+
+    // The tiles already exist, so the events only update the lighting and the world map
+    for (tile_pos, furniture) in Furniture::tiles(new_type, new_style, new_origin) {
+        place_tile_events.send(PlaceTileEvent { tile_pos, tile_type: TileType::Block(Some(BlockType::Furniture(furniture))) });
+        update_neighbors_events.send(UpdateNeighborsEvent { tile_pos });
+    }
+
+    commands.play_sound(SoundType::BlockHit(BlockType::Furniture(furniture)));
+}
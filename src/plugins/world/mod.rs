@@ -2,14 +2,18 @@ pub(crate) mod events;
 pub(crate) mod resources;
 pub(crate) mod constants;
 pub(crate) mod time;
+mod falling_block;
+mod furniture;
 mod utils;
 mod systems;
 
+use std::time::Duration;
+
 use crate::{common::{state::GameState, systems::set_resource}, world::{block::BlockType, wall::WallType}};
-use bevy::{prelude::{Plugin, App, OnEnter, IntoSystemConfigs, Update, Rect, OnExit, Resource, UVec2, Deref}, math::URect, render::view::RenderLayers};
+use bevy::{prelude::{Plugin, App, OnEnter, IntoSystemConfigs, Update, FixedUpdate, Rect, OnExit, Resource, UVec2, Deref, on_event, Condition}, math::URect, render::view::RenderLayers, app::AppExit, window::WindowCloseRequested, time::common_conditions::on_fixed_timer};
 use bevy_ecs_tilemap::TilemapPlugin;
 
-use self::time::{WorldTimePlugin, GameTime};
+use self::{time::{WorldTimePlugin, GameTime}, falling_block::FallingBlockPlugin, furniture::FurniturePlugin};
 
 use super::{InGameSystemSet, particles::ParticlePlugin, item::ItemPlugin};
 
@@ -30,14 +34,16 @@ pub(crate) enum TileType {
 pub(crate) struct WorldPlugin;
 impl Plugin for WorldPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins((TilemapPlugin, ParticlePlugin, ItemPlugin, WorldTimePlugin));
+        app.add_plugins((TilemapPlugin, ParticlePlugin, ItemPlugin, WorldTimePlugin, FallingBlockPlugin, FurniturePlugin));
 
         app.add_event::<events::BreakTileEvent>();
 
         app.add_event::<events::TileRemovedEvent>();
+        app.add_event::<events::LiquidChangedEvent>();
 
         app.add_event::<events::DigBlockEvent>();
         app.add_event::<events::DigWallEvent>();
+        app.add_event::<events::HammerBlockEvent>();
 
         app.add_event::<events::PlaceTileEvent>();
 
@@ -50,7 +56,7 @@ impl Plugin for WorldPlugin {
         app.add_event::<events::SeedEvent>();
 
         app.add_systems(OnEnter(GameState::WorldLoading), (systems::setup, systems::spawn_terrain));
-        app.add_systems(OnExit(GameState::InGame), systems::cleanup);
+        app.add_systems(OnExit(GameState::InGame), (systems::save_world, systems::cleanup).chain());
 
         app.add_systems(
             OnEnter(GameState::InGame),
@@ -65,6 +71,7 @@ impl Plugin for WorldPlugin {
 
                 systems::handle_dig_block_event,
                 systems::handle_dig_wall_event,
+                systems::handle_hammer_block_event,
 
                 systems::handle_place_tile_event,
 
@@ -81,6 +88,20 @@ impl Plugin for WorldPlugin {
             .in_set(InGameSystemSet::Update)
         );
 
+        app.add_systems(
+            FixedUpdate,
+            systems::update_liquids
+                .in_set(InGameSystemSet::FixedUpdate)
+                .run_if(on_fixed_timer(Duration::from_millis(50)))
+        );
+
+        app.add_systems(
+            Update,
+            systems::save_world
+                .run_if(on_event::<AppExit>().or_else(on_event::<WindowCloseRequested>()))
+                .in_set(InGameSystemSet::Update)
+        );
+
         #[cfg(feature = "debug")]
         app.add_systems(Update, systems::set_tiles_visibility.in_set(InGameSystemSet::Update));
     }
@@ -1,9 +1,9 @@
-use bevy::{utils::HashSet, prelude::{Resource, Query, Entity, UVec2, Commands, DespawnRecursiveExt, BuildChildren}};
+use bevy::{utils::HashSet, prelude::{Resource, Query, Entity, UVec2, Commands, DespawnRecursiveExt, BuildChildren, World}, math::URect};
 use bevy_ecs_tilemap::tiles::{TilePos, TileStorage, TileTextureIndex};
 
-use crate::world::{chunk::{ChunkPos, Chunk, ChunkType}, block::{BlockType, Block}};
+use crate::world::{chunk::{ChunkPos, Chunk, ChunkType}, block::{BlockType, Block}, WorldData};
 
-use super::{systems::{spawn_block, spawn_cracks, spawn_wall}, utils::{get_chunk_pos, get_chunk_tile_pos}};
+use super::{constants::CHUNK_SIZE_U, events::LiquidChangedEvent, systems::{spawn_block, spawn_cracks, spawn_wall, spawn_liquid}, utils::{get_chunk_pos, get_chunk_tile_pos, liquid_texture_index}};
 
 #[derive(Resource, Clone, Copy, Default)]
 pub(crate) struct WorldUndergroundLevel(pub(crate) u32);
@@ -14,6 +14,14 @@ pub(super) struct ChunkManager {
 }
 
 impl ChunkManager {
+    /// Returns the tiles covered by the spawned chunks
+    pub(super) fn spawned_area(&self) -> Option<URect> {
+        let min = self.spawned_chunks.iter().copied().reduce(UVec2::min)?;
+        let max = self.spawned_chunks.iter().copied().reduce(UVec2::max)?;
+
+        Some(URect::from_corners(min * CHUNK_SIZE_U, (max + 1) * CHUNK_SIZE_U))
+    }
+
     fn filter_chunk(chunk: &Chunk, chunk_pos: UVec2, block_type: BlockType) -> bool {
         chunk.pos == chunk_pos && chunk.chunk_type == ChunkType::from(block_type)
     }
@@ -115,6 +123,69 @@ impl ChunkManager {
         }
     }
 
+    /// Updates the sprite of the liquid at the position, spawning or despawning the tile if needed.
+    /// The liquid is removed when `index` is `None`.
+    pub(super) fn update_liquid(
+        commands: &mut Commands,
+        query_chunk: &mut Query<(&Chunk, &mut TileStorage, Entity)>,
+        query_tile: &mut Query<&mut TileTextureIndex>,
+        tile_pos: TilePos,
+        index: Option<u32>,
+    ) {
+        let chunk_pos = get_chunk_pos(tile_pos);
+        let chunk_tile_pos = get_chunk_tile_pos(tile_pos);
+
+        let filtered_chunk = query_chunk
+            .iter_mut()
+            .find(|(chunk, _, _)| {
+                chunk.pos == chunk_pos && chunk.chunk_type == ChunkType::Liquid
+            });
+
+        let Some((_, mut tile_storage, liquid_map_entity)) = filtered_chunk else { return; };
+
+        match (tile_storage.checked_get(&chunk_tile_pos), index) {
+            (Some(liquid_entity), Some(index)) => {
+                if let Ok(mut tile_texture) = query_tile.get_mut(liquid_entity) {
+                    tile_texture.0 = index;
+                }
+            },
+            (Some(liquid_entity), None) => {
+                commands.entity(liquid_entity).despawn_recursive();
+                tile_storage.remove(&chunk_tile_pos);
+            },
+            (None, Some(index)) => {
+                let liquid_entity = spawn_liquid(commands, chunk_tile_pos, liquid_map_entity, index);
+                commands.entity(liquid_map_entity).add_child(liquid_entity);
+                tile_storage.set(&chunk_tile_pos, liquid_entity);
+            },
+            (None, None) => {}
+        }
+    }
+
+    /// Updates the sprites of the changed liquids and of the liquids below them.
+    /// A [`LiquidChangedEvent`] is sent for every changed liquid, so the lighting sees it too.
+    pub(super) fn update_liquids(
+        commands: &mut Commands,
+        query_chunk: &mut Query<(&Chunk, &mut TileStorage, Entity)>,
+        query_tile: &mut Query<&mut TileTextureIndex>,
+        world_data: &WorldData,
+        changed: &HashSet<TilePos>,
+    ) {
+        // The sprite of a liquid depends on the liquid above it
+        let to_update: HashSet<TilePos> = changed.iter()
+            .flat_map(|pos| [*pos, TilePos::new(pos.x, pos.y + 1)])
+            .filter(|pos| (pos.y as usize) < world_data.height())
+            .collect();
+
+        for tile_pos in to_update {
+            let index = liquid_texture_index(world_data, tile_pos);
+            Self::update_liquid(commands, query_chunk, query_tile, tile_pos, index);
+        }
+
+        let events: Vec<LiquidChangedEvent> = changed.iter().map(|&tile_pos| LiquidChangedEvent { tile_pos }).collect();
+        commands.add(move |world: &mut World| world.send_event_batch(events));
+    }
+
     pub(super) fn update_tile_cracks(
         commands: &mut Commands,
         query_chunk: &mut Query<(&Chunk, &mut TileStorage, Entity)>,
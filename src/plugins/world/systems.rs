@@ -1,10 +1,10 @@
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::{time::{SystemTime, UNIX_EPOCH}, io::ErrorKind};
 
 use bevy::{
     prelude::{
         EventReader, ResMut, Query, Commands, EventWriter, Entity, BuildChildren, Transform, 
         default, SpatialBundle, DespawnRecursiveExt, OrthographicProjection, Changed, 
-        GlobalTransform, With, Res, UVec2, NextState, Name, Vec2,
+        GlobalTransform, With, Res, UVec2, NextState, Name, Vec2, Local, ParamSet,
     }, 
     math::Vec3Swizzles, render::view::NoFrustumCulling
 };
@@ -20,13 +20,14 @@ use bevy_ecs_tilemap::{
 };
 use rand::{thread_rng, Rng};
 
-use crate::{plugins::{assets::{BlockAssets, WallAssets}, camera::components::MainCamera, audio::{SoundType, AudioCommandsExt}, DespawnOnGameExit, item::ItemCommandsExt, particles::Particle}, common::{state::GameState, TextureAtlasPos, math::map_range_i32, helpers::{tile_to_world_pos, random_point_cone}}, world::{WorldSize, chunk::{Chunk, ChunkType, ChunkContainer, ChunkPos}, WorldData, block::{BlockType, Block}, wall::Wall, tree::TreeFrameType, generator::generate_world}, WALL_LAYER, TILES_LAYER, items::ItemStack};
+use crate::{plugins::{assets::{BlockAssets, WallAssets}, camera::components::MainCamera, audio::{SoundType, AudioCommandsExt}, DespawnOnGameExit, item::ItemCommandsExt}, common::{state::GameState, TextureAtlasPos, math::map_range_i32, helpers::{tile_to_world_pos, random_point_cone}}, world::{WorldSize, chunk::{Chunk, ChunkType, ChunkContainer, ChunkPos}, WorldData, block::{BlockType, Block}, wall::Wall, tree::TreeFrameType, furniture::{Furniture, FURNITURE_TEXTURE_WIDTH}, generator::generate_world}, WALL_LAYER, TILES_LAYER, items::ItemStack};
 
 use super::{
-    utils::{get_chunk_pos, get_camera_fov, get_chunk_tile_pos, get_chunk_range_by_camera_fov, self}, 
-    events::{UpdateNeighborsEvent, DigBlockEvent, UpdateBlockEvent, SeedEvent, UpdateCracksEvent, UpdateWallEvent, DigWallEvent, BreakTileEvent, PlaceTileEvent, TileRemovedEvent},
+    utils::{get_chunk_pos, get_camera_fov, get_chunk_tile_pos, get_chunk_range_by_camera_fov, liquid_texture_index, self}, 
+    events::{UpdateNeighborsEvent, DigBlockEvent, UpdateBlockEvent, SeedEvent, UpdateCracksEvent, UpdateWallEvent, DigWallEvent, HammerBlockEvent, BreakTileEvent, PlaceTileEvent, TileRemovedEvent},
     resources::{ChunkManager, WorldUndergroundLevel}, 
-    constants::{CHUNK_SIZE_U, WALL_SIZE, CHUNKMAP_SIZE, TREE_SIZE, TREE_BRANCHES_SIZE, TREE_TOPS_SIZE, CHUNK_SIZE, TILE_SIZE}, WORLD_RENDER_LAYER, TileType
+    furniture,
+    constants::{CHUNK_SIZE_U, WALL_SIZE, CHUNKMAP_SIZE, TREE_SIZE, TREE_BRANCHES_SIZE, TREE_TOPS_SIZE, CHUNK_SIZE, TILE_SIZE, WORLD_FILE_NAME}, WORLD_RENDER_LAYER, TileType
 };
 
 #[cfg(feature = "debug")]
@@ -42,9 +43,18 @@ pub(super) fn spawn_terrain(mut commands: Commands) {
     // let seed = current_time.as_millis() as u32;
     let seed = 2225406523;
 
-    println!("The seed of the world is {}", seed);
+    let world_data = match WorldData::load(WORLD_FILE_NAME) {
+        Ok(world_data) => world_data,
+        Err(err) => {
+            if err.kind() != ErrorKind::NotFound {
+                eprintln!("Failed to load the world: {}", err);
+            }
+
+            println!("The seed of the world is {}", seed);
 
-    let world_data = generate_world(seed, WorldSize::Tiny);
+            generate_world(seed, WorldSize::Tiny)
+        }
+    };
 
     commands.insert_resource(WorldUndergroundLevel(world_data.layer.underground as u32));
     commands.insert_resource(super::WorldSize(world_data.area.size()));
@@ -52,6 +62,12 @@ pub(super) fn spawn_terrain(mut commands: Commands) {
     commands.insert_resource(NextState(Some(GameState::InGame)));
 }
 
+pub(super) fn save_world(world_data: Res<WorldData>) {
+    if let Err(err) = world_data.save(WORLD_FILE_NAME) {
+        eprintln!("Failed to save the world: {}", err);
+    }
+}
+
 pub(super) fn cleanup(mut commands: Commands) {
     commands.remove_resource::<WorldData>();
     commands.remove_resource::<ChunkManager>();
@@ -64,18 +80,30 @@ pub(super) fn spawn_block(
     tilemap_entity: Entity,
     index: u32
 ) -> Entity {
-    commands
-        .spawn((
-            Name::new(format!("{:#?}", block.block_type)),
-            block,
-            TileBundle {
-                position: tile_pos,
-                tilemap_id: TilemapId(tilemap_entity),
-                texture_index: TileTextureIndex(index),
-                ..default()
-            }
-        ))
-        .id()
+    let mut entity_commands = commands.spawn((
+        Name::new(format!("{:#?}", block.block_type)),
+        block,
+        TileBundle {
+            position: tile_pos,
+            tilemap_id: TilemapId(tilemap_entity),
+            texture_index: TileTextureIndex(index),
+            ..default()
+        }
+    ));
+
+    // The light source is positioned relative to the tilemap, so it moves together with the chunk
+    if let Some(light) = block.definition().light {
+        entity_commands.insert((
+            SpatialBundle::from_transform(Transform::from_xyz(
+                tile_pos.x as f32 * TILE_SIZE,
+                tile_pos.y as f32 * TILE_SIZE,
+                0.
+            )),
+            light.light_source()
+        ));
+    }
+
+    entity_commands.id()
 }
 
 pub(super) fn spawn_cracks(
@@ -109,6 +137,22 @@ pub(super) fn spawn_wall(
         .id()
 }
 
+pub(super) fn spawn_liquid(
+    commands: &mut Commands,
+    tile_pos: TilePos,
+    liquid_map_entity: Entity,
+    index: u32
+) -> Entity {
+    commands
+        .spawn(TileBundle {
+            position: tile_pos,
+            tilemap_id: TilemapId(liquid_map_entity),
+            texture_index: TileTextureIndex(index),
+            ..default()
+        })
+        .id()
+}
+
 pub(super) fn spawn_chunks(
     mut commands: Commands,
     block_assets: Res<BlockAssets>,
@@ -198,6 +242,12 @@ pub(super) fn spawn_chunk(
     let tree_tops_map_entity = commands.spawn_empty().id();
     let mut tree_tops_storage = TileStorage::empty(CHUNKMAP_SIZE);
 
+    let liquid_map_entity = commands.spawn_empty().id();
+    let mut liquid_storage = TileStorage::empty(CHUNKMAP_SIZE);
+
+    let furniture_map_entity = commands.spawn_empty().id();
+    let mut furniture_storage = TileStorage::empty(CHUNKMAP_SIZE);
+
     for y in 0..CHUNK_SIZE_U {
         for x in 0..CHUNK_SIZE_U {
             let chunk_tile_pos = TilePos { 
@@ -231,6 +281,12 @@ pub(super) fn spawn_chunk(
                             tree_storage.set(&chunk_tile_pos, tree_entity);
                         }
                     }
+                } else if let BlockType::Furniture(furniture) = block.block_type {
+                    let index = furniture.texture_atlas_pos().to_2d_index(FURNITURE_TEXTURE_WIDTH);
+
+                    let furniture_entity = spawn_block(commands, block, chunk_tile_pos, furniture_map_entity, index);
+                    commands.entity(furniture_map_entity).add_child(furniture_entity);
+                    furniture_storage.set(&chunk_tile_pos, furniture_entity);
                 } else {
                     let index = Block::get_sprite_index(
                         &world_data.get_block_neighbors(map_tile_pos, block.is_solid()).map_ref(|b| b.block_type), 
@@ -267,6 +323,12 @@ pub(super) fn spawn_chunk(
                     tile_storage.set(&chunk_tile_pos, cracks_entity);
                 }
             }
+
+            if let Some(index) = liquid_texture_index(world_data, map_tile_pos) {
+                let liquid_entity = spawn_liquid(commands, chunk_tile_pos, liquid_map_entity, index);
+                commands.entity(liquid_map_entity).add_child(liquid_entity);
+                liquid_storage.set(&chunk_tile_pos, liquid_entity);
+            }
         }
     }
 
@@ -416,10 +478,66 @@ pub(super) fn spawn_chunk(
             }
         ));
 
+    commands
+        .entity(liquid_map_entity)
+        .insert((
+            Name::new("LiquidMap"),
+            Chunk::new(chunk_pos, ChunkType::Liquid),
+            WORLD_RENDER_LAYER,
+            NoFrustumCulling,
+            TilemapBundle {
+                grid_size: TilemapGridSize {
+                    x: TILE_SIZE,
+                    y: TILE_SIZE,
+                },
+                size: CHUNKMAP_SIZE,
+                storage: liquid_storage,
+                texture: TilemapTexture::Single(block_assets.liquids.clone_weak()),
+                tile_size: TilemapTileSize {
+                    x: TILE_SIZE,
+                    y: TILE_SIZE,
+                },
+                spacing: TilemapSpacing {
+                    x: 2.,
+                    y: 2.
+                },
+                transform: Transform::from_xyz(0., 0., TILES_LAYER + 0.3),
+                ..default()
+            }
+        ));
+
+    commands
+        .entity(furniture_map_entity)
+        .insert((
+            Name::new("FurnitureMap"),
+            Chunk::new(chunk_pos, ChunkType::Furniture),
+            WORLD_RENDER_LAYER,
+            NoFrustumCulling,
+            TilemapBundle {
+                grid_size: TilemapGridSize {
+                    x: TILE_SIZE,
+                    y: TILE_SIZE,
+                },
+                size: CHUNKMAP_SIZE,
+                storage: furniture_storage,
+                texture: TilemapTexture::Single(block_assets.furniture.clone_weak()),
+                tile_size: TilemapTileSize {
+                    x: TILE_SIZE,
+                    y: TILE_SIZE,
+                },
+                spacing: TilemapSpacing {
+                    x: 2.,
+                    y: 2.
+                },
+                transform: Transform::from_xyz(0., 0., TILES_LAYER + 0.4),
+                ..default()
+            }
+        ));
+
     commands
         .entity(chunk)
         .push_children(
-            &[tilemap_entity, wallmap_entity, treemap_entity, tree_branches_map_entity, tree_tops_map_entity, tile_crack_map_entity]
+            &[tilemap_entity, wallmap_entity, treemap_entity, tree_branches_map_entity, tree_tops_map_entity, tile_crack_map_entity, liquid_map_entity, furniture_map_entity]
         );
 }
 
@@ -440,22 +558,62 @@ pub(super) fn handle_break_tile_event(
 
                 if let BlockType::Tree(_) = block_type {
                     break_tree(&mut commands, &mut world_data, &mut query_chunk, &mut tile_removed, tile_pos, false);
+                } else if let BlockType::Furniture(furniture) = block_type {
+                    let origin = furniture::remove_furniture(&mut commands, &mut world_data, &mut query_chunk, furniture, tile_pos);
+
+                    // The whole object drops a single item from its center
+                    let size = furniture.furniture_type.size().as_vec2();
+                    let position = tile_to_world_pos(origin) + Vec2::new(size.x - 1., -(size.y - 1.)) * TILE_SIZE / 2.;
+
+                    for (pos, furniture) in Furniture::tiles(furniture.furniture_type, furniture.style, origin) {
+                        if let Some(particle) = block_type.definition().particle {
+                            utils::spawn_particles_on_break(&mut commands, particle, pos);
+                        }
+
+                        if pos != tile_pos {
+                            tile_removed.send(TileRemovedEvent { tile_pos: pos, tile_type: TileType::Block(Some(BlockType::Furniture(furniture))) });
+                            update_neighbors.send(UpdateNeighborsEvent { tile_pos: pos });
+                        }
+                    }
+
+                    if let Some(item) = block_type.dropped_item() {
+                        commands.spawn_dropped_item(
+                            position,
+                            Vec2::new(rng.gen_range(-0.5f32..0.5f32), rng.gen_range(0.5f32..1.0f32)) * 3.,
+                            ItemStack::new(item),
+                            None
+                        );
+                    }
+
+                    // A chest spills the items stored in it
+                    let stored_items = world_data.chests.remove(&origin).into_iter().flat_map(|chest| chest.slots).flatten();
+
+                    for item_stack in stored_items {
+                        commands.spawn_dropped_item(
+                            position,
+                            Vec2::new(rng.gen_range(-0.5f32..0.5f32), rng.gen_range(0.5f32..1.0f32)) * 3.,
+                            item_stack,
+                            None
+                        );
+                    }
                 } else {
                     world_data.remove_block(tile_pos);
 
                     ChunkManager::remove(&mut commands, &mut query_chunk, tile_pos, ChunkType::from(block_type));
                     ChunkManager::remove(&mut commands, &mut query_chunk, tile_pos, ChunkType::Cracks);
 
-                    if let Some(particle) = Particle::get_by_block(block_type) {
+                    if let Some(particle) = block_type.definition().particle {
                         utils::spawn_particles_on_break(&mut commands, particle, tile_pos);
                     }
 
-                    commands.spawn_dropped_item(
-                        tile_to_world_pos(tile_pos),
-                        Vec2::new(rng.gen_range(-0.5f32..0.5f32), rng.gen_range(0.5f32..1.0f32)) * 3.,
-                        ItemStack::new_block(block_type.into()),
-                        None
-                    );
+                    if let Some(item) = block_type.dropped_item() {
+                        commands.spawn_dropped_item(
+                            tile_to_world_pos(tile_pos),
+                            Vec2::new(rng.gen_range(-0.5f32..0.5f32), rng.gen_range(0.5f32..1.0f32)) * 3.,
+                            ItemStack::new(item),
+                            None
+                        );
+                    }
                 }
 
                 commands.play_sound(SoundType::BlockHit(block_type));
@@ -469,10 +627,19 @@ pub(super) fn handle_break_tile_event(
                 ChunkManager::remove(&mut commands, &mut query_chunk, tile_pos, ChunkType::Wall);
                 ChunkManager::remove(&mut commands, &mut query_chunk, tile_pos, ChunkType::Cracks);
 
-                if let Some(particle) = Particle::get_by_wall(wall_type) {
+                if let Some(particle) = wall_type.definition().particle {
                     utils::spawn_particles_on_break(&mut commands, particle, tile_pos);
                 }
 
+                if let Some(item) = wall_type.dropped_item() {
+                    commands.spawn_dropped_item(
+                        tile_to_world_pos(tile_pos),
+                        Vec2::new(rng.gen_range(-0.5f32..0.5f32), rng.gen_range(0.5f32..1.0f32)) * 3.,
+                        ItemStack::new(item),
+                        None
+                    );
+                }
+
                 commands.play_sound(SoundType::WallHit);
                 update_neighbors.send(UpdateNeighborsEvent { tile_pos });
             },
@@ -494,6 +661,12 @@ pub(super) fn handle_dig_block_event(
 
     for &DigBlockEvent { tile_pos, tool } in dig_block_events.iter() {
         if let Some(block) = world_data.get_block_mut(tile_pos) {
+            if !block.can_be_damaged_by(tool) {
+                commands.play_sound(SoundType::ToolTooWeak);
+                continue;
+            }
+
+            // The hits are spaced by the tool's use cooldown, so a higher tier breaks blocks both in fewer hits and faster
             block.hp -= tool.power();
 
             if block.hp <= 0 {
@@ -502,12 +675,14 @@ pub(super) fn handle_dig_block_event(
                     tile_type: TileType::Block(Some(block.block_type))
                 });
             } else {
-                if let Some(particle) = Particle::get_by_block(block.block_type) {
+                if let Some(particle) = block.definition().particle {
                     utils::spawn_particles_on_dig(&mut commands, particle, tile_pos);
                 }
 
-                if block.block_type == BlockType::Grass {
-                    block.block_type = BlockType::Dirt;
+                match block.block_type {
+                    BlockType::Grass => block.block_type = BlockType::Dirt,
+                    BlockType::JungleGrass => block.block_type = BlockType::Mud,
+                    _ => {}
                 }
 
                 let x = block.cracks_index.map(|index| index / 6).unwrap_or_else(|| rng.gen_range(0..6));
@@ -525,6 +700,26 @@ pub(super) fn handle_dig_block_event(
     }
 }
 
+pub(super) fn handle_hammer_block_event(
+    mut commands: Commands,
+    mut world_data: ResMut<WorldData>,
+    mut update_block_events: EventWriter<UpdateBlockEvent>,
+    mut hammer_block_events: EventReader<HammerBlockEvent>,
+) {
+    for &HammerBlockEvent { tile_pos } in hammer_block_events.iter() {
+        if let Some(block) = world_data.get_block_mut(tile_pos) {
+            block.shape = block.shape.next();
+
+            if let Some(particle) = block.definition().particle {
+                utils::spawn_particles_on_dig(&mut commands, particle, tile_pos);
+            }
+
+            commands.play_sound(SoundType::BlockHit(block.block_type));
+            update_block_events.send(UpdateBlockEvent { tile_pos, block: *block });
+        }
+    }
+}
+
 pub(super) fn handle_dig_wall_event(
     mut commands: Commands,
     mut world_data: ResMut<WorldData>,
@@ -546,7 +741,7 @@ pub(super) fn handle_dig_wall_event(
                     tile_type: TileType::Wall(Some(wall.wall_type))
                 });
             } else {
-                if let Some(particle) = Particle::get_by_wall(wall.wall_type) {
+                if let Some(particle) = wall.definition().particle {
                     utils::spawn_particles_on_dig(&mut commands, particle, tile_pos);
                 }
 
@@ -568,12 +763,32 @@ pub(super) fn handle_dig_wall_event(
 pub(super) fn handle_place_tile_event(
     mut commands: Commands,
     mut query_chunk: Query<(&Chunk, &mut TileStorage, Entity)>,
+    mut query_tile: Query<&mut TileTextureIndex>,
     mut world_data: ResMut<WorldData>,
-    mut place_block: EventReader<PlaceTileEvent>,
+    mut place_tile_events: ParamSet<(EventReader<PlaceTileEvent>, EventWriter<PlaceTileEvent>)>,
     mut update_neighbors: EventWriter<UpdateNeighborsEvent>,
 ) {
-    for &PlaceTileEvent { tile_pos, tile_type } in place_block.iter() {
+    let events: Vec<PlaceTileEvent> = place_tile_events.p0().iter().copied().collect();
+
+    for PlaceTileEvent { tile_pos, tile_type } in events {
         match tile_type {
+            TileType::Block(Some(BlockType::Furniture(furniture))) => {
+                let furniture_type = furniture.furniture_type;
+
+                let Some((origin, style)) = world_data.furniture_placement(furniture_type, tile_pos) else { continue; };
+
+                furniture::spawn_furniture(&mut commands, &mut world_data, &mut query_chunk, &mut query_tile, furniture_type, style, origin);
+
+                // Let the lighting and the world map know about the rest of the tiles
+                for (pos, furniture) in Furniture::tiles(furniture_type, style, origin) {
+                    if pos != tile_pos {
+                        place_tile_events.p1().send(PlaceTileEvent { tile_pos: pos, tile_type: TileType::Block(Some(BlockType::Furniture(furniture))) });
+                    }
+                    update_neighbors.send(UpdateNeighborsEvent { tile_pos: pos });
+                }
+
+                commands.play_sound(SoundType::BlockPlace(BlockType::Furniture(furniture)));
+            },
             TileType::Block(Some(block_type)) => {
                 if world_data.block_exists(tile_pos) { continue; }
 
@@ -589,6 +804,12 @@ pub(super) fn handle_place_tile_event(
 
                 ChunkManager::spawn_block(&mut commands, &mut query_chunk, tile_pos, &new_block, index);
 
+                // A solid block displaces the liquid
+                if block_type.is_solid() && world_data.get_liquid(tile_pos).is_some() {
+                    let changed = world_data.displace_liquid(tile_pos);
+                    ChunkManager::update_liquids(&mut commands, &mut query_chunk, &mut query_tile, &world_data, &changed);
+                }
+
                 update_neighbors.send(UpdateNeighborsEvent { tile_pos });
                 commands.play_sound(SoundType::BlockHit(block_type));
             },
@@ -615,6 +836,24 @@ pub(super) fn handle_place_tile_event(
     }
 }
 
+pub(super) fn update_liquids(
+    mut commands: Commands,
+    mut world_data: ResMut<WorldData>,
+    chunk_manager: Res<ChunkManager>,
+    mut query_chunk: Query<(&Chunk, &mut TileStorage, Entity)>,
+    mut query_tile: Query<&mut TileTextureIndex>,
+    mut step: Local<u32>,
+) {
+    // The liquids flow only in the chunks which are currently spawned
+    let Some(area) = chunk_manager.spawned_area() else { return; };
+
+    *step = step.wrapping_add(1);
+
+    let changed = world_data.simulate_liquids(area, *step);
+
+    ChunkManager::update_liquids(&mut commands, &mut query_chunk, &mut query_tile, &world_data, &changed);
+}
+
 pub(super) fn handle_update_neighbors_event(
     world_data: Res<WorldData>,
     mut events: EventReader<UpdateNeighborsEvent>,
@@ -738,14 +977,18 @@ fn break_tree(
 
             ChunkManager::remove(commands, chunks, pos, ChunkType::from(block.block_type));
             ChunkManager::remove(commands, chunks, pos, ChunkType::Cracks);
-            utils::spawn_particles_on_break(commands, Particle::Wood, pos);
-
-            commands.spawn_dropped_item(
-                tile_to_world_pos(pos),
-                random_point_cone(Vec2::Y, 150.0, 1.) * 4.,
-                ItemStack::new_block(block.block_type.into()),
-                None
-            );
+            if let Some(particle) = block.definition().particle {
+                utils::spawn_particles_on_break(commands, particle, pos);
+            }
+
+            if let Some(item) = block.dropped_item() {
+                commands.spawn_dropped_item(
+                    tile_to_world_pos(pos),
+                    random_point_cone(Vec2::Y, 150.0, 1.) * 4.,
+                    ItemStack::new(item),
+                    None
+                );
+            }
 
             tile_removed.send(TileRemovedEvent { tile_pos: pos, tile_type: TileType::Block(Some(block.block_type)) });
 
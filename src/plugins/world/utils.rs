@@ -4,7 +4,7 @@ use bevy::prelude::{Vec2, OrthographicProjection, UVec2, Commands};
 use bevy_ecs_tilemap::tiles::TilePos;
 use rand::{thread_rng, Rng};
 
-use crate::{world::chunk::ChunkPos, common::helpers::{random_point_circle, tile_to_world_pos}, plugins::particles::{PARTICLE_SIZE, Particle, ParticleCommandsExt, ParticleBuilder}};
+use crate::{world::{chunk::ChunkPos, WorldData}, common::helpers::{random_point_circle, tile_to_world_pos}, plugins::particles::{PARTICLE_SIZE, Particle, ParticleCommandsExt, ParticleBuilder}};
 
 use super::{constants::{CHUNK_SIZE_U, CHUNK_SIZE, TILE_SIZE}, CameraFov, ChunkRange, WORLD_RENDER_LAYER};
 
@@ -21,6 +21,17 @@ pub(super) fn get_chunk_tile_pos(map_tile_pos: TilePos) -> TilePos {
     }
 }
 
+/// A liquid is drawn as a full tile when there is more of the same liquid above it
+pub(super) fn liquid_texture_index(world_data: &WorldData, tile_pos: TilePos) -> Option<u32> {
+    let liquid = world_data.get_liquid(tile_pos)?;
+
+    let liquid_above = tile_pos.y > 0 && world_data
+        .get_liquid((tile_pos.x, tile_pos.y - 1))
+        .is_some_and(|above| above.liquid_type == liquid.liquid_type);
+
+    Some(liquid.texture_index(liquid_above))
+}
+
 #[inline(always)]
 pub(super) fn get_camera_fov(camera_pos: Vec2, projection: &OrthographicProjection) -> CameraFov {
     CameraFov {
@@ -285,7 +285,9 @@ pub(super) fn init_world_map_texture(
 
             let index = ((y * world_data.playable_width()) + x) * 4;
 
-            let color = world_data.get_block_color(pos).or(world_data.get_wall_color(pos))
+            let color = world_data.get_block_color(pos)
+                .or(world_data.get_liquid_color(pos))
+                .or(world_data.get_wall_color(pos))
                 .unwrap_or_else(|| {
                     if y >= world_data.layer.underground {
                         WallType::Dirt.color()
@@ -354,7 +356,7 @@ pub(super) fn update_world_map_texture(
             let index = ((y * world_data.playable_width()) + x) * 4;
 
             let color = match event.tile_type {
-                TileType::Block(_) => world_data.get_wall_color(event.tile_pos),
+                TileType::Block(_) => world_data.get_liquid_color(event.tile_pos).or(world_data.get_wall_color(event.tile_pos)),
                 TileType::Wall(_) => world_data.get_block_color(event.tile_pos),
             };
 
@@ -0,0 +1,13 @@
+use std::sync::Once;
+
+use crate::{items::load_items, world::registry::load_tiles};
+
+/// Loads the tile and item registries. The tests share them, so they are only loaded once.
+pub(crate) fn load_registries() {
+    static LOAD: Once = Once::new();
+
+    LOAD.call_once(|| {
+        load_tiles().unwrap();
+        load_items().unwrap();
+    });
+}
@@ -0,0 +1,103 @@
+use std::ops::Range;
+
+use super::{WorldData, block::BlockType, wall::WallType, tree::TreeType};
+
+pub(crate) type BiomeId = u8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "debug", derive(bevy::reflect::Reflect))]
+pub enum Biome {
+    #[default]
+    Forest,
+    Desert,
+    Snow,
+    Jungle,
+}
+
+impl Biome {
+    pub(crate) const ALL: [Biome; 4] = [Biome::Forest, Biome::Desert, Biome::Snow, Biome::Jungle];
+
+    pub const fn id(&self) -> BiomeId {
+        *self as BiomeId
+    }
+
+    pub(crate) fn from_id(id: BiomeId) -> Option<Biome> {
+        Self::ALL.into_iter().find(|biome| biome.id() == id)
+    }
+
+    /// The block which replaces grass on the surface
+    pub(crate) const fn surface_block(&self) -> BlockType {
+        match self {
+            Biome::Forest => BlockType::Grass,
+            Biome::Desert => BlockType::Sand,
+            Biome::Snow => BlockType::Snow,
+            Biome::Jungle => BlockType::JungleGrass,
+        }
+    }
+
+    /// The block which replaces dirt
+    pub(crate) const fn dirt_block(&self) -> BlockType {
+        match self {
+            Biome::Forest => BlockType::Dirt,
+            Biome::Desert => BlockType::Sand,
+            Biome::Snow => BlockType::Snow,
+            Biome::Jungle => BlockType::Mud,
+        }
+    }
+
+    /// The wall which replaces the dirt wall
+    pub(crate) const fn wall(&self) -> WallType {
+        match self {
+            Biome::Forest | Biome::Desert => WallType::Dirt,
+            Biome::Snow => WallType::Snow,
+            Biome::Jungle => WallType::Mud,
+        }
+    }
+
+    pub(crate) const fn tree_type(&self) -> Option<TreeType> {
+        match self {
+            Biome::Forest => Some(TreeType::Forest),
+            Biome::Desert => None,
+            Biome::Snow => Some(TreeType::Snow),
+            Biome::Jungle => Some(TreeType::Jungle),
+        }
+    }
+
+    /// Returns the biome the block is specific to
+    pub(crate) const fn from_block(block_type: BlockType) -> Option<Biome> {
+        match block_type {
+            BlockType::Dirt | BlockType::Grass => Some(Biome::Forest),
+            BlockType::Sand => Some(Biome::Desert),
+            BlockType::Snow => Some(Biome::Snow),
+            BlockType::Mud | BlockType::JungleGrass => Some(Biome::Jungle),
+            _ => None
+        }
+    }
+
+    /// Style of the tree tops and the underground background of a background region in a Terraria world.
+    /// Terraria picks both randomly for every region, the biome is used instead so the regions look different.
+    pub(crate) const fn terraria_region_style(&self) -> i32 {
+        match self {
+            Biome::Forest => 0,
+            Biome::Desert => 1,
+            Biome::Snow => 2,
+            Biome::Jungle => 3,
+        }
+    }
+}
+
+impl WorldData {
+    /// Returns the consecutive columns of the world which have the same biome, from left to right
+    pub fn biome_ranges(&self) -> Vec<(Range<usize>, Biome)> {
+        let mut ranges: Vec<(Range<usize>, Biome)> = Vec::new();
+
+        for (x, &biome) in self.biomes.iter().enumerate() {
+            match ranges.last_mut() {
+                Some((range, last_biome)) if *last_biome == biome => range.end = x + 1,
+                _ => ranges.push((x..x + 1, biome))
+            }
+        }
+
+        ranges
+    }
+}
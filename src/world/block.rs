@@ -3,13 +3,15 @@ use std::{ops::Deref, fmt::Debug};
 use bevy::prelude::Component;
 use bevy_ecs_tilemap::helpers::square_grid::neighbors::Neighbors;
 use rand::{thread_rng, Rng};
+use serde::Deserialize;
 
-use crate::{common::{helpers::get_tile_start_index, TextureAtlasPos}, items::{ItemTool, ItemBlock}};
+use crate::{common::{helpers::{get_tile_start_index, get_shaped_tile_start_index}, TextureAtlasPos}, items::{Item, ItemTool, ItemBlock}};
 
-use super::{tree::{Tree, TreeFrameType}, TerrariaFrame};
+use super::{tree::{Tree, TreeFrameType}, furniture::{Furniture, FURNITURE_TEXTURE_WIDTH}, registry::{BlockDefinition, block_definitions}, TerrariaFrame};
 
 pub(crate) type BlockId = u8;
 
+/// The definitions of the blocks in the registry follow the order of the variants
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[cfg_attr(feature = "debug", derive(bevy::reflect::Reflect))]
 pub enum BlockType {
@@ -17,58 +19,127 @@ pub enum BlockType {
     Stone,
     Grass,
     Wood,
-    Tree(Tree)
+    CopperOre,
+    IronOre,
+    SilverOre,
+    GoldOre,
+    Sand,
+    Snow,
+    Mud,
+    JungleGrass,
+    Tree(Tree),
+    Furniture(Furniture)
 }
 
 impl BlockType {
-    pub const fn id(&self) -> BlockId {
+    /// The blocks which have no data, in the order of the variants
+    pub(crate) const PLAIN: [BlockType; 12] = [
+        BlockType::Dirt, BlockType::Stone, BlockType::Grass, BlockType::Wood,
+        BlockType::CopperOre, BlockType::IronOre, BlockType::SilverOre, BlockType::GoldOre,
+        BlockType::Sand, BlockType::Snow, BlockType::Mud, BlockType::JungleGrass,
+    ];
+
+    /// Position of the variant in the enum
+    pub(crate) const fn variant_index(&self) -> usize {
         match self {
             BlockType::Dirt => 0,
             BlockType::Stone => 1,
             BlockType::Grass => 2,
-            BlockType::Tree(_) => 5,
-            BlockType::Wood => 30,
+            BlockType::Wood => 3,
+            BlockType::CopperOre => 4,
+            BlockType::IronOre => 5,
+            BlockType::SilverOre => 6,
+            BlockType::GoldOre => 7,
+            BlockType::Sand => 8,
+            BlockType::Snow => 9,
+            BlockType::Mud => 10,
+            BlockType::JungleGrass => 11,
+            BlockType::Tree(_) => 12,
+            BlockType::Furniture(_) => 13,
         }
     }
 
-    pub(crate) const fn is_solid(&self) -> bool {
-        !matches!(self, BlockType::Tree(_))
-    }
-
-    pub(crate) const fn dirt_mergeable(&self) -> bool {
+    /// Index of the definition of the block in the registry
+    #[inline(always)]
+    const fn definition_index(&self) -> usize {
         match self {
-            BlockType::Dirt | BlockType::Grass | BlockType::Tree(_) => false,
-            BlockType::Stone => true,
-            BlockType::Wood => true,
+            BlockType::Furniture(furniture) => furniture.furniture_type.definition_index(),
+            _ => self.variant_index()
         }
     }
 
-    pub(crate) const fn check_required_tool(&self, tool: ItemTool) -> bool {
-        match self {
-            BlockType::Tree(_) => {
-                matches!(tool, ItemTool::Axe(_))
-            },
-            _ => {
-                matches!(tool, ItemTool::Pickaxe(_))
-            }
-        }
+    /// The name of the definition of the block in the data file
+    #[inline]
+    pub fn name(&self) -> &'static str {
+        &self.definition().name
     }
 
-    pub(crate) const fn max_hp(&self) -> i32 {
-        match self {
-            BlockType::Dirt | BlockType::Grass => 50,
-            BlockType::Stone | BlockType::Wood => 100,
-            BlockType::Tree(_) => 500,
-        }
+    #[inline]
+    pub(crate) fn definition(&self) -> &'static BlockDefinition {
+        &block_definitions()[self.definition_index()]
+    }
+
+    #[inline]
+    pub fn id(&self) -> BlockId {
+        self.definition().id
     }
 
-    pub(crate) const fn dusty(&self) -> bool {
+    /// Trees and furniture are not listed because their type depends on the frame, so they can't be restored from the id alone.
+    pub(crate) fn from_id(id: BlockId) -> Option<BlockType> {
+        Self::PLAIN.into_iter().find(|block_type| block_type.id() == id)
+    }
+
+    pub(crate) const fn is_solid(&self) -> bool {
         match self {
-            BlockType::Dirt => true,
-            _ => false
+            BlockType::Tree(_) => false,
+            BlockType::Furniture(furniture) => furniture.furniture_type.is_solid(),
+            _ => true
         }
     }
 
+    /// Whether the block can be shaped with a hammer
+    #[inline(always)]
+    pub(crate) const fn is_shapeable(&self) -> bool {
+        !matches!(self, BlockType::Tree(_) | BlockType::Furniture(_))
+    }
+
+    /// Whether the block falls down when there is nothing under it
+    pub(crate) const fn is_affected_by_gravity(&self) -> bool {
+        matches!(self, BlockType::Sand)
+    }
+
+    #[inline]
+    pub(crate) fn merge_group(&self) -> Option<MergeGroup> {
+        self.definition().merges_with
+    }
+
+    #[inline]
+    pub(crate) fn check_required_tool(&self, tool: ItemTool) -> bool {
+        tool.tool_type == self.definition().tool
+    }
+
+    /// Whether the tool is strong enough to damage the block
+    #[inline]
+    pub(crate) fn can_be_damaged_by(&self, tool: ItemTool) -> bool {
+        tool.power() >= self.definition().min_tool_power
+    }
+
+    #[inline]
+    pub(crate) fn max_hp(&self) -> i32 {
+        self.definition().hardness
+    }
+
+    #[inline]
+    pub(crate) fn dusty(&self) -> bool {
+        self.definition().dusty
+    }
+
+    /// The item which is dropped when the block is broken
+    pub(crate) fn dropped_item(&self) -> Option<Item> {
+        // The dropped items are checked when the items are loaded
+        self.definition().drop.as_deref().map(|id| Item::from_id(id).unwrap())
+    }
+
     pub(crate) const fn cracks(&self) -> bool {
         match self {
             BlockType::Tree(tree) => match tree.frame_type {
@@ -81,18 +152,25 @@ impl BlockType {
                 TreeFrameType::TopBareJagged => true,
                 _ => false
             },
+            BlockType::Furniture(_) => false,
             _ => true
         }
     }
 
-    pub(crate) const fn color(&self) -> [u8; 3] {
-        match self {
-            BlockType::Dirt =>  [151, 107, 75],
-            BlockType::Stone => [128, 128, 128],
-            BlockType::Grass => [28, 216, 94],
-            BlockType::Wood => [170, 120, 84],
-            BlockType::Tree(_) => [151, 107, 75],
-        }
+    #[inline]
+    pub(crate) fn color(&self) -> [u8; 3] {
+        self.definition().color
+    }
+
+    #[inline]
+    pub(crate) fn glow(&self) -> Option<[f32; 3]> {
+        self.definition().glow
+    }
+
+    /// How much of the passing light the block absorbs, from 0 like air to 1 like a solid block
+    #[inline]
+    pub(crate) fn light_absorption(&self) -> f32 {
+        self.definition().absorption.unwrap_or(if self.is_solid() { 1. } else { 0. })
     }
 }
 
@@ -101,9 +179,158 @@ impl From<ItemBlock> for BlockType {
         match item {
             ItemBlock::Dirt => BlockType::Dirt,
             ItemBlock::Stone => BlockType::Stone,
-            ItemBlock::Wood => BlockType::Wood
+            ItemBlock::Wood => BlockType::Wood,
+            ItemBlock::CopperOre => BlockType::CopperOre,
+            ItemBlock::IronOre => BlockType::IronOre,
+            ItemBlock::SilverOre => BlockType::SilverOre,
+            ItemBlock::GoldOre => BlockType::GoldOre,
+            ItemBlock::Sand => BlockType::Sand,
+            ItemBlock::Snow => BlockType::Snow,
+            ItemBlock::Mud => BlockType::Mud,
+        }
+    }
+}
+
+/// A block which other blocks blend into, together with the grass which grows on it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub(crate) enum MergeGroup {
+    Dirt,
+    Mud,
+}
+
+impl MergeGroup {
+    pub(crate) const fn base(&self) -> BlockType {
+        match self {
+            MergeGroup::Dirt => BlockType::Dirt,
+            MergeGroup::Mud => BlockType::Mud,
+        }
+    }
+
+    pub(crate) const fn grass(&self) -> BlockType {
+        match self {
+            MergeGroup::Dirt => BlockType::Grass,
+            MergeGroup::Mud => BlockType::JungleGrass,
+        }
+    }
+
+    /// The sprites which blend into a block are picked as if the block was dirt.
+    /// Maps the blocks of the group to dirt and grass and the actual dirt and grass to an unrelated block.
+    fn as_dirt_neighbors(&self, neighbors: &Neighbors<BlockType>) -> Neighbors<BlockType> {
+        neighbors.map_ref(|&block_type| {
+            if block_type == self.base() {
+                BlockType::Dirt
+            } else if block_type == self.grass() {
+                BlockType::Grass
+            } else if matches!(block_type, BlockType::Dirt | BlockType::Grass) {
+                BlockType::Stone
+            } else {
+                block_type
+            }
+        })
+    }
+}
+
+/// The shape of a block which is changed by hitting it with a hammer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BlockShape {
+    #[default]
+    Full,
+    Half,
+    /// The solid part of the block is in its bottom left corner
+    SlopeBottomLeft,
+    /// The solid part of the block is in its bottom right corner
+    SlopeBottomRight,
+    /// The solid part of the block is in its top left corner
+    SlopeTopLeft,
+    /// The solid part of the block is in its top right corner
+    SlopeTopRight,
+}
+
+impl BlockShape {
+    pub(crate) const ALL: [BlockShape; 6] = [
+        BlockShape::Full,
+        BlockShape::Half,
+        BlockShape::SlopeBottomLeft,
+        BlockShape::SlopeBottomRight,
+        BlockShape::SlopeTopLeft,
+        BlockShape::SlopeTopRight,
+    ];
+
+    #[inline(always)]
+    pub(crate) const fn id(&self) -> u8 {
+        *self as u8
+    }
+
+    pub(crate) fn from_id(id: u8) -> Option<BlockShape> {
+        Self::ALL.get(id as usize).copied()
+    }
+
+    /// The shape the block takes when it's hit with a hammer
+    pub(crate) const fn next(&self) -> BlockShape {
+        match self {
+            BlockShape::Full => BlockShape::Half,
+            BlockShape::Half => BlockShape::SlopeBottomRight,
+            BlockShape::SlopeBottomRight => BlockShape::SlopeBottomLeft,
+            BlockShape::SlopeBottomLeft => BlockShape::SlopeTopRight,
+            BlockShape::SlopeTopRight => BlockShape::SlopeTopLeft,
+            BlockShape::SlopeTopLeft => BlockShape::Full,
+        }
+    }
+
+    /// The half brick flag and the slope of the tile in a Terraria world
+    pub(crate) const fn terraria_slope(&self) -> (bool, u8) {
+        match self {
+            BlockShape::Full => (false, 0),
+            BlockShape::Half => (true, 0),
+            BlockShape::SlopeBottomLeft => (false, 1),
+            BlockShape::SlopeBottomRight => (false, 2),
+            BlockShape::SlopeTopLeft => (false, 3),
+            BlockShape::SlopeTopRight => (false, 4),
+        }
+    }
+
+    pub(crate) const fn from_terraria_slope(half_brick: bool, slope: u8) -> Option<BlockShape> {
+        match (half_brick, slope) {
+            (false, 0) => Some(BlockShape::Full),
+            (true, _) => Some(BlockShape::Half),
+            (false, 1) => Some(BlockShape::SlopeBottomLeft),
+            (false, 2) => Some(BlockShape::SlopeBottomRight),
+            (false, 3) => Some(BlockShape::SlopeTopLeft),
+            (false, 4) => Some(BlockShape::SlopeTopRight),
+            _ => None
+        }
+    }
+
+    /// Whether the top side of the block is sloped, so it can be walked up
+    #[inline(always)]
+    pub(crate) const fn is_floor_slope(&self) -> bool {
+        matches!(self, BlockShape::SlopeBottomLeft | BlockShape::SlopeBottomRight)
+    }
+
+    /// Height of the top side of the block at `x`.
+    /// Both `x` and the height are fractions of the tile size measured from the bottom left corner of the block.
+    pub(crate) fn top_height(&self, x: f32) -> f32 {
+        match self {
+            BlockShape::Half => 0.5,
+            BlockShape::SlopeBottomLeft => 1. - x,
+            BlockShape::SlopeBottomRight => x,
+            BlockShape::Full | BlockShape::SlopeTopLeft | BlockShape::SlopeTopRight => 1.,
         }
     }
+
+    /// Column of the frame of the shape in the row of the block type in the shaped tiles texture
+    pub(crate) const fn texture_column(&self, variant: u32) -> Option<u32> {
+        let index = match self {
+            BlockShape::Full => return None,
+            BlockShape::Half => 0,
+            BlockShape::SlopeBottomLeft => 1,
+            BlockShape::SlopeBottomRight => 2,
+            BlockShape::SlopeTopLeft => 3,
+            BlockShape::SlopeTopRight => 4,
+        };
+
+        Some(index * 3 + variant)
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Component)]
@@ -111,13 +338,13 @@ pub struct Block {
     pub(crate) block_type: BlockType,
     pub(crate) hp: i32,
     pub(crate) variant: u32,
-    pub(crate) cracks_index: Option<u32>
+    pub(crate) cracks_index: Option<u32>,
+    pub(crate) shape: BlockShape,
 }
 
 impl From<BlockType> for Block {
     fn from(block_type: BlockType) -> Self {
-        let mut rng = thread_rng();
-        Block::new(block_type, rng.gen_range(0..3))
+        Block::with_random_variant(block_type, &mut thread_rng())
     }
 }
 
@@ -138,13 +365,20 @@ impl Block {
             block_type,
             variant,
             hp: block_type.max_hp(),
-            cracks_index: None
+            cracks_index: None,
+            shape: BlockShape::Full,
         }
     }
 
+    #[inline]
+    pub(crate) fn with_random_variant(block_type: BlockType, rng: &mut impl Rng) -> Block {
+        Block::new(block_type, rng.gen_range(0..3))
+    }
+
     pub(crate) const fn frame(&self) -> Option<TerrariaFrame> {
         match self.block_type {
             BlockType::Tree(tree) => Some(tree.terraria_frame(self.variant)),
+            BlockType::Furniture(furniture) => Some(furniture.terraria_frame()),
             _ => None
         }
     }
@@ -162,16 +396,28 @@ impl Block {
             return get_tree_sprite_index(neighbors, tree, block.variant).to_2d_index_from_block_type(block.block_type);
         }
 
-        let mut index = get_sprite_index_by_neighbors(neighbors, block.variant);
+        if let BlockType::Furniture(furniture) = block.block_type {
+            return furniture.texture_atlas_pos().to_2d_index(FURNITURE_TEXTURE_WIDTH);
+        }
 
-        if block.dirt_mergeable() {
-            if let Some(idx) = get_sprite_index_by_dirt_connections(neighbors, block.variant) {
-                index = idx;
-            }
+        // A shaped block looks the same regardless of its neighbors
+        if let Some(column) = block.shape.texture_column(block.variant) {
+            return (get_shaped_tile_start_index(block.block_type) + TextureAtlasPos::new(column, 0)).to_block_index();
         }
 
-        if block.block_type == BlockType::Grass {
-            if let Some(idx) = get_grass_sprite_index_by_dirt_connections(neighbors, block.variant) {
+        let mut index = get_sprite_index_by_neighbors(neighbors, block.variant);
+
+        if let Some(merge_group) = block.merge_group() {
+            let neighbors = merge_group.as_dirt_neighbors(neighbors);
+
+            // The grass of the group blends into its base block like grass into dirt
+            let merged_index = if block.block_type == merge_group.grass() {
+                get_grass_sprite_index_by_dirt_connections(&neighbors, block.variant)
+            } else {
+                get_sprite_index_by_dirt_connections(&neighbors, block.variant)
+            };
+
+            if let Some(idx) = merged_index {
                 index = idx;
             }
         }
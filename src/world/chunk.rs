@@ -18,6 +18,8 @@ pub(crate) enum ChunkType {
     Tree,
     TreeBranch,
     TreeTop,
+    Liquid,
+    Furniture,
 }
 
 impl From<BlockType> for ChunkType {
@@ -26,6 +28,7 @@ impl From<BlockType> for ChunkType {
             BlockType::Tree(Tree { frame_type: TreeFrameType::BranchLeftLeaves | TreeFrameType::BranchRightLeaves, .. }) => ChunkType::TreeBranch,
             BlockType::Tree(Tree { frame_type: TreeFrameType::TopLeaves, .. }) => ChunkType::TreeTop,
             BlockType::Tree(_) => ChunkType::Tree,
+            BlockType::Furniture(_) => ChunkType::Furniture,
             _ => ChunkType::Tile
         }       
     }
@@ -0,0 +1,273 @@
+use bevy::prelude::UVec2;
+use bevy_ecs_tilemap::tiles::TilePos;
+
+use crate::{common::TextureAtlasPos, items::ItemFurniture};
+
+use super::{TerrariaFrame, WorldData, block::{BlockId, BlockType}, registry::{block_definitions, FURNITURE_DEFINITIONS_START}};
+
+/// Number of tiles in a row of the furniture texture
+pub(crate) const FURNITURE_TEXTURE_WIDTH: u32 = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "debug", derive(bevy::reflect::Reflect))]
+pub enum FurnitureType {
+    Torch,
+    Workbench,
+    Chest,
+    DoorClosed,
+    DoorOpen,
+    Lantern,
+}
+
+/// What an object has to be attached to in order to be placed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Anchor {
+    /// Solid blocks under the whole object
+    Floor,
+    /// Solid blocks above the whole object
+    Ceiling,
+    /// Walls behind every tile of the object
+    Wall,
+    /// Solid blocks both under and above the whole object
+    FloorAndCeiling,
+}
+
+impl FurnitureType {
+    pub(crate) const ALL: [FurnitureType; 6] = [
+        FurnitureType::Torch,
+        FurnitureType::Workbench,
+        FurnitureType::Chest,
+        FurnitureType::DoorClosed,
+        FurnitureType::DoorOpen,
+        FurnitureType::Lantern,
+    ];
+
+    /// Index of the definition of the object in the block registry
+    pub(super) const fn definition_index(&self) -> usize {
+        FURNITURE_DEFINITIONS_START + *self as usize
+    }
+
+    #[inline]
+    pub(crate) fn id(&self) -> BlockId {
+        block_definitions()[self.definition_index()].id
+    }
+
+    pub(crate) fn from_id(id: BlockId) -> Option<FurnitureType> {
+        Self::ALL.into_iter().find(|furniture_type| furniture_type.id() == id)
+    }
+
+    /// Size of the object in tiles
+    pub(crate) const fn size(&self) -> UVec2 {
+        match self {
+            FurnitureType::Torch => UVec2::new(1, 1),
+            FurnitureType::Workbench => UVec2::new(2, 1),
+            FurnitureType::Chest => UVec2::new(2, 2),
+            FurnitureType::DoorClosed => UVec2::new(1, 3),
+            FurnitureType::DoorOpen => UVec2::new(2, 3),
+            FurnitureType::Lantern => UVec2::new(1, 2),
+        }
+    }
+
+    /// The object can be placed if any of the anchors is satisfied
+    pub(crate) const fn anchors(&self) -> &'static [Anchor] {
+        match self {
+            FurnitureType::Torch => &[Anchor::Floor, Anchor::Wall],
+            FurnitureType::Workbench | FurnitureType::Chest => &[Anchor::Floor],
+            FurnitureType::DoorClosed | FurnitureType::DoorOpen => &[Anchor::FloorAndCeiling],
+            FurnitureType::Lantern => &[Anchor::Ceiling],
+        }
+    }
+
+    /// Whether the object hangs from the ceiling, so it is placed with its top tile at the cursor
+    const fn is_hanging(&self) -> bool {
+        matches!(self.anchors(), [Anchor::Ceiling])
+    }
+
+    /// The style of an object which is attached to the anchor
+    const fn style_for_anchor(&self, anchor: Anchor) -> u8 {
+        match (self, anchor) {
+            (FurnitureType::Torch, Anchor::Wall) => 1,
+            _ => 0
+        }
+    }
+
+    pub(crate) const fn is_solid(&self) -> bool {
+        matches!(self, FurnitureType::DoorClosed)
+    }
+}
+
+impl From<ItemFurniture> for FurnitureType {
+    fn from(item: ItemFurniture) -> Self {
+        match item {
+            ItemFurniture::Torch => FurnitureType::Torch,
+            ItemFurniture::Workbench => FurnitureType::Workbench,
+            ItemFurniture::Chest => FurnitureType::Chest,
+            ItemFurniture::Door => FurnitureType::DoorClosed,
+            ItemFurniture::Lantern => FurnitureType::Lantern,
+        }
+    }
+}
+
+/// A single tile of an object which takes one or more tiles
+#[derive(Debug, Clone, Copy, Eq)]
+#[cfg_attr(feature = "debug", derive(bevy::reflect::Reflect))]
+pub struct Furniture {
+    pub(crate) furniture_type: FurnitureType,
+    /// Column of the tile inside of the object, starting from the left
+    pub(crate) x: u8,
+    /// Row of the tile inside of the object, starting from the top
+    pub(crate) y: u8,
+    /// The look of the whole object.
+    /// A torch on the floor has the style 0 and on a wall has the style 1.
+    /// An open door which opens to the right has the style 0 and to the left has the style 1.
+    pub(crate) style: u8,
+}
+
+impl Furniture {
+    pub(crate) const fn new(furniture_type: FurnitureType, x: u8, y: u8, style: u8) -> Self {
+        Self { furniture_type, x, y, style }
+    }
+
+    /// Returns the position of the top left tile of the object
+    pub(crate) fn origin(&self, tile_pos: TilePos) -> TilePos {
+        TilePos::new(tile_pos.x - self.x as u32, tile_pos.y - self.y as u32)
+    }
+
+    /// Returns all tiles of an object which has its top left tile at `origin`
+    pub(crate) fn tiles(furniture_type: FurnitureType, style: u8, origin: TilePos) -> impl Iterator<Item = (TilePos, Furniture)> {
+        let size = furniture_type.size();
+
+        (0..size.y).flat_map(move |y| {
+            (0..size.x).map(move |x| {
+                (
+                    TilePos::new(origin.x + x, origin.y + y),
+                    Furniture::new(furniture_type, x as u8, y as u8, style)
+                )
+            })
+        })
+    }
+
+    /// The anchor the object is attached to
+    pub(crate) const fn anchor(&self) -> Anchor {
+        match (self.furniture_type, self.style) {
+            (FurnitureType::Torch, 1) => Anchor::Wall,
+            (FurnitureType::Torch | FurnitureType::Workbench | FurnitureType::Chest, _) => Anchor::Floor,
+            (FurnitureType::DoorClosed | FurnitureType::DoorOpen, _) => Anchor::FloorAndCeiling,
+            (FurnitureType::Lantern, _) => Anchor::Ceiling,
+        }
+    }
+
+    pub(crate) const fn terraria_frame(&self) -> TerrariaFrame {
+        let x = self.x as u16 * 18;
+        let y = self.y as u16 * 18;
+
+        match self.furniture_type {
+            FurnitureType::Torch => TerrariaFrame::new(if self.style == 0 { 0 } else { 66 }, 0),
+            FurnitureType::Workbench | FurnitureType::Chest | FurnitureType::DoorClosed | FurnitureType::Lantern => TerrariaFrame::new(x, y),
+            FurnitureType::DoorOpen => TerrariaFrame::new(self.style as u16 * 36 + x, y),
+        }
+    }
+
+    /// Returns the tile of the object which is written as the given frame
+    pub(crate) fn from_terraria_frame(furniture_type: FurnitureType, frame: TerrariaFrame) -> Option<Furniture> {
+        let size = furniture_type.size();
+
+        let x = (frame.x / 18) as u32;
+        let y = (frame.y / 18) as u32;
+
+        match furniture_type {
+            // A torch attached to a block on its side is loaded as a torch on a wall
+            FurnitureType::Torch => match frame {
+                TerrariaFrame { x: 0, y: 0 } => Some(Furniture::new(furniture_type, 0, 0, 0)),
+                TerrariaFrame { x: 22 | 44 | 66, y: 0 } => Some(Furniture::new(furniture_type, 0, 0, 1)),
+                _ => None
+            },
+            // Only the first style of the objects is supported
+            FurnitureType::Workbench | FurnitureType::Chest | FurnitureType::DoorClosed | FurnitureType::Lantern => {
+                (x < size.x && y < size.y).then(|| Furniture::new(furniture_type, x as u8, y as u8, 0))
+            },
+            FurnitureType::DoorOpen => {
+                let style = x / size.x;
+                (style < 2 && y < size.y).then(|| Furniture::new(furniture_type, (x % size.x) as u8, y as u8, style as u8))
+            }
+        }
+    }
+
+    pub(crate) const fn texture_atlas_pos(&self) -> TextureAtlasPos {
+        let x = self.x as u32;
+        let y = self.y as u32;
+        let style = self.style as u32;
+
+        match self.furniture_type {
+            FurnitureType::Torch => TextureAtlasPos::new(style, 0),
+            FurnitureType::Workbench => TextureAtlasPos::new(x, 1),
+            FurnitureType::Chest => TextureAtlasPos::new(x, 2 + y),
+            FurnitureType::DoorClosed => TextureAtlasPos::new(0, 4 + y),
+            FurnitureType::DoorOpen => TextureAtlasPos::new(1 + style * 2 + x, 4 + y),
+            FurnitureType::Lantern => TextureAtlasPos::new(2, y),
+        }
+    }
+}
+
+impl PartialEq for Furniture {
+    fn eq(&self, other: &Self) -> bool {
+        self.furniture_type == other.furniture_type
+    }
+}
+
+impl WorldData {
+    /// Returns the position of the top left tile and the style of an object placed with its bottom left tile at `tile_pos`
+    /// (top left tile for hanging objects), or `None` if the object doesn't fit there or has nothing to be attached to
+    pub(crate) fn furniture_placement(&self, furniture_type: FurnitureType, tile_pos: TilePos) -> Option<(TilePos, u8)> {
+        let size = furniture_type.size();
+        let origin = if furniture_type.is_hanging() {
+            tile_pos
+        } else {
+            TilePos::new(tile_pos.x, tile_pos.y.checked_sub(size.y - 1)?)
+        };
+
+        if (origin.x + size.x) as usize > self.width() || (origin.y + size.y) as usize > self.height() {
+            return None;
+        }
+
+        let is_empty = Furniture::tiles(furniture_type, 0, origin).all(|(pos, _)| !self.block_exists(pos));
+        if !is_empty { return None; }
+
+        let floor = (0..size.x).all(|x| self.solid_block_exists((origin.x + x, origin.y + size.y)));
+        let ceiling = origin.y > 0 && (0..size.x).all(|x| self.solid_block_exists((origin.x + x, origin.y - 1)));
+        let wall = Furniture::tiles(furniture_type, 0, origin).all(|(pos, _)| self.wall_exists(pos));
+
+        let anchor = furniture_type.anchors().iter().copied().find(|anchor| match anchor {
+            Anchor::Floor => floor,
+            Anchor::Ceiling => ceiling,
+            Anchor::Wall => wall,
+            Anchor::FloorAndCeiling => floor && ceiling,
+        })?;
+
+        Some((origin, furniture_type.style_for_anchor(anchor)))
+    }
+
+    /// Whether an object is attached to the block at the position
+    pub(crate) fn block_supports_furniture(&self, tile_pos: TilePos) -> bool {
+        let anchor_above = tile_pos.y.checked_sub(1)
+            .and_then(|y| self.get_furniture((tile_pos.x, y)))
+            .map(|furniture| furniture.anchor());
+
+        let anchor_below = self.get_furniture((tile_pos.x, tile_pos.y + 1)).map(|furniture| furniture.anchor());
+
+        matches!(anchor_above, Some(Anchor::Floor | Anchor::FloorAndCeiling))
+            || matches!(anchor_below, Some(Anchor::Ceiling | Anchor::FloorAndCeiling))
+    }
+
+    /// Whether an object is attached to the wall at the position
+    pub(crate) fn wall_supports_furniture(&self, tile_pos: TilePos) -> bool {
+        self.get_furniture((tile_pos.x, tile_pos.y)).is_some_and(|furniture| furniture.anchor() == Anchor::Wall)
+    }
+
+    fn get_furniture(&self, tile_pos: (u32, u32)) -> Option<Furniture> {
+        match self.get_block(tile_pos)?.block_type {
+            BlockType::Furniture(furniture) => Some(furniture),
+            _ => None
+        }
+    }
+}
@@ -1,7 +1,9 @@
 use std::collections::VecDeque;
+use std::ops::Range;
 
 use bevy::math::URect;
 use bevy::prelude::UVec2;
+use bevy::utils::HashMap;
 use bevy_ecs_tilemap::helpers::square_grid::neighbors::Neighbors;
 use bevy_ecs_tilemap::prelude::TilemapSize;
 use bevy_ecs_tilemap::tiles::TilePos;
@@ -15,12 +17,41 @@ use crate::common::math::map_range_f32;
 use crate::world::block::BlockType;
 
 use super::block::Block;
-use super::wall::WallType;
+use super::wall::{Wall, WallType};
 use super::tree::{TreeType, TreeFrameType};
-use super::{WorldSize, WorldData, Layer, BlockArray, WallArray, AsWorldPos};
+use super::biome::Biome;
+use super::liquid::{Liquid, LiquidType};
+use super::{WorldSize, WorldData, Layer, BlockArray, WallArray, LiquidArray, AsWorldPos};
 
 pub(crate) const DIRT_HILL_HEIGHT: usize = 75;
 
+/// The passes of the generator which use random numbers.
+/// Each one gets its own seed, so the passes aren't correlated with each other.
+#[derive(Clone, Copy)]
+enum Pass {
+    Biomes = 1,
+    Terrain,
+    Hills,
+    Walls,
+    BigCaves,
+    SmallCaves,
+    UndergroundDirt,
+    CavernDirt,
+    Grass,
+    RocksInDirt,
+    Ores,
+    CavernBorder,
+    ConvertBiomes,
+    Liquids,
+    Trees,
+}
+
+/// Mixes the pass into the world seed
+#[inline]
+fn pass_seed(seed: u32, pass: Pass) -> u32 {
+    seed ^ (pass as u32).wrapping_mul(0x9E37_79B9)
+}
+
 macro_rules! tree {
     ($tree_type: expr, $frame_type: expr) => {
         super::block::BlockType::Tree(super::tree::Tree::new($tree_type, $frame_type))
@@ -37,6 +68,7 @@ pub fn generate_world(seed: u32, world_size: WorldSize) -> WorldData {
 
     let blocks = BlockArray::default((area.height() as usize, area.width() as usize));
     let walls = WallArray::default((area.height() as usize, area.width() as usize));
+    let liquids = LiquidArray::default((area.height() as usize, area.width() as usize));
 
     let surface = (playable_area.min.y + playable_area.height() / 10) as usize;
     let underground = playable_area.min.y as usize + (playable_area.height() as f32 / 3.) as usize;
@@ -52,43 +84,233 @@ pub fn generate_world(seed: u32, world_size: WorldSize) -> WorldData {
     let mut world = WorldData {
         blocks, 
         walls,
+        liquids,
         area,
         playable_area,
         layer,
         spawn_point: TilePos::new(0, 0),
+        biomes: vec![Biome::Forest; area.width() as usize],
+        chests: HashMap::default(),
     };
 
-    spawn_terrain(&mut world);
+    generate_biomes(&mut world, pass_seed(seed, Pass::Biomes));
 
-    make_hills(&mut world, seed);   
+    spawn_terrain(&mut world, pass_seed(seed, Pass::Terrain));
 
-    generate_walls(&mut world);
+    make_hills(&mut world, pass_seed(seed, Pass::Hills));
+
+    generate_walls(&mut world, pass_seed(seed, Pass::Walls));
 
     extend_terrain(&mut world);
 
-    generate_big_caves(&mut world, seed);
-    generate_small_caves(&mut world, seed);
+    generate_big_caves(&mut world, pass_seed(seed, Pass::BigCaves));
+    generate_small_caves(&mut world, pass_seed(seed, Pass::SmallCaves));
 
     generate_dirt_in_rocks(&mut world, seed);
 
-    grassify(&mut world);
+    grassify(&mut world, pass_seed(seed, Pass::Grass));
+
+    generate_rocks_in_dirt(&mut world, pass_seed(seed, Pass::RocksInDirt));
+
+    generate_ores(&mut world, pass_seed(seed, Pass::Ores));
+
+    rough_cavern_layer_border(&mut world, pass_seed(seed, Pass::CavernBorder));
 
-    generate_rocks_in_dirt(&mut world, seed);
+    convert_biomes(&mut world, pass_seed(seed, Pass::ConvertBiomes));
 
-    rough_cavern_layer_border(&mut world, seed);
+    settle_falling_blocks(&mut world);
+
+    generate_liquids(&mut world, pass_seed(seed, Pass::Liquids));
 
     remove_walls_from_surface(&mut world);
 
-    grow_trees(&mut world, seed);
+    grow_trees(&mut world, pass_seed(seed, Pass::Trees));
 
     set_spawn_point(&mut world);
 
     world
 }
 
-fn spawn_terrain(world: &mut WorldData) {
+fn generate_biomes(world: &mut WorldData, seed: u32) {
+    println!("Generating biomes...");
+
+    let mut rng = StdRng::seed_from_u64(seed as u64);
+
+    let width = world.playable_width();
+    let center = world.width() / 2;
+
+    // Keep the spawn point in the forest and the biomes away from the world edges
+    let spawn_margin = width / 10;
+    let edge_margin = width / 20;
+
+    let left = (world.playable_area.min.x as usize + edge_margin)..(center - spawn_margin);
+    let right = (center + spawn_margin)..(world.playable_area.max.x as usize - edge_margin);
+
+    let (snow_side, jungle_side) = if rng.gen_bool(0.5) {
+        (left.clone(), right.clone())
+    } else {
+        (right.clone(), left.clone())
+    };
+
+    place_biome(world, &mut rng, Biome::Snow, snow_side, width / 12);
+    place_biome(world, &mut rng, Biome::Jungle, jungle_side, width / 8);
+
+    let desert_side = if rng.gen_bool(0.5) { left } else { right };
+
+    place_biome(world, &mut rng, Biome::Desert, desert_side, width / 16);
+}
+
+/// Places the biome at a random position in the range where it doesn't touch the other biomes.
+/// The biome is left out if there is no space for it.
+fn place_biome(world: &mut WorldData, rng: &mut StdRng, biome: Biome, range: Range<usize>, width: usize) {
+    const MIN_FOREST_BETWEEN: usize = 20;
+    const ATTEMPTS: usize = 10;
+
+    if range.len() < width { return; }
+
+    for _ in 0..ATTEMPTS {
+        let start = rng.gen_range(range.start..=range.end - width);
+        let end = start + width;
+
+        let is_free = world.biomes[start.saturating_sub(MIN_FOREST_BETWEEN)..(end + MIN_FOREST_BETWEEN).min(world.biomes.len())]
+            .iter()
+            .all(|b| *b == Biome::Forest);
+
+        if is_free {
+            world.biomes[start..end].fill(biome);
+            return;
+        }
+    }
+}
+
+fn convert_biomes(world: &mut WorldData, seed: u32) {
+    println!("Converting biomes...");
+
+    let mut rng = StdRng::seed_from_u64(seed as u64);
+
+    for (range, biome) in world.biome_ranges() {
+        if biome == Biome::Forest { continue; }
+
+        let depth = match biome {
+            Biome::Desert => world.layer.underground,
+            _ => world.layer.cavern
+        };
+
+        for block in world.blocks.slice_mut(s![..depth, range.clone()]).iter_mut().flatten() {
+            let block_type = match block.block_type {
+                BlockType::Dirt => biome.dirt_block(),
+                BlockType::Grass => biome.surface_block(),
+                _ => continue
+            };
+
+            *block = Block::with_random_variant(block_type, &mut rng);
+        }
+
+        for wall in world.walls.slice_mut(s![..depth, range]).iter_mut().flatten() {
+            if wall.wall_type == WallType::Dirt {
+                *wall = Wall::with_random_variant(biome.wall(), &mut rng);
+            }
+        }
+    }
+}
+
+/// Drops the blocks affected by gravity which ended up above the caves onto the ground below them.
+/// Otherwise they would stay in the air until one of their neighbors is updated.
+fn settle_falling_blocks(world: &mut WorldData) {
+    println!("Settling sand...");
+
+    for x in 0..world.width() {
+        // The lowest empty tile above the ground in the column
+        let mut free_y: Option<usize> = None;
+
+        for y in (0..world.height()).rev() {
+            match world.blocks[(y, x)] {
+                None => if free_y.is_none() { free_y = Some(y); },
+                Some(block) if block.block_type.is_affected_by_gravity() => {
+                    if let Some(target_y) = free_y {
+                        world.blocks[(target_y, x)] = Some(block);
+                        world.blocks[(y, x)] = None;
+                        free_y = Some(target_y - 1);
+                    }
+                },
+                Some(_) => free_y = None
+            }
+        }
+    }
+}
+
+fn generate_liquids(world: &mut WorldData, seed: u32) {
+    println!("Generating liquids...");
+
+    let mut rng = StdRng::seed_from_u64(seed as u64);
+
+    let playable_area_min_x = world.playable_area.min.x as usize;
+    let playable_area_max_x = world.playable_area.max.x as usize;
+    let playable_area_max_y = world.playable_area.max.y as usize;
+
+    // Lava fills the pools in the lower half of the cavern layer
+    let lava_level = world.layer.cavern + (playable_area_max_y - world.layer.cavern) / 2;
+
+    let attempts = world.playable_width() / 4;
+
+    for _ in 0..attempts {
+        let x = rng.gen_range(playable_area_min_x..playable_area_max_x);
+        let mut y = rng.gen_range(world.layer.underground..playable_area_max_y - 1);
+
+        if world.block_exists((x, y)) || world.get_liquid((x, y)).is_some() { continue; }
+
+        // Drop to the floor of the cave
+        while y + 1 < playable_area_max_y && !world.block_exists((x, y + 1)) {
+            y += 1;
+        }
+
+        if y + 1 >= playable_area_max_y { continue; }
+
+        let liquid_type = if y >= lava_level { LiquidType::Lava } else { LiquidType::Water };
+
+        fill_pool(world, x, y, liquid_type);
+    }
+}
+
+/// Fills the cave around the position with the liquid row by row, starting from the floor.
+/// Stops at the first row which is too wide or would leak into the tiles below it.
+fn fill_pool(world: &mut WorldData, x: usize, floor_y: usize, liquid_type: LiquidType) {
+    const MAX_WIDTH: usize = 40;
+    const MAX_DEPTH: usize = 12;
+
+    let min_x = world.playable_area.min.x as usize;
+    let max_x = world.playable_area.max.x as usize;
+
+    for y in (floor_y.saturating_sub(MAX_DEPTH - 1)..=floor_y).rev() {
+        if world.block_exists((x, y)) { return; }
+
+        let mut left = x;
+        while left > min_x && !world.block_exists((left - 1, y)) {
+            left -= 1;
+        }
+
+        let mut right = x;
+        while right + 1 < max_x && !world.block_exists((right + 1, y)) {
+            right += 1;
+        }
+
+        if left == min_x || right + 1 == max_x || right - left + 1 > MAX_WIDTH { return; }
+
+        let is_supported = (left..=right).all(|x| {
+            world.block_exists((x, y + 1)) || world.get_liquid((x, y + 1)).is_some()
+        });
+
+        if !is_supported { return; }
+
+        world.liquids.slice_mut(s![y, left..=right]).fill(Some(Liquid::full(liquid_type)));
+    }
+}
+
+fn spawn_terrain(world: &mut WorldData, seed: u32) {
     println!("Generating terrain...");
 
+    let mut rng = StdRng::seed_from_u64(seed as u64);
+
     let playable_area_min_x = world.playable_area.min.x as usize;
     let playable_area_max_x = world.playable_area.max.x as usize;
 
@@ -103,12 +325,10 @@ fn spawn_terrain(world: &mut WorldData) {
             *block = None;
         }
 
-        if y >= world.layer.underground - world.layer.dirt_height {
-            *block = Some(BlockType::Dirt.into());
-        }
-
         if y >= world.layer.underground {
-            *block = Some(BlockType::Stone.into());
+            *block = Some(Block::with_random_variant(BlockType::Stone, &mut rng));
+        } else if y >= world.layer.underground - world.layer.dirt_height {
+            *block = Some(Block::with_random_variant(BlockType::Dirt, &mut rng));
         }
     }
 }
@@ -138,7 +358,9 @@ fn make_hills(world: &mut WorldData, seed: u32) {
         let noise_value = fbm[x - playable_area_min_x] * gradient[x - playable_area_min_x];
 
         let hill_height = level - (noise_value * DIRT_HILL_HEIGHT as f32) as usize;
-        world.blocks.slice_mut(s![hill_height..level, x]).fill(Some(BlockType::Dirt.into()));
+        for block in world.blocks.slice_mut(s![hill_height..level, x]) {
+            *block = Some(Block::with_random_variant(BlockType::Dirt, &mut rng));
+        }
     }
 }
 
@@ -174,17 +396,21 @@ fn rough_cavern_layer_border(world: &mut WorldData, seed: u32) {
 
         let hill_height = level - (noise_value.abs() * ROUGHNESS) as usize;
         
+        let dirt = Some(Block::with_random_variant(BlockType::Dirt, &mut rng));
+
         if x != x_offset {
-            world.blocks.slice_mut(s![level..hill_height, x_range]).fill(Some(BlockType::Dirt.into()));
+            world.blocks.slice_mut(s![level..hill_height, x_range]).fill(dirt);
         } else {
-            world.blocks.slice_mut(s![level..hill_height, x]).fill(Some(BlockType::Dirt.into()));
+            world.blocks.slice_mut(s![level..hill_height, x]).fill(dirt);
         }
     }
 }
 
-fn generate_walls(world: &mut WorldData) {
+fn generate_walls(world: &mut WorldData, seed: u32) {
     println!("Generating walls...");
 
+    let mut rng = StdRng::seed_from_u64(seed as u64);
+
     let dirt_level = world.layer.underground - world.layer.dirt_height - DIRT_HILL_HEIGHT;
     let underground_level = world.layer.underground;
 
@@ -207,7 +433,7 @@ fn generate_walls(world: &mut WorldData) {
         if block_not_exists(dirt_level + y + 1, next_x) { continue; }
         if block_not_exists(dirt_level + y - 1, next_x) { continue; }
 
-        *wall = Some(WallType::Dirt.into());
+        *wall = Some(Wall::with_random_variant(WallType::Dirt, &mut rng));
     }
 }
 
@@ -216,8 +442,8 @@ fn generate_dirt_in_rocks(world: &mut WorldData, seed: u32) {
     let underground_level = world.layer.underground;
     let cavern_level = world.layer.cavern;
 
-    generate_dirt(world, seed, underground_level, cavern_level, 0.2, 0.4, 0.8);
-    generate_dirt(world, seed, cavern_level, world.height(), 0.3, 0.72, 0.72);
+    generate_dirt(world, pass_seed(seed, Pass::UndergroundDirt), underground_level, cavern_level, 0.2, 0.4, 0.8);
+    generate_dirt(world, pass_seed(seed, Pass::CavernDirt), cavern_level, world.height(), 0.3, 0.72, 0.72);
 }
 
 fn generate_dirt(world: &mut WorldData, seed: u32, from: usize, to: usize, freq: f32, min_prevalence: f32, max_prevalence: f32) {
@@ -231,6 +457,8 @@ fn generate_dirt(world: &mut WorldData, seed: u32, from: usize, to: usize, freq:
 
     let height = slice.nrows();
 
+    let mut rng = StdRng::seed_from_u64(seed as u64);
+
     let noise = NoiseBuilder::fbm_2d(slice.ncols(), height)
         .with_seed(seed as i32)
         .with_freq(freq)
@@ -246,7 +474,7 @@ fn generate_dirt(world: &mut WorldData, seed: u32, from: usize, to: usize, freq:
 
         if noise_value >= a {
             if let Some(BlockType::Stone) = block_type {
-                *block = Some(BlockType::Dirt.into());
+                *block = Some(Block::with_random_variant(BlockType::Dirt, &mut rng));
             }
         }
     }
@@ -258,6 +486,8 @@ fn generate_rocks_in_dirt(world: &mut WorldData, seed: u32) {
     let dirt_level = world.layer.underground - world.layer.dirt_height - DIRT_HILL_HEIGHT;
     let underground_level = world.layer.underground;
 
+    let mut rng = StdRng::seed_from_u64(seed as u64);
+
     let noise = NoiseBuilder::fbm_2d(world.playable_width(), underground_level - dirt_level)
         .with_seed(seed as i32)
         .with_freq(0.15)
@@ -279,12 +509,65 @@ fn generate_rocks_in_dirt(world: &mut WorldData, seed: u32) {
         if noise_value >= 0.5 {
             let block_type = block.map(|b| b.block_type);
             if matches!(block_type, Some(BlockType::Dirt | BlockType::Grass)) {
-                *block = Some(BlockType::Stone.into());
+                *block = Some(Block::with_random_variant(BlockType::Stone, &mut rng));
             }
         }
     }
 }
 
+fn generate_ores(world: &mut WorldData, seed: u32) {
+    println!("Generating ores...");
+
+    let dirt_level = world.layer.underground - world.layer.dirt_height;
+    let underground_level = world.layer.underground;
+    let cavern_level = world.layer.cavern;
+    let world_height = world.playable_area.max.y as usize;
+
+    // Deeper ores are rarer and start lower
+    generate_ore(world, seed, BlockType::CopperOre, dirt_level, world_height, 0.82, 0.76);
+    generate_ore(world, seed, BlockType::IronOre, dirt_level, world_height, 0.84, 0.78);
+    generate_ore(world, seed, BlockType::SilverOre, underground_level, world_height, 0.86, 0.8);
+    generate_ore(world, seed, BlockType::GoldOre, cavern_level, world_height, 0.88, 0.82);
+}
+
+/// Replaces dirt and stone with veins of the ore.
+/// The veins get more common from `max_threshold` at the top of the range to `min_threshold` at the bottom.
+fn generate_ore(world: &mut WorldData, seed: u32, ore: BlockType, from: usize, to: usize, max_threshold: f32, min_threshold: f32) {
+    let world_width = world.playable_width();
+
+    let playable_area_min_x = world.playable_area.min.x as usize;
+    let playable_area_max_x = world.playable_area.max.x as usize;
+
+    let mut slice = world.blocks
+        .slice_mut(s![from..to, playable_area_min_x..playable_area_max_x]);
+
+    let height = slice.nrows();
+
+    // Every ore gets its own noise so the veins don't overlap
+    let ore_seed = seed.wrapping_add(ore.id() as u32);
+
+    let mut rng = StdRng::seed_from_u64(ore_seed as u64);
+
+    let noise = NoiseBuilder::fbm_2d(slice.ncols(), height)
+        .with_seed(ore_seed as i32)
+        .with_freq(0.3)
+        .with_octaves(2)
+        .generate_scaled(0., 1.);
+
+    for ((y, x), block) in slice.indexed_iter_mut() {
+        let index = (y * world_width) + x;
+
+        let threshold = map_range_f32(0., height as f32, max_threshold, min_threshold, y as f32);
+
+        if noise[index] < threshold { continue; }
+
+        let block_type = block.map(|b| b.block_type);
+        if matches!(block_type, Some(BlockType::Stone | BlockType::Dirt)) {
+            *block = Some(Block::with_random_variant(ore, &mut rng));
+        }
+    }
+}
+
 fn generate_big_caves(world: &mut WorldData, seed: u32) {
     println!("Generating big caves...");
 
@@ -345,9 +628,11 @@ fn generate_small_caves(world: &mut WorldData, seed: u32) {
     }
 }
 
-fn grassify(world: &mut WorldData) {
+fn grassify(world: &mut WorldData, seed: u32) {
     println!("Growing grass...");
 
+    let mut rng = StdRng::seed_from_u64(seed as u64);
+
     fn is_valid(world: &mut WorldData, x: usize, y: usize) -> bool {
         if x >= world.width() { return false; }
         if y >= world.height() { return false; }
@@ -356,11 +641,11 @@ fn grassify(world: &mut WorldData) {
         any_neighbor_not_exist(world, x, y)
     }
     
-    fn flood_fill(world: &mut WorldData, x: usize, y: usize) {
+    fn flood_fill(world: &mut WorldData, rng: &mut StdRng, x: usize, y: usize) {
         let mut queue = VecDeque::new();
         queue.push_back((x, y));
 
-        world.set_block((x, y), BlockType::Grass);
+        world.set_block((x, y), Block::with_random_variant(BlockType::Grass, rng));
     
         while !queue.is_empty() {
             let (x, y) = queue.pop_back().unwrap();
@@ -369,49 +654,49 @@ fn grassify(world: &mut WorldData) {
 
             if is_valid(world, x + 1, y) {
                 let pos = (x + 1, y);
-                world.set_block(pos, BlockType::Grass);
+                world.set_block(pos, Block::with_random_variant(BlockType::Grass, rng));
                 queue.push_back(pos);
             }
     
             if is_valid(world, prev_x, y) {
                 let pos = (prev_x, y);
-                world.set_block(pos, BlockType::Grass);
+                world.set_block(pos, Block::with_random_variant(BlockType::Grass, rng));
                 queue.push_back(pos);
             }
     
             if is_valid(world, x, y + 1) {
                 let pos = (x, y + 1);
-                world.set_block(pos, BlockType::Grass);
+                world.set_block(pos, Block::with_random_variant(BlockType::Grass, rng));
                 queue.push_back(pos);
             }
     
             if is_valid(world, x, y - 1) {
                 let pos = (x, y - 1);
-                world.set_block(pos, BlockType::Grass);
+                world.set_block(pos, Block::with_random_variant(BlockType::Grass, rng));
                 queue.push_back(pos);
             }
 
             if is_valid(world, prev_x, y - 1) {
                 let pos = (prev_x, y - 1);
-                world.set_block(pos, BlockType::Grass);
+                world.set_block(pos, Block::with_random_variant(BlockType::Grass, rng));
                 queue.push_back(pos);
             }
 
             if is_valid(world, x + 1, y - 1) {
                 let pos = (x + 1, y - 1);
-                world.set_block(pos, BlockType::Grass);
+                world.set_block(pos, Block::with_random_variant(BlockType::Grass, rng));
                 queue.push_back(pos);
             }
 
             if is_valid(world, prev_x, y + 1) {
                 let pos = (prev_x, y + 1);
-                world.set_block(pos, BlockType::Grass);
+                world.set_block(pos, Block::with_random_variant(BlockType::Grass, rng));
                 queue.push_back(pos);
             }
 
             if is_valid(world, x + 1, y + 1) {
                 let pos = (x + 1, y + 1);
-                world.set_block(pos, BlockType::Grass);
+                world.set_block(pos, Block::with_random_variant(BlockType::Grass, rng));
                 queue.push_back(pos);
             }
         }
@@ -420,7 +705,7 @@ fn grassify(world: &mut WorldData) {
     for x in 0..world.width() {
         let y = get_surface_block_y(world, x);
         if world.block_exists_with_type((x, y), BlockType::Dirt) {
-            flood_fill(world, x, y);
+            flood_fill(world, &mut rng, x, y);
         }
     }
 
@@ -535,7 +820,7 @@ fn remove_walls_from_surface(world: &mut WorldData) {
     }
 }
 
-fn grow_tree(world: &mut WorldData, rng: &mut StdRng, root_pos: impl AsWorldPos) {
+fn grow_tree(world: &mut WorldData, rng: &mut StdRng, tree_type: TreeType, root_pos: impl AsWorldPos) {
     let height: usize = rng.gen_range(5..=16);
 
     let root_pos_x = root_pos.x();
@@ -578,7 +863,7 @@ fn grow_tree(world: &mut WorldData, rng: &mut StdRng, root_pos: impl AsWorldPos)
         variant = rng.gen_range(0..3);
         world.set_block(
             (root_pos_x - 1, root_pos_y),
-            Block::new(tree!(TreeType::Forest, TreeFrameType::BasePlainLeft), variant)
+            Block::new(tree!(tree_type, TreeFrameType::BasePlainLeft), variant)
         );
     }
 
@@ -586,7 +871,7 @@ fn grow_tree(world: &mut WorldData, rng: &mut StdRng, root_pos: impl AsWorldPos)
         variant = rng.gen_range(0..3);
         world.set_block(
             (root_pos_x + 1, root_pos_y),
-            Block::new(tree!(TreeType::Forest, TreeFrameType::BasePlainRight), variant)
+            Block::new(tree!(tree_type, TreeFrameType::BasePlainRight), variant)
         );
     }
 
@@ -603,14 +888,14 @@ fn grow_tree(world: &mut WorldData, rng: &mut StdRng, root_pos: impl AsWorldPos)
     variant = rng.gen_range(0..3);
     world.set_block(
         (root_pos_x, root_pos_y),
-        Block::new(tree!(TreeType::Forest, trunk), variant)
+        Block::new(tree!(tree_type, trunk), variant)
     );
 
     // Trunk
     variant = rng.gen_range(0..3);
     world.blocks
         .slice_mut(s![root_pos_y - height..root_pos_y, root_pos_x])
-        .fill(Block::new(tree!(TreeType::Forest, TreeFrameType::TrunkPlain), variant).into());
+        .fill(Block::new(tree!(tree_type, TreeFrameType::TrunkPlain), variant).into());
 
     // Branches
     
@@ -629,7 +914,7 @@ fn grow_tree(world: &mut WorldData, rng: &mut StdRng, root_pos: impl AsWorldPos)
             variant = rng.gen_range(0..3);
             world.set_block(
                 (root_pos_x - 1, y),
-                Block::new(tree!(TreeType::Forest, frame_type), variant)
+                Block::new(tree!(tree_type, frame_type), variant)
             );
         }
     }
@@ -649,7 +934,7 @@ fn grow_tree(world: &mut WorldData, rng: &mut StdRng, root_pos: impl AsWorldPos)
             variant = rng.gen_range(0..3);
             world.set_block(
                 (root_pos_x + 1, y),
-                Block::new(tree!(TreeType::Forest, frame_type), variant)
+                Block::new(tree!(tree_type, frame_type), variant)
             );
         }
     }
@@ -669,7 +954,7 @@ fn grow_tree(world: &mut WorldData, rng: &mut StdRng, root_pos: impl AsWorldPos)
     variant = rng.gen_range(0..3);
     world.set_block(
         (root_pos_x, root_pos_y - height - 1),
-        Block::new(tree!(TreeType::Forest, frame_type), variant)
+        Block::new(tree!(tree_type, frame_type), variant)
     );
 }
 
@@ -687,13 +972,16 @@ fn grow_trees(world: &mut WorldData, seed: u32) {
         let grow = rng.gen_bool(1. / 5.);
 
         if grow {
-            // Trees can only grow on dirt or grass
+            // Trees can only grow on soil
             let is_valid_block = world.get_block((x, y))
-                .filter(|b| matches!(b.block_type, BlockType::Dirt | BlockType::Grass))
+                .filter(|b| matches!(b.block_type, BlockType::Dirt | BlockType::Grass | BlockType::Snow | BlockType::Mud | BlockType::JungleGrass))
                 .is_some();
 
             if is_valid_block {
-                grow_tree(world, &mut rng, (x, y - 1))
+                // Deserts have no trees
+                if let Some(tree_type) = world.get_biome(x).tree_type() {
+                    grow_tree(world, &mut rng, tree_type, (x, y - 1))
+                }
             }
         }
     }
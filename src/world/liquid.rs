@@ -0,0 +1,318 @@
+use std::collections::VecDeque;
+
+use bevy::{math::URect, utils::HashSet};
+use bevy_ecs_tilemap::tiles::TilePos;
+
+use super::WorldData;
+
+pub(crate) type LiquidId = u8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "debug", derive(bevy::reflect::Reflect))]
+pub enum LiquidType {
+    Water,
+    Lava,
+}
+
+impl LiquidType {
+    pub(crate) const fn id(&self) -> LiquidId {
+        *self as LiquidId
+    }
+
+    pub(crate) fn from_id(id: LiquidId) -> Option<LiquidType> {
+        [LiquidType::Water, LiquidType::Lava]
+            .into_iter()
+            .find(|liquid_type| liquid_type.id() == id)
+    }
+
+    pub const fn color(&self) -> [u8; 3] {
+        match self {
+            LiquidType::Water => [9, 61, 191],
+            LiquidType::Lava => [253, 32, 3],
+        }
+    }
+
+    /// The light the liquid gives off, it's baked into the lighting like the glow of the blocks
+    pub(crate) const fn glow(&self) -> Option<[f32; 3]> {
+        match self {
+            LiquidType::Water => None,
+            LiquidType::Lava => Some([0.55, 0.33, 0.15]),
+        }
+    }
+
+    /// Lava is thick, so it moves only on some of the steps
+    const fn flow_interval(&self) -> u32 {
+        match self {
+            LiquidType::Water => 1,
+            LiquidType::Lava => 4,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "debug", derive(bevy::reflect::Reflect))]
+pub struct Liquid {
+    pub liquid_type: LiquidType,
+    pub amount: u8,
+}
+
+impl Liquid {
+    pub const MAX_AMOUNT: u8 = u8::MAX;
+
+    /// A liquid which can't spread anymore dries out when it gets below this amount
+    const MIN_AMOUNT: u8 = 2;
+
+    /// The number of tiles searched for room when a liquid is displaced by a block
+    const MAX_DISPLACE_TILES: usize = 64;
+
+    /// The number of sprites for the different amounts of liquid in a tile
+    pub(crate) const LEVELS: u32 = 8;
+
+    #[inline(always)]
+    pub(crate) const fn new(liquid_type: LiquidType, amount: u8) -> Self {
+        Self { liquid_type, amount }
+    }
+
+    #[inline(always)]
+    pub(crate) const fn full(liquid_type: LiquidType) -> Self {
+        Self::new(liquid_type, Self::MAX_AMOUNT)
+    }
+
+    /// A liquid which has more liquid right above it is drawn as a full tile
+    pub(crate) const fn texture_index(&self, liquid_above: bool) -> u32 {
+        let level = if liquid_above {
+            Self::LEVELS
+        } else {
+            let level = (self.amount as u32 * Self::LEVELS + Self::MAX_AMOUNT as u32 - 1) / Self::MAX_AMOUNT as u32;
+            if level == 0 { 1 } else { level }
+        };
+
+        self.liquid_type.id() as u32 * Self::LEVELS + level - 1
+    }
+}
+
+impl WorldData {
+    /// Whether the liquid of the given type can flow into the tile
+    fn can_flow_into(&self, x: usize, y: usize, liquid_type: LiquidType) -> bool {
+        if x >= self.width() || y >= self.height() { return false; }
+        if self.solid_block_exists((x, y)) { return false; }
+
+        match self.get_liquid((x, y)) {
+            Some(liquid) => liquid.liquid_type == liquid_type,
+            None => true
+        }
+    }
+
+    #[inline]
+    fn liquid_amount(&self, x: usize, y: usize) -> u8 {
+        self.get_liquid((x, y)).map(|l| l.amount).unwrap_or(0)
+    }
+
+    #[inline]
+    fn set_liquid_amount(&mut self, x: usize, y: usize, liquid_type: LiquidType, amount: u8) {
+        self.liquids[(y, x)] = (amount > 0).then_some(Liquid::new(liquid_type, amount));
+    }
+
+    /// Moves the liquid out of a tile which has been filled by a solid block and returns the positions of the tiles which have changed.
+    ///
+    /// The liquid is pushed into the closest tiles which still have room for it, preferring the ones above.
+    /// Whatever doesn't fit within [`Liquid::MAX_DISPLACE_TILES`] tiles is lost.
+    pub(crate) fn displace_liquid(&mut self, tile_pos: TilePos) -> HashSet<TilePos> {
+        let mut changed = HashSet::new();
+
+        let (x, y) = (tile_pos.x as usize, tile_pos.y as usize);
+
+        let Some(liquid) = self.get_liquid((x, y)).copied() else { return changed; };
+        let liquid_type = liquid.liquid_type;
+        let mut amount = liquid.amount;
+
+        self.remove_liquid((x, y));
+        changed.insert(tile_pos);
+
+        let mut visited = HashSet::new();
+        visited.insert((x, y));
+
+        let mut queue = VecDeque::from([(x, y)]);
+
+        while let Some((x, y)) = queue.pop_front() {
+            if amount == 0 || visited.len() > Liquid::MAX_DISPLACE_TILES { break; }
+
+            let up = y.checked_sub(1).map(|up| (x, up));
+            let left = x.checked_sub(1).map(|left| (left, y));
+            let right = Some((x + 1, y));
+            let down = Some((x, y + 1));
+
+            for (nx, ny) in [up, left, right, down].into_iter().flatten() {
+                if !self.can_flow_into(nx, ny, liquid_type) || !visited.insert((nx, ny)) { continue; }
+
+                let current = self.liquid_amount(nx, ny);
+                let flow = amount.min(Liquid::MAX_AMOUNT - current);
+
+                if flow > 0 {
+                    amount -= flow;
+                    self.set_liquid_amount(nx, ny, liquid_type, current + flow);
+                    changed.insert(TilePos::new(nx as u32, ny as u32));
+                }
+
+                queue.push_back((nx, ny));
+            }
+        }
+
+        changed
+    }
+
+    /// Runs one step of the liquid flow inside of the area and returns the positions of the tiles which have changed.
+    ///
+    /// The liquid first falls down and then evens out with its left and right neighbors.
+    /// Rows are processed from the bottom so a falling liquid moves by one tile per step.
+    /// The tiles which received liquid from the side are not processed again in the same step for the same reason.
+    pub(crate) fn simulate_liquids(&mut self, area: URect, step: u32) -> HashSet<TilePos> {
+        let mut changed = HashSet::new();
+        let mut moved = HashSet::new();
+
+        let min_x = area.min.x as usize;
+        let max_x = (area.max.x as usize).min(self.width());
+        let min_y = area.min.y as usize;
+        let max_y = (area.max.y as usize).min(self.height());
+
+        // Alternate the horizontal direction to avoid the liquid drifting to one side
+        let left_to_right = step % 2 == 0;
+
+        for y in (min_y..max_y).rev() {
+            for i in 0..max_x.saturating_sub(min_x) {
+                let x = if left_to_right { min_x + i } else { max_x - 1 - i };
+
+                if moved.contains(&(x, y)) { continue; }
+
+                let Some(liquid) = self.get_liquid((x, y)).copied() else { continue; };
+                let liquid_type = liquid.liquid_type;
+
+                if step % liquid_type.flow_interval() != 0 { continue; }
+
+                let mut amount = liquid.amount;
+
+                // Fall down
+                if self.can_flow_into(x, y + 1, liquid_type) {
+                    let below = self.liquid_amount(x, y + 1);
+                    let flow = amount.min(Liquid::MAX_AMOUNT - below);
+
+                    if flow > 0 {
+                        amount -= flow;
+
+                        self.set_liquid_amount(x, y + 1, liquid_type, below + flow);
+                        self.set_liquid_amount(x, y, liquid_type, amount);
+
+                        changed.insert(TilePos::new(x as u32, y as u32 + 1));
+                        changed.insert(TilePos::new(x as u32, y as u32));
+                    }
+                }
+
+                if amount == 0 { continue; }
+
+                // Spread to the sides
+                let left = x.checked_sub(1).filter(|&left| self.can_flow_into(left, y, liquid_type));
+                let right = Some(x + 1).filter(|&right| self.can_flow_into(right, y, liquid_type));
+
+                let mut total = amount as u32;
+                let mut count = 1;
+
+                for side in [left, right].into_iter().flatten() {
+                    total += self.liquid_amount(side, y) as u32;
+                    count += 1;
+                }
+
+                if count == 1 {
+                    continue;
+                }
+
+                let average = total / count;
+                let mut remainder = total % count;
+
+                // A thin layer of liquid which has nowhere to go dries out
+                if average < Liquid::MIN_AMOUNT as u32 && !self.can_flow_into(x, y + 1, liquid_type) {
+                    for side_x in [left, Some(x), right].into_iter().flatten() {
+                        if self.get_liquid((side_x, y)).is_some() {
+                            self.remove_liquid((side_x, y));
+                            changed.insert(TilePos::new(side_x as u32, y as u32));
+                        }
+                    }
+                    continue;
+                }
+
+                // The remainder is handed out one unit per tile, starting with the current one.
+                // There is a remainder only when the average is below the maximum, so no tile overflows.
+                for side_x in [Some(x), left, right].into_iter().flatten() {
+                    let extra = remainder.min(1);
+                    remainder -= extra;
+
+                    let new_amount = (average + extra).min(Liquid::MAX_AMOUNT as u32) as u8;
+
+                    if self.liquid_amount(side_x, y) != new_amount {
+                        self.set_liquid_amount(side_x, y, liquid_type, new_amount);
+                        changed.insert(TilePos::new(side_x as u32, y as u32));
+
+                        if side_x != x {
+                            moved.insert((side_x, y));
+                        }
+                    }
+                }
+            }
+        }
+
+        changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{math::UVec2, utils::HashMap};
+
+    use crate::{test_utils::load_registries, world::{Layer, BlockArray, WallArray, LiquidArray, biome::Biome, block::BlockType}};
+
+    use super::*;
+
+    fn world(width: u32, height: u32) -> WorldData {
+        let area = URect::from_corners(UVec2::ZERO, UVec2::new(width, height));
+        let shape = (height as usize, width as usize);
+
+        WorldData {
+            area,
+            layer: Layer { surface: 0, underground: 0, cavern: 0, dirt_height: 0 },
+            playable_area: area,
+            spawn_point: TilePos::new(0, 0),
+            blocks: BlockArray::default(shape),
+            walls: WallArray::default(shape),
+            liquids: LiquidArray::default(shape),
+            biomes: vec![Biome::Forest; width as usize],
+            chests: HashMap::default(),
+        }
+    }
+
+    fn total_amount(world: &WorldData) -> u32 {
+        world.liquids.iter().flatten().map(|liquid| liquid.amount as u32).sum()
+    }
+
+    #[test]
+    fn spreading_a_nearly_full_row_keeps_the_liquid() {
+        load_registries();
+
+        // A pool of three tiles between two blocks, the middle one evens out with both full sides
+        let mut world = world(5, 2);
+
+        for x in 0..5_usize {
+            world.set_block((x, 1), BlockType::Stone);
+        }
+        world.set_block((0_usize, 0_usize), BlockType::Stone);
+        world.set_block((4_usize, 0_usize), BlockType::Stone);
+
+        for (x, amount) in [(1, Liquid::MAX_AMOUNT), (2, Liquid::MAX_AMOUNT - 1), (3, Liquid::MAX_AMOUNT)] {
+            world.liquids[(0, x)] = Some(Liquid::new(LiquidType::Water, amount));
+        }
+
+        let area = world.area;
+        world.simulate_liquids(area, 0);
+
+        assert_eq!(total_amount(&world), 3 * Liquid::MAX_AMOUNT as u32 - 1);
+        assert!((1..4).all(|x: usize| world.get_liquid((x, 0)).is_some()));
+    }
+}
@@ -0,0 +1 @@
+pub mod terraria_world;
\ No newline at end of file
@@ -0,0 +1,599 @@
+use std::{io::{BufReader, Read, Error, ErrorKind}, fs::File, path::Path, collections::BTreeMap};
+
+use bevy::{math::URect, prelude::UVec2, utils::HashMap};
+use bevy_ecs_tilemap::tiles::TilePos;
+use rand::{rngs::StdRng, SeedableRng, Rng};
+
+use crate::{items::{ChestContents, Item, ItemStack, SlotContainer, CHEST_SLOT_COUNT}, world::{
+    WorldData, Layer, BlockArray, WallArray, LiquidArray, TerrariaFrame,
+    block::{Block, BlockType, BlockId, BlockShape},
+    wall::{Wall, WallType, WallId},
+    tree::{Tree, TreeType, TreeFrameType},
+    biome::Biome,
+    liquid::{Liquid, LiquidType},
+    furniture::{Furniture, FurnitureType}
+}};
+
+const SUPPORTED_VERSION: i32 = 71;
+
+/// The number of chest and sign slots in the world file
+const MAX_CHESTS: usize = 1000;
+const MAX_SIGNS: usize = 1000;
+
+/// The number of items in a Terraria chest
+const TERRARIA_CHEST_SLOT_COUNT: usize = 40;
+
+// Tiles which store their frame in the world file (Main.tileFrameImportant in Terraria 1.2)
+const FRAME_IMPORTANT_TILES: &[u8] = &[
+    3, 4, 5, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 24, 26, 27, 28, 29,
+    31, 33, 34, 35, 36, 42, 50, 55, 61, 71, 72, 73, 74, 77, 78, 79, 81, 82, 83, 84,
+    85, 86, 87, 88, 89, 90, 91, 92, 93, 94, 95, 96, 97, 98, 99, 100, 101, 102, 103, 104,
+    105, 106, 110, 113, 114, 125, 126, 128, 129, 132, 133, 134, 135, 136, 137, 138, 139, 141, 142, 143,
+    144, 149, 165, 171, 172, 173, 174, 178, 184, 185, 186, 187, 201, 207, 209, 210, 212, 215, 216, 217,
+    218, 219, 220, 227, 228, 231, 233, 235, 236, 237, 238, 239, 240, 241, 242, 243, 244, 245, 246, 247,
+];
+
+/// Parts of a Terraria world which have no counterpart in the game and were left out
+#[derive(Default)]
+pub struct SkippedTiles {
+    pub blocks: BTreeMap<BlockId, usize>,
+    pub walls: BTreeMap<WallId, usize>,
+    pub tree_frames: usize,
+    pub furniture_frames: usize,
+    pub honey: usize,
+    /// Chests which aren't placed on a chest tile of the loaded world
+    pub chests: usize,
+    /// Chest items by their Terraria net id
+    pub chest_items: BTreeMap<i32, usize>,
+    pub signs: usize,
+    pub npcs: usize,
+}
+
+impl SkippedTiles {
+    pub fn is_empty(&self) -> bool {
+        self.blocks.is_empty() && self.walls.is_empty() && self.tree_frames == 0 && self.furniture_frames == 0 && self.honey == 0
+            && self.chests == 0 && self.chest_items.is_empty() && self.signs == 0 && self.npcs == 0
+    }
+}
+
+struct TerrariaWorldHeader {
+    name: String,
+    id: i32,
+    width: u32,
+    height: u32,
+    spawn_point: TilePos,
+    ground_level: f64,
+    rock_level: f64,
+}
+
+impl WorldData {
+    pub fn load_terraria_world(path: impl AsRef<Path>) -> std::io::Result<(WorldData, SkippedTiles)> {
+        println!("Loading Terraria world...");
+
+        let world_file = File::open(path)?;
+        let mut reader = BufReader::new(world_file);
+
+        let header = load_world_header(&mut reader)?;
+
+        let area = URect::from_corners(UVec2::ZERO, UVec2::new(header.width, header.height));
+        let playable_area = URect::from_corners(area.min + UVec2::new(8, 0), area.max - UVec2::new(8, 1));
+
+        let surface = (playable_area.min.y + playable_area.height() / 10) as usize;
+        let underground = header.ground_level as usize + 1;
+
+        let mut world = WorldData {
+            area,
+            layer: Layer {
+                surface,
+                underground,
+                cavern: header.rock_level as usize,
+                dirt_height: underground.saturating_sub(surface) / 4,
+            },
+            playable_area,
+            spawn_point: header.spawn_point,
+            blocks: BlockArray::default((header.height as usize, header.width as usize)),
+            walls: WallArray::default((header.height as usize, header.width as usize)),
+            liquids: LiquidArray::default((header.height as usize, header.width as usize)),
+            biomes: vec![Biome::Forest; header.width as usize],
+            chests: HashMap::default(),
+        };
+
+        // Terraria doesn't store the frames of the plain blocks, it picks a random variant when it frames them on load.
+        // The world id is used as the seed so loading the same world always gives the same variants.
+        let mut rng = StdRng::seed_from_u64(header.id as u64);
+
+        let mut skipped = world.load_world_tiles(&mut reader, &mut rng)?;
+
+        world.detect_biomes();
+
+        world.load_world_chests(&mut reader, &mut skipped)?;
+
+        skip_world_signs(&mut reader, &mut skipped)?;
+
+        skip_world_npcs(&mut reader, &mut skipped)?;
+
+        // NPC names
+        for _ in 0..18 {
+            read_string(&mut reader)?;
+        }
+
+        // Validation
+        let valid = read_bool(&mut reader)?;
+        let validation_name = read_string(&mut reader)?;
+        read_i32(&mut reader)?;
+
+        if !valid || validation_name != header.name {
+            return Err(invalid_data("World file failed validation"));
+        }
+
+        Ok((world, skipped))
+    }
+
+    fn load_world_tiles<R: Read>(&mut self, reader: &mut R, rng: &mut impl Rng) -> std::io::Result<SkippedTiles> {
+        let mut skipped = SkippedTiles::default();
+
+        for x in 0..self.width() {
+            let mut y = 0;
+
+            while y < self.height() {
+                let (block, wall, liquid) = load_tile(reader, &mut skipped)?;
+
+                // Number of the following tiles in the column which are the same as this one
+                let copies = read_i16(reader)?.max(0) as usize;
+
+                for _ in 0..=copies {
+                    if y >= self.height() {
+                        return Err(invalid_data("Tile column overflows the world height"));
+                    }
+
+                    self.blocks[(y, x)] = block.map(|block| match block.block_type {
+                        BlockType::Tree(_) | BlockType::Furniture(_) => block,
+                        _ => Block { variant: rng.gen_range(0..3), ..block }
+                    });
+                    self.walls[(y, x)] = wall;
+                    self.liquids[(y, x)] = liquid;
+
+                    y += 1;
+                }
+            }
+        }
+
+        Ok(skipped)
+    }
+
+    /// Fills the chests which are placed in the world with the items Terraria knows the net id of
+    fn load_world_chests<R: Read>(&mut self, reader: &mut R, skipped: &mut SkippedTiles) -> std::io::Result<()> {
+        for _ in 0..MAX_CHESTS {
+            if !read_bool(reader)? { continue; }
+
+            // The position of the top left tile
+            let x = read_i32(reader)?;
+            let y = read_i32(reader)?;
+
+            let mut chest = ChestContents::default();
+
+            for index in 0..TERRARIA_CHEST_SLOT_COUNT {
+                let stack = read_i16(reader)?;
+                if stack <= 0 { continue; }
+
+                let net_id = read_i32(reader)?;
+
+                // Prefix
+                skip(reader, 1)?;
+
+                match Item::from_terraria_id(net_id).filter(|_| index < CHEST_SLOT_COUNT) {
+                    Some(item) => {
+                        let stack = (stack as u16).min(item.max_stack());
+                        chest.set_slot(index, Some(ItemStack::new(item).with_stack(stack)));
+                    },
+                    None => *skipped.chest_items.entry(net_id).or_default() += 1
+                }
+            }
+
+            let is_chest = self.get_block((x.max(0) as usize, y.max(0) as usize)).is_some_and(|block| matches!(
+                block.block_type,
+                BlockType::Furniture(Furniture { furniture_type: FurnitureType::Chest, x: 0, y: 0, .. })
+            ));
+
+            if !is_chest {
+                skipped.chests += 1;
+                continue;
+            }
+
+            if !chest.is_empty() {
+                self.chests.insert(TilePos::new(x as u32, y as u32), chest);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Terraria doesn't store biomes, so they are guessed from the topmost block of each column.
+    /// Trees take the type of the biome they grow in.
+    fn detect_biomes(&mut self) {
+        for x in 0..self.width() {
+            let biome = self.blocks.column(x)
+                .iter()
+                .flatten()
+                .filter(|block| block.is_solid())
+                .find_map(|block| Biome::from_block(block.block_type))
+                .unwrap_or_default();
+
+            self.biomes[x] = biome;
+
+            let tree_type = biome.tree_type().unwrap_or(TreeType::Forest);
+
+            for block in self.blocks.column_mut(x).iter_mut().flatten() {
+                if let BlockType::Tree(tree) = &mut block.block_type {
+                    tree.tree_type = tree_type;
+                }
+            }
+        }
+    }
+}
+
+fn skip_world_signs<R: Read>(reader: &mut R, skipped: &mut SkippedTiles) -> std::io::Result<()> {
+    for _ in 0..MAX_SIGNS {
+        if !read_bool(reader)? { continue; }
+
+        // Text, x and y
+        read_string(reader)?;
+        skip(reader, 2 * 4)?;
+
+        skipped.signs += 1;
+    }
+
+    Ok(())
+}
+
+fn skip_world_npcs<R: Read>(reader: &mut R, skipped: &mut SkippedTiles) -> std::io::Result<()> {
+    // Every NPC is preceded by a flag telling whether there is one more
+    while read_bool(reader)? {
+        // Name
+        read_string(reader)?;
+
+        // Position, homeless, home tile x and y
+        skip(reader, 2 * 4 + 1 + 2 * 4)?;
+
+        skipped.npcs += 1;
+    }
+
+    Ok(())
+}
+
+fn load_world_header<R: Read>(reader: &mut R) -> std::io::Result<TerrariaWorldHeader> {
+    let version = read_i32(reader)?;
+
+    if version != SUPPORTED_VERSION {
+        return Err(invalid_data(format!("Unsupported Terraria world version {}", version)));
+    }
+
+    let name = read_string(reader)?;
+
+    let id = read_i32(reader)?;
+
+    // Left, right, top and bottom world borders
+    skip(reader, 4 * 4)?;
+
+    let height = read_i32(reader)?;
+    let width = read_i32(reader)?;
+
+    if width <= 16 || height <= 1 {
+        return Err(invalid_data(format!("Invalid world size {}x{}", width, height)));
+    }
+
+    // Moon type
+    skip(reader, 1)?;
+
+    // Tree x, tree style, caveback x, caveback style
+    skip(reader, (3 + 4 + 3 + 4) * 4)?;
+
+    // Iceback, jungleback and hellback styles
+    skip(reader, 3 * 4)?;
+
+    let spawn_point = TilePos::new(read_i32(reader)? as u32, read_i32(reader)? as u32);
+
+    let ground_level = read_f64(reader)?;
+    let rock_level = read_f64(reader)?;
+
+    // Time, day time, moon phase, blood moon, eclipse
+    skip(reader, 8 + 1 + 4 + 1 + 1)?;
+
+    // Dungeon x and y
+    skip(reader, 2 * 4)?;
+
+    // Downed bosses, saved NPCs, downed invasions, shadow orb smashed, spawn meteor
+    skip(reader, 20)?;
+
+    // Shadow orb count, altar count, hard mode
+    skip(reader, 1 + 4 + 1)?;
+
+    // Invasion delay, size, type and x
+    skip(reader, 4 * 4)?;
+
+    // Is raining, rain time, max rain
+    skip(reader, 1 + 4 + 8)?;
+
+    // Ore tiers
+    skip(reader, 3 * 4)?;
+
+    // Tree, corrupt, jungle, snow, hallow, crimson, desert and ocean backgrounds
+    skip(reader, 8)?;
+
+    // Cloud BG active, number of clouds, wind speed
+    skip(reader, 4 + 2 + 4)?;
+
+    Ok(TerrariaWorldHeader {
+        name,
+        id,
+        width: width as u32,
+        height: height as u32,
+        spawn_point,
+        ground_level,
+        rock_level,
+    })
+}
+
+fn load_tile<R: Read>(reader: &mut R, skipped: &mut SkippedTiles) -> std::io::Result<(Option<Block>, Option<Wall>, Option<Liquid>)> {
+    let mut block = None;
+    let mut wall = None;
+    let mut liquid = None;
+
+    // Is active
+    if read_bool(reader)? {
+        let id = read_u8(reader)?;
+
+        let frame = if FRAME_IMPORTANT_TILES.contains(&id) {
+            Some(TerrariaFrame::new(read_u16(reader)?, read_u16(reader)?))
+        } else {
+            None
+        };
+
+        // Color
+        if read_bool(reader)? {
+            skip(reader, 1)?;
+        }
+
+        let tree_id = BlockType::Tree(Tree::new(TreeType::Forest, TreeFrameType::TrunkPlain)).id();
+
+        block = match (id, frame, FurnitureType::from_id(id)) {
+            (id, Some(frame), _) if id == tree_id => match TreeFrameType::from_terraria_frame(TreeType::Forest, frame) {
+                Some((frame_type, variant)) => Some(Block::new(BlockType::Tree(Tree::new(TreeType::Forest, frame_type)), variant)),
+                None => {
+                    skipped.tree_frames += 1;
+                    None
+                }
+            },
+            (_, Some(frame), Some(furniture_type)) => match Furniture::from_terraria_frame(furniture_type, frame) {
+                Some(furniture) => Some(Block::new(BlockType::Furniture(furniture), 0)),
+                None => {
+                    skipped.furniture_frames += 1;
+                    None
+                }
+            },
+            _ => match BlockType::from_id(id) {
+                Some(block_type) => Some(Block::new(block_type, 0)),
+                None => {
+                    *skipped.blocks.entry(id).or_default() += 1;
+                    None
+                }
+            }
+        };
+    }
+
+    // Is wall
+    if read_bool(reader)? {
+        let id = read_u8(reader)?;
+
+        // Color
+        if read_bool(reader)? {
+            skip(reader, 1)?;
+        }
+
+        wall = match WallType::from_id(id) {
+            Some(wall_type) => Some(Wall::new(wall_type, 0)),
+            None => {
+                *skipped.walls.entry(id).or_default() += 1;
+                None
+            }
+        };
+    }
+
+    // Is liquid
+    if read_bool(reader)? {
+        let amount = read_u8(reader)?;
+        let lava = read_bool(reader)?;
+        let honey = read_bool(reader)?;
+
+        if honey {
+            skipped.honey += 1;
+        } else if amount > 0 {
+            let liquid_type = if lava { LiquidType::Lava } else { LiquidType::Water };
+            liquid = Some(Liquid::new(liquid_type, amount));
+        }
+    }
+
+    // Wires (red, green, blue)
+    skip(reader, 3)?;
+
+    let half_brick = read_bool(reader)?;
+    let slope = read_u8(reader)?;
+
+    if let Some(block) = block.as_mut().filter(|block| block.block_type.is_shapeable()) {
+        block.shape = BlockShape::from_terraria_slope(half_brick, slope).unwrap_or_default();
+    }
+
+    // Actuator, inactive
+    skip(reader, 2)?;
+
+    Ok((block, wall, liquid))
+}
+
+fn invalid_data<E>(error: E) -> Error
+where
+    E: Into<Box<dyn std::error::Error + Send + Sync>>
+{
+    Error::new(ErrorKind::InvalidData, error)
+}
+
+fn skip<R: Read>(reader: &mut R, count: u64) -> std::io::Result<()> {
+    let skipped = std::io::copy(&mut reader.take(count), &mut std::io::sink())?;
+
+    if skipped != count {
+        return Err(Error::from(ErrorKind::UnexpectedEof));
+    }
+
+    Ok(())
+}
+
+fn read_string<R: Read>(reader: &mut R) -> std::io::Result<String> {
+    // The length is written as a 7-bit encoded integer
+    let mut length = 0usize;
+    let mut shift = 0;
+
+    loop {
+        let byte = read_u8(reader)?;
+        length |= ((byte & 0x7F) as usize) << shift;
+
+        if byte & 0x80 == 0 { break; }
+
+        shift += 7;
+
+        if shift > 28 {
+            return Err(invalid_data("Invalid string length"));
+        }
+    }
+
+    let mut bytes = vec![0u8; length];
+    reader.read_exact(&mut bytes)?;
+
+    String::from_utf8(bytes).map_err(invalid_data)
+}
+
+#[inline(always)]
+fn read_bool<R: Read>(reader: &mut R) -> std::io::Result<bool> {
+    read_u8(reader).map(|b| b != 0)
+}
+
+#[inline(always)]
+fn read_u8<R: Read>(reader: &mut R) -> std::io::Result<u8> {
+    let mut bytes = [0u8; 1];
+    reader.read_exact(&mut bytes)?;
+    Ok(bytes[0])
+}
+
+#[inline(always)]
+fn read_u16<R: Read>(reader: &mut R) -> std::io::Result<u16> {
+    let mut bytes = [0u8; 2];
+    reader.read_exact(&mut bytes)?;
+    Ok(u16::from_le_bytes(bytes))
+}
+
+#[inline(always)]
+fn read_i16<R: Read>(reader: &mut R) -> std::io::Result<i16> {
+    let mut bytes = [0u8; 2];
+    reader.read_exact(&mut bytes)?;
+    Ok(i16::from_le_bytes(bytes))
+}
+
+#[inline(always)]
+fn read_i32<R: Read>(reader: &mut R) -> std::io::Result<i32> {
+    let mut bytes = [0u8; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(i32::from_le_bytes(bytes))
+}
+
+#[inline(always)]
+fn read_f64<R: Read>(reader: &mut R) -> std::io::Result<f64> {
+    let mut bytes = [0u8; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(f64::from_le_bytes(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_ecs_tilemap::tiles::TilePos;
+
+    use crate::{
+        test_utils::load_registries,
+        items::SlotContainer,
+        world::{WorldData, WorldSize, generator::generate_world, block::BlockType, tree::{Tree, TreeType, TreeFrameType}}
+    };
+
+    /// What survives the export. Terraria doesn't store the variants of the plain blocks, the hp and the cracks,
+    /// the tree type is taken from the biome on load.
+    fn block_key(world: &WorldData, x: usize, y: usize) -> Option<(BlockType, Option<TreeFrameType>, u8)> {
+        world.blocks[(y, x)].map(|block| match block.block_type {
+            // Trees are equal when their types are, so the frame is compared separately
+            BlockType::Tree(tree) => (BlockType::Tree(Tree::new(TreeType::Forest, tree.frame_type)), Some(tree.frame_type), block.shape.id()),
+            block_type => (block_type, None, block.shape.id())
+        })
+    }
+
+    #[test]
+    fn generated_world_round_trip() {
+        load_registries();
+
+        let world = generate_world(7, WorldSize::Tiny);
+
+        let path = std::env::temp_dir().join(format!("terraria_round_trip_{}.wld", std::process::id()));
+        world.save_as_terraria_world(&path).unwrap();
+        let result = WorldData::load_terraria_world(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        let (loaded, skipped) = result.unwrap();
+
+        assert!(skipped.is_empty(), "a generated world has no unsupported tiles");
+
+        assert_eq!(world.area, loaded.area);
+        assert_eq!(world.spawn_point, loaded.spawn_point);
+        assert_eq!(world.layer.underground, loaded.layer.underground);
+        assert_eq!(world.layer.cavern, loaded.layer.cavern);
+
+        for y in 0..world.height() {
+            for x in 0..world.width() {
+                assert!(block_key(&world, x, y) == block_key(&loaded, x, y), "the blocks differ at ({}, {})", x, y);
+                assert!(
+                    world.walls[(y, x)].map(|wall| wall.wall_type) == loaded.walls[(y, x)].map(|wall| wall.wall_type),
+                    "the walls differ at ({}, {})", x, y
+                );
+                assert!(world.liquids[(y, x)] == loaded.liquids[(y, x)], "the liquids differ at ({}, {})", x, y);
+            }
+        }
+    }
+
+    /// A 40x30 version 71 world with a stone floor, a chest with a few items, a sign and the Guide.
+    /// The chest section also has a chest which isn't placed on a chest tile.
+    #[test]
+    fn loads_chests_and_skips_signs_and_npcs() {
+        load_registries();
+
+        let (world, skipped) = WorldData::load_terraria_world("tests/fixtures/terraria_v71_guide_and_chest.wld").unwrap();
+
+        assert_eq!(world.width(), 40);
+        assert_eq!(world.spawn_point, TilePos::new(20, 19));
+
+        let chest = world.chests.get(&TilePos::new(10, 18)).expect("the chest is imported");
+        let slot = |index: usize| chest.slots()[index].map(|item_stack| (item_stack.item.definition().id.as_str(), item_stack.stack));
+
+        assert_eq!(slot(0), Some(("Torch", 10)));
+        assert_eq!(slot(1), None, "the copper shortsword has no counterpart");
+        assert_eq!(slot(2), Some(("Wood", 20)));
+        assert_eq!(slot(5), Some(("DirtBlock", 999)));
+        assert_eq!(world.chests.len(), 1);
+
+        assert_eq!(skipped.chests, 1);
+        assert_eq!(skipped.chest_items.get(&-15), Some(&1));
+        assert_eq!(skipped.signs, 1);
+        assert_eq!(skipped.npcs, 1);
+        assert_eq!(skipped.blocks.get(&55), Some(&4), "the sign tiles are skipped");
+
+        // The variants of the plain blocks are picked on load
+        let variants = world.blocks.iter().flatten()
+            .filter(|block| block.block_type == BlockType::Stone)
+            .map(|block| block.variant);
+
+        assert!(variants.clone().all(|variant| variant < 3));
+        assert!(variants.clone().any(|variant| variant != 0));
+    }
+}
@@ -1,18 +1,29 @@
 pub mod block;
 pub mod tree;
 pub mod wall;
+pub mod biome;
+pub mod liquid;
+pub mod furniture;
+pub mod registry;
 pub(crate) mod chunk;
 pub mod generator;
-pub(crate) mod save_as;
+pub mod world_file;
+pub mod save_as;
+pub mod load_from;
 
-use bevy::{prelude::Resource, math::URect};
+use std::str::FromStr;
+
+use bevy::{prelude::Resource, math::URect, utils::HashMap};
 use bevy_ecs_tilemap::{tiles::TilePos, prelude::TilemapSize, helpers::square_grid::neighbors::{SquareDirection, Neighbors}};
 use ndarray::Array2;
 
-use self::{block::{Block, BlockType}, wall::Wall};
+use crate::items::ChestContents;
+
+use self::{block::{Block, BlockType}, wall::Wall, biome::Biome, liquid::Liquid};
 
 pub(crate) type BlockArray = Array2<Option<Block>>;
 pub(crate) type WallArray = Array2<Option<Wall>>;
+pub(crate) type LiquidArray = Array2<Option<Liquid>>;
 
 #[derive(Clone, Copy)]
 pub struct Layer {
@@ -45,6 +56,19 @@ impl WorldSize {
     }
 }
 
+impl FromStr for WorldSize {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "tiny" => Ok(WorldSize::Tiny),
+            "medium" => Ok(WorldSize::Medium),
+            "large" => Ok(WorldSize::Large),
+            _ => Err(format!("Unknown world size \"{}\", expected tiny, medium or large", s))
+        }
+    }
+}
+
 #[derive(Resource)]
 pub struct WorldData {
     pub area: URect,
@@ -53,6 +77,12 @@ pub struct WorldData {
     pub spawn_point: TilePos,
     pub blocks: Array2<Option<Block>>,
     pub walls: Array2<Option<Wall>>,
+    pub liquids: Array2<Option<Liquid>>,
+    /// The biome of every column of the world
+    pub biomes: Vec<Biome>,
+    /// The items stored in the chests by the position of their top left tile.
+    /// A chest which has never been opened has no entry.
+    pub(crate) chests: HashMap<TilePos, ChestContents>,
 }
 
 pub trait AsWorldPos {
@@ -120,6 +150,11 @@ impl WorldData {
         self.playable_area.height() as usize
     }
 
+    #[inline]
+    pub fn get_biome(&self, x: usize) -> Biome {
+        self.biomes.get(x).copied().unwrap_or_default()
+    }
+
     #[inline]
     pub fn get_block<Pos: AsWorldPos>(&self, world_pos: Pos) -> Option<&Block> {
         self.blocks.get(world_pos.yx()).and_then(|b| b.as_ref())
@@ -165,6 +200,23 @@ impl WorldData {
         self.walls.get_mut(world_pos.yx()).and_then(|w| w.as_mut())
     }
 
+    #[inline]
+    pub(crate) fn remove_liquid<Pos: AsWorldPos>(&mut self, world_pos: Pos) {
+        if let Some(liquid) = self.liquids.get_mut(world_pos.yx()) {
+            *liquid = None;
+        }
+    }
+
+    #[inline(always)]
+    pub fn get_liquid<Pos: AsWorldPos>(&self, world_pos: Pos) -> Option<&Liquid> {
+        self.liquids.get(world_pos.yx()).and_then(|l| l.as_ref())
+    }
+
+    #[inline]
+    pub fn get_liquid_color<Pos: AsWorldPos>(&self, world_pos: Pos) -> Option<[u8; 3]> {
+        self.get_liquid(world_pos).map(|l| l.liquid_type.color())
+    }
+
     pub(crate) fn set_block<Pos: AsWorldPos>(&mut self, world_pos: Pos, block: impl Into<Block>) {
         if let Some(b) = self.blocks.get_mut_ptr(world_pos.yx()) {
             unsafe {
@@ -223,7 +275,8 @@ impl WorldData {
 
         let get_block = move |pos: TilePos| -> Option<&Block> {
             if solid {
-                self.get_solid_block(pos)
+                // Blocks don't connect to objects, even to the solid ones
+                self.get_solid_block(pos).filter(|b| !matches!(b.block_type, BlockType::Furniture(_)))
             } else {
                 self.get_block(pos)
             }
@@ -258,7 +311,7 @@ impl WorldData {
     }
 }
 
-#[derive(Debug, Clone, Copy, Default)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub(crate) struct TerrariaFrame {
     pub(crate) x: u16,
     pub(crate) y: u16
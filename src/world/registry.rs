@@ -0,0 +1,273 @@
+use std::{io::BufReader, fs::File, error::Error, collections::HashMap, sync::OnceLock};
+
+use bevy::prelude::{UVec2, Vec3};
+use serde::{Deserialize, de::DeserializeOwned};
+
+use crate::{items::{Item, ItemBehaviour, ToolType}, lighting::types::LightSource, plugins::particles::Particle};
+
+use super::{block::{BlockId, BlockType, MergeGroup}, wall::{WallId, WallType}, furniture::{Furniture, FurnitureType}};
+
+static BLOCK_REGISTRY: OnceLock<Vec<BlockDefinition>> = OnceLock::new();
+static WALL_REGISTRY: OnceLock<Vec<WallDefinition>> = OnceLock::new();
+
+/// Index of the first furniture definition, the definitions of the plain blocks and the trees come before
+pub(super) const FURNITURE_DEFINITIONS_START: usize = BlockType::PLAIN.len() + 1;
+
+/// The sound which is played when a tile is hit or placed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub(crate) enum TileSound {
+    Dig,
+    Tink,
+}
+
+/// Light which is emitted by a tile
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub(crate) struct LightEmission {
+    pub(crate) color: [f32; 3],
+    pub(crate) intensity: f32,
+    #[serde(default)]
+    pub(crate) jitter_intensity: f32,
+}
+
+impl LightEmission {
+    pub(crate) fn light_source(&self) -> LightSource {
+        LightSource {
+            size: UVec2::splat(1),
+            color: Vec3::from_array(self.color),
+            intensity: self.intensity,
+            jitter_intensity: self.jitter_intensity,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub(crate) struct BlockDefinition {
+    /// The key of the definition in the data file
+    #[serde(skip)]
+    pub(crate) name: String,
+    /// The id of the tile in Terraria which is also used in the world files
+    pub(crate) id: BlockId,
+    /// The amount of damage the block takes before it breaks
+    pub(crate) hardness: i32,
+    /// The power a tool must have to damage the block
+    #[serde(default)]
+    pub(crate) min_tool_power: i32,
+    /// The color of the block on the world map
+    pub(crate) color: [u8; 3],
+    /// The tool which is used to break the block
+    pub(crate) tool: ToolType,
+    /// The block the sprite of the block blends into when they are next to each other
+    pub(crate) merges_with: Option<MergeGroup>,
+    /// The id of the item which is dropped when the block is broken
+    pub(crate) drop: Option<String>,
+    pub(crate) particle: Option<Particle>,
+    pub(crate) sound: TileSound,
+    /// Whether walking and landing on the block raises dust
+    #[serde(default)]
+    pub(crate) dusty: bool,
+    pub(crate) light: Option<LightEmission>,
+    /// The color the block lights itself with. Unlike `light` it is baked into the light map, so it is cheap for common blocks.
+    pub(crate) glow: Option<[f32; 3]>,
+    /// How much of the passing light the block absorbs, from 0 like air to 1 like a solid block.
+    /// Defaults to 1 for solid blocks and 0 for the others.
+    pub(crate) absorption: Option<f32>,
+}
+
+impl BlockDefinition {
+    fn validate(&self) -> Result<(), String> {
+        if self.hardness <= 0 {
+            return Err("the hardness must be greater than zero".to_owned());
+        }
+
+        if self.min_tool_power < 0 {
+            return Err("the minimum tool power must not be negative".to_owned());
+        }
+
+        if self.dusty && self.particle.is_none() {
+            return Err("a dusty block must have a particle".to_owned());
+        }
+
+        validate_glow(self.glow)?;
+
+        if self.absorption.is_some_and(|absorption| !(0. ..=1.).contains(&absorption)) {
+            return Err("the light absorption must be between 0 and 1".to_owned());
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Deserialize)]
+pub(crate) struct WallDefinition {
+    /// The id of the wall in Terraria which is also used in the world files
+    pub(crate) id: WallId,
+    /// The amount of damage the wall takes before it breaks
+    pub(crate) hardness: i32,
+    /// The color of the wall on the world map
+    pub(crate) color: [u8; 3],
+    /// The id of the item which is dropped when the wall is broken
+    pub(crate) drop: Option<String>,
+    pub(crate) particle: Option<Particle>,
+    /// The color the wall lights itself with when no block covers it
+    pub(crate) glow: Option<[f32; 3]>,
+}
+
+impl WallDefinition {
+    fn validate(&self) -> Result<(), String> {
+        if self.hardness <= 0 {
+            return Err("the hardness must be greater than zero".to_owned());
+        }
+
+        validate_glow(self.glow)
+    }
+}
+
+fn validate_glow(glow: Option<[f32; 3]>) -> Result<(), String> {
+    if glow.is_some_and(|color| color.iter().any(|channel| !(0. ..=1.).contains(channel))) {
+        return Err("the glow color channels must be between 0 and 1".to_owned());
+    }
+
+    Ok(())
+}
+
+#[inline]
+pub(super) fn block_definitions() -> &'static [BlockDefinition] {
+    BLOCK_REGISTRY.get().expect("The tiles must be loaded before they are used")
+}
+
+#[inline]
+pub(super) fn wall_definitions() -> &'static [WallDefinition] {
+    WALL_REGISTRY.get().expect("The tiles must be loaded before they are used")
+}
+
+/// Loads the block and wall definitions into the registry. Must be called once before any tile is used.
+pub fn load_tiles() -> Result<(), Box<dyn Error>> {
+    let block_names = block_definition_names();
+    let wall_names = wall_definition_names();
+
+    let mut blocks: Vec<BlockDefinition> = read_definitions("./assets/data/blocks.json", &block_names)?;
+    let walls: Vec<WallDefinition> = read_definitions("./assets/data/walls.json", &wall_names)?;
+
+    for (name, block) in block_names.iter().zip(&blocks) {
+        block.validate().map_err(|err| format!("Block \"{}\" is invalid: {}", name, err))?;
+    }
+
+    for (name, wall) in wall_names.iter().zip(&walls) {
+        wall.validate().map_err(|err| format!("Wall \"{}\" is invalid: {}", name, err))?;
+    }
+
+    if let Some(name) = find_duplicate_id(&block_names, blocks.iter().map(|block| block.id)) {
+        return Err(format!("Block \"{}\" has the same id as another block", name).into());
+    }
+
+    if let Some(name) = find_duplicate_id(&wall_names, walls.iter().map(|wall| wall.id)) {
+        return Err(format!("Wall \"{}\" has the same id as another wall", name).into());
+    }
+
+    for (name, block) in block_names.into_iter().zip(&mut blocks) {
+        block.name = name;
+    }
+
+    BLOCK_REGISTRY.set(blocks).map_err(|_| "The tiles are already loaded")?;
+    WALL_REGISTRY.set(walls).map_err(|_| "The tiles are already loaded")?;
+
+    Ok(())
+}
+
+/// Checks that the tiles drop existing items and that breaking a placed tile gives back the item which placed it.
+/// Must be called after both the tiles and the items are loaded.
+pub(crate) fn validate_tile_drops() -> Result<(), String> {
+    let block_names = block_definition_names();
+    let wall_names = wall_definition_names();
+
+    let drops = block_names.iter().zip(block_definitions()).map(|(name, block)| ("Block", name, &block.drop))
+        .chain(wall_names.iter().zip(wall_definitions()).map(|(name, wall)| ("Wall", name, &wall.drop)));
+
+    for (kind, name, drop) in drops {
+        if let Some(id) = drop {
+            if Item::from_id(id).is_none() {
+                return Err(format!("{} \"{}\" drops an unknown item \"{}\"", kind, name, id));
+            }
+        }
+    }
+
+    for item in Item::all() {
+        let dropped_item = match item.behaviour() {
+            ItemBehaviour::Block(item_block) => BlockType::from(item_block).dropped_item(),
+            ItemBehaviour::Wall(item_wall) => WallType::from(item_wall).dropped_item(),
+            ItemBehaviour::Furniture(item_furniture) => {
+                BlockType::Furniture(Furniture::new(item_furniture.into(), 0, 0, 0)).dropped_item()
+            },
+            _ => continue
+        };
+
+        if dropped_item != Some(item) {
+            return Err(format!("Item \"{}\" places a tile which doesn't drop it back", item.definition().id));
+        }
+    }
+
+    Ok(())
+}
+
+/// Names of the block definitions in the data file in the order of their indices in the registry
+fn block_definition_names() -> Vec<String> {
+    BlockType::PLAIN.iter().map(|block_type| format!("{:?}", block_type))
+        .chain(std::iter::once(String::from("Tree")))
+        .chain(FurnitureType::ALL.iter().map(|furniture_type| format!("{:?}", furniture_type)))
+        .collect()
+}
+
+/// Names of the wall definitions in the data file in the order of [`WallType::ALL`]
+fn wall_definition_names() -> Vec<String> {
+    WallType::ALL.iter().map(|wall_type| format!("{:?}", wall_type)).collect()
+}
+
+/// Reads a file which maps the names of the definitions to the definitions and returns them in the order of `names`
+fn read_definitions<T: DeserializeOwned>(path: &str, names: &[String]) -> Result<Vec<T>, Box<dyn Error>> {
+    let reader = BufReader::new(File::open(path)?);
+    let mut definitions: HashMap<String, T> = serde_json::from_reader(reader)?;
+
+    let ordered = names.iter()
+        .map(|name| definitions.remove(name).ok_or_else(|| format!("\"{}\" is not defined in {}", name, path)))
+        .collect::<Result<Vec<T>, String>>()?;
+
+    if let Some(name) = definitions.keys().next() {
+        return Err(format!("Unknown definition \"{}\" in {}", name, path).into());
+    }
+
+    Ok(ordered)
+}
+
+fn find_duplicate_id(names: &[String], ids: impl Iterator<Item = u8>) -> Option<&str> {
+    let ids: Vec<u8> = ids.collect();
+
+    (0..ids.len())
+        .find(|&i| ids[..i].contains(&ids[i]))
+        .map(|i| names[i].as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{test_utils::load_registries, world::tree::{Tree, TreeType, TreeFrameType}};
+
+    use super::*;
+
+    #[test]
+    fn definitions_follow_the_variants() {
+        for (index, block_type) in BlockType::PLAIN.iter().enumerate() {
+            assert_eq!(block_type.variant_index(), index, "{:?}", block_type);
+        }
+
+        let tree = BlockType::Tree(Tree::new(TreeType::Forest, TreeFrameType::TrunkPlain));
+        assert_eq!(tree.variant_index(), BlockType::PLAIN.len());
+
+        let furniture = BlockType::Furniture(Furniture::new(FurnitureType::Torch, 0, 0, 0));
+        assert_eq!(furniture.variant_index(), FURNITURE_DEFINITIONS_START);
+    }
+
+    #[test]
+    fn placed_tiles_drop_their_items() {
+        load_registries();
+        validate_tile_drops().unwrap();
+    }
+}
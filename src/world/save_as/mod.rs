@@ -1 +1,2 @@
-pub mod terraria_world;
\ No newline at end of file
+pub mod terraria_world;
+pub mod world_image;
\ No newline at end of file
@@ -1,15 +1,18 @@
-use std::{io::{BufWriter, Write}, fs::File};
+use std::{io::{BufWriter, Write}, fs::File, ops::Range, path::Path};
 
-use rand::{thread_rng, Rng};
-
-use crate::world::WorldData;
+use crate::{world::{WorldData, biome::Biome, liquid::LiquidType}, common::math::Fnv1aHasher};
 
 impl WorldData {
-    #[allow(unused)]
-    pub fn save_as_terraria_world(&self, world_name: &str) -> std::io::Result<()> {
+    pub fn save_as_terraria_world(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
         println!("Saving as Terraria world...");
 
-        let world_file = File::create(format!("{}.wld", world_name))?;
+        let path = path.as_ref();
+
+        let world_name = path.file_stem()
+            .and_then(|name| name.to_str())
+            .unwrap_or("World");
+
+        let world_file = File::create(path)?;
         let mut world_writer = BufWriter::new(world_file);
 
         self.save_world_header(world_name, &mut world_writer)?;
@@ -28,7 +31,7 @@ impl WorldData {
         write_true(&mut world_writer)?;
 
         write_string(world_name, &mut world_writer)?;
-        write_i32(123, &mut world_writer);
+        write_i32(123, &mut world_writer)?;
 
         Ok(())
     }
@@ -41,7 +44,7 @@ impl WorldData {
         write_string(world_name, world_writer)?;
 
         // World Id
-        write_i32(thread_rng().gen(), world_writer)?;
+        write_i32(world_id(world_name), world_writer)?;
 
         // Left world
         write_i32(0, world_writer)?;
@@ -64,36 +67,38 @@ impl WorldData {
         // Moon type
         write_i8(1, world_writer)?;
 
+        let (region_x, region_biomes) = self.terraria_background_regions();
+
         // Tree x
-        write_i32(0, world_writer)?;
-        write_i32(0, world_writer)?;
-        write_i32(0, world_writer)?;
+        for x in region_x {
+            write_i32(x, world_writer)?;
+        }
 
         // Tree style
-        write_i32(0, world_writer)?;
-        write_i32(0, world_writer)?;
-        write_i32(0, world_writer)?;
-        write_i32(0, world_writer)?;
+        for biome in region_biomes {
+            write_i32(biome.terraria_region_style(), world_writer)?;
+        }
 
         // Caveback x
-        write_i32((self.width() / 2) as i32, world_writer)?;
-        write_i32(self.width() as i32, world_writer)?;
-        write_i32(self.width() as i32, world_writer)?;
+        for x in region_x {
+            write_i32(x, world_writer)?;
+        }
 
-        // Tree style
-        write_i32(0, world_writer)?;
-        write_i32(1, world_writer)?;
-        write_i32(2, world_writer)?;
-        write_i32(3, world_writer)?;
+        // Caveback style
+        for biome in region_biomes {
+            write_i32(biome.terraria_region_style(), world_writer)?;
+        }
+
+        let biome_ranges = self.biome_ranges();
 
         // Iceback style
-        write_i32(0, world_writer)?;
+        write_i32(terraria_biome_back_style(&biome_ranges, Some(Biome::Snow), 4), world_writer)?;
 
         // Jungleback style
-        write_i32(0, world_writer)?;
+        write_i32(terraria_biome_back_style(&biome_ranges, Some(Biome::Jungle), 2), world_writer)?;
 
         // Hellback style
-        write_i32(0, world_writer)?;
+        write_i32(terraria_biome_back_style(&biome_ranges, None, 3), world_writer)?;
 
         // Spawn tile coords
         world_writer.write_all(&self.spawn_point.x.to_le_bytes())?;
@@ -261,11 +266,31 @@ impl WorldData {
         Ok(())
     }
 
+    /// Terraria splits the background styles into 4 regions separated by 3 x coordinates.
+    /// The regions follow the biomes of the world, the biomes which don't fit are merged into the last region.
+    fn terraria_background_regions(&self) -> ([i32; 3], [Biome; 4]) {
+        let ranges = self.biome_ranges();
+
+        let mut region_x = [self.width() as i32; 3];
+        let mut region_biomes = [Biome::Forest; 4];
+
+        for (i, (range, biome)) in ranges.into_iter().take(4).enumerate() {
+            if i > 0 {
+                region_x[i - 1] = range.start as i32;
+            }
+
+            region_biomes[i] = biome;
+        }
+
+        (region_x, region_biomes)
+    }
+
     fn save_world_tiles<W: Write>(&self, writer: &mut BufWriter<W>) -> std::io::Result<()> {
         for x in 0..self.width() {
             for y in 0..self.height() {
                 let block = self.blocks[(y, x)];
                 let wall = self.walls[(y, x)];
+                let liquid = self.liquids[(y, x)];
 
                 // Is active
                 write_bool(block.is_some(), writer)?;
@@ -293,18 +318,30 @@ impl WorldData {
                 }
 
                 // Is liquid
-                write_false(writer)?;
+                write_bool(liquid.is_some(), writer)?;
+
+                if let Some(liquid) = liquid {
+                    write_u8(liquid.amount, writer)?;
+
+                    // Lava
+                    write_bool(liquid.liquid_type == LiquidType::Lava, writer)?;
+
+                    // Honey
+                    write_false(writer)?;
+                }
 
                 // Is wire (red, green, blue)
                 write_false(writer)?;
                 write_false(writer)?;
                 write_false(writer)?;
 
+                let (half_brick, slope) = block.map_or((false, 0), |block| block.shape.terraria_slope());
+
                 // Half brick
-                write_false(writer)?;
+                write_bool(half_brick, writer)?;
 
                 // Slope
-                write_u8(0, writer)?;
+                write_u8(slope, writer)?;
 
                 write_false(writer)?;
                 write_false(writer)?;
@@ -317,6 +354,29 @@ impl WorldData {
     }
 }
 
+/// The world id is derived from the name so that exporting the same world twice gives identical files
+fn world_id(world_name: &str) -> i32 {
+    let mut hasher = Fnv1aHasher::default();
+    hasher.update(world_name.as_bytes());
+    hasher.finish() as i32
+}
+
+/// Terraria picks the biome background styles randomly when creating a world.
+/// They are derived from the columns the biome occupies instead, `None` takes the whole biome layout into account.
+fn terraria_biome_back_style(biome_ranges: &[(Range<usize>, Biome)], biome: Option<Biome>, style_count: u64) -> i32 {
+    let mut hasher = Fnv1aHasher::default();
+
+    for (range, range_biome) in biome_ranges {
+        if biome.is_none() || biome == Some(*range_biome) {
+            hasher.update(&(range.start as u32).to_le_bytes());
+            hasher.update(&(range.end as u32).to_le_bytes());
+            hasher.update(&[range_biome.id()]);
+        }
+    }
+
+    (hasher.finish() % style_count) as i32
+}
+
 fn save_world_chests<W: Write>(writer: &mut BufWriter<W>) -> std::io::Result<()> {
     for _ in 0..1000 {
         write_false(writer)?;
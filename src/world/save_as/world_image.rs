@@ -0,0 +1,75 @@
+use std::{error::Error, path::Path};
+
+use image::{RgbImage, ImageBuffer, GenericImageView, Pixel};
+
+use crate::world::{WorldData, wall::WallType};
+
+const SKY_IMAGE_PATH: &str = "assets/sprites/backgrounds/Background_0.png";
+
+impl WorldData {
+    pub fn save_as_image(&self, path: impl AsRef<Path>, draw_layers: bool) -> Result<(), Box<dyn Error>> {
+        fn map_range(value: f32, in_min: f32, in_max: f32, out_min: f32, out_max: f32) -> f32 {
+            out_min + (((value - in_min) / (in_max - in_min)) * (out_max - out_min))
+        }
+
+        println!("Saving as image...");
+
+        let playable_area_min_x = self.playable_area.min.x;
+        let playable_area_min_y = self.playable_area.min.y;
+
+        let sky_image = image::io::Reader::open(SKY_IMAGE_PATH)?.decode()?;
+        let sky_image_height = sky_image.height() as usize;
+        let mut image: RgbImage = ImageBuffer::new(self.playable_area.width(), self.playable_area.height());
+
+        // Draw walls
+        for y in 0..self.playable_area.height() {
+            for x in 0..self.playable_area.width() {
+                if let Some(color) = self.get_wall_color((playable_area_min_x + x, playable_area_min_y + y)) {
+                    image.put_pixel(x, y, image::Rgb(color));
+                } else {
+                    let sky_image_y = map_range(y as f32, 0., self.height() as f32, 0., sky_image_height as f32);
+
+                    let color = sky_image.get_pixel(0, sky_image_y as u32);
+
+                    image.put_pixel(x, y, color.to_rgb());
+                }
+            }
+        }
+
+        // Draw background
+        for y in self.layer.underground..self.playable_height() {
+            for x in 0..self.playable_width() {
+                let color = WallType::Dirt.color();
+                image.put_pixel(x as u32, y as u32, image::Rgb(color));
+            }
+        }
+
+        // Draw blocks and liquids
+        for y in 0..self.playable_area.height() {
+            for x in 0..self.playable_area.width() {
+                let pos = (playable_area_min_x + x, playable_area_min_y + y);
+
+                if let Some(color) = self.get_block_color(pos).or_else(|| self.get_liquid_color(pos)) {
+                    image.put_pixel(x, y, image::Rgb(color));
+                }
+            }
+        }
+
+        // Draw layer borders
+        if draw_layers {
+            let surface_layer = self.layer.surface;
+            let underground_layer = self.layer.underground;
+            let cavern_layer = self.layer.cavern;
+
+            for x in 0..self.playable_width() {
+                image.put_pixel(x as u32, surface_layer as u32, image::Rgb([255, 0, 0]));
+                image.put_pixel(x as u32, underground_layer as u32, image::Rgb([255, 0, 0]));
+                image.put_pixel(x as u32, cavern_layer as u32, image::Rgb([255, 0, 0]));
+            }
+        }
+
+        image.save(path)?;
+
+        Ok(())
+    }
+}
@@ -34,9 +34,26 @@ pub enum TreeFrameType {
 }
 
 impl TreeFrameType {
+    pub(crate) const ALL: [TreeFrameType; 13] = [
+        Self::TrunkPlain,
+        Self::BasePlainLeft,
+        Self::BasePlainRight,
+        Self::BasePlainAD,
+        Self::BasePlainA,
+        Self::BasePlainD,
+        Self::BranchLeftBare,
+        Self::BranchRightBare,
+        Self::BranchLeftLeaves,
+        Self::BranchRightLeaves,
+        Self::TopBare,
+        Self::TopLeaves,
+        Self::TopBareJagged,
+    ];
+
     pub(crate) const fn terraria_frame(&self, tree_type: TreeType) -> [TerrariaFrame; 3] {
+        // Terraria picks the style of a tree by the block it grows on, so all tree types share the frames
         match tree_type {
-            TreeType::Forest => {
+            TreeType::Forest | TreeType::Jungle | TreeType::Snow => {
                 match self {
                     Self::TrunkPlain => [TerrariaFrame::new(0, 0), TerrariaFrame::new(0, 22), TerrariaFrame::new(0, 44)],
                     Self::BasePlainLeft => [TerrariaFrame::new(44, 132), TerrariaFrame::new(44, 154), TerrariaFrame::new(44, 176)],
@@ -56,27 +73,36 @@ impl TreeFrameType {
         }
     }
 
+    /// Returns the frame type and the variant which are written as the given frame
+    pub(crate) fn from_terraria_frame(tree_type: TreeType, frame: TerrariaFrame) -> Option<(TreeFrameType, u32)> {
+        Self::ALL.into_iter().find_map(|frame_type| {
+            frame_type.terraria_frame(tree_type)
+                .iter()
+                .position(|f| *f == frame)
+                .map(|variant| (frame_type, variant as u32))
+        })
+    }
+
     pub(crate) const fn texture_atlas_pos(&self, tree_type: TreeType, variant: u32) -> TextureAtlasPos {
         assert!(variant < 3, "Variant of texture must be in range of 0 to 3");
 
-        match tree_type {
-            TreeType::Forest => {
-                match self {
-                    Self::TrunkPlain => TextureAtlasPos::new(0, variant),
-                    Self::BasePlainLeft => TextureAtlasPos::new(2, 6 + variant),
-                    Self::BasePlainRight => TextureAtlasPos::new(1, 6 + variant),
-                    Self::BasePlainAD => TextureAtlasPos::new(4, 6 + variant),
-                    Self::BasePlainA => TextureAtlasPos::new(3, 6 + variant),
-                    Self::BasePlainD => TextureAtlasPos::new(0, 6 + variant),
-                    Self::BranchLeftBare => TextureAtlasPos::new(3, variant),
-                    Self::BranchRightBare => TextureAtlasPos::new(4, 3 + variant),
-                    Self::BranchLeftLeaves => TextureAtlasPos::new(0, variant),
-                    Self::BranchRightLeaves => TextureAtlasPos::new(1, variant),
-                    Self::TopBare => TextureAtlasPos::new(5, variant),
-                    Self::TopLeaves => TextureAtlasPos::new(0, variant),
-                    Self::TopBareJagged => TextureAtlasPos::new(0, 9 + variant)
-                }
-            }
+        // The trunks are shared, the leaves of every tree type are stacked vertically in the same texture
+        let tree_type = tree_type as u32;
+
+        match self {
+            Self::TrunkPlain => TextureAtlasPos::new(0, variant),
+            Self::BasePlainLeft => TextureAtlasPos::new(2, 6 + variant),
+            Self::BasePlainRight => TextureAtlasPos::new(1, 6 + variant),
+            Self::BasePlainAD => TextureAtlasPos::new(4, 6 + variant),
+            Self::BasePlainA => TextureAtlasPos::new(3, 6 + variant),
+            Self::BasePlainD => TextureAtlasPos::new(0, 6 + variant),
+            Self::BranchLeftBare => TextureAtlasPos::new(3, variant),
+            Self::BranchRightBare => TextureAtlasPos::new(4, 3 + variant),
+            Self::BranchLeftLeaves => TextureAtlasPos::new(0, tree_type * 3 + variant),
+            Self::BranchRightLeaves => TextureAtlasPos::new(1, tree_type * 3 + variant),
+            Self::TopBare => TextureAtlasPos::new(5, variant),
+            Self::TopLeaves => TextureAtlasPos::new(variant, tree_type),
+            Self::TopBareJagged => TextureAtlasPos::new(0, 9 + variant)
         }
     }
 
@@ -97,7 +123,13 @@ impl TreeFrameType {
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[cfg_attr(feature = "debug", derive(bevy::reflect::Reflect))]
 pub enum TreeType {
-    Forest
+    Forest,
+    Jungle,
+    Snow,
+}
+
+impl TreeType {
+    pub(crate) const ALL: [TreeType; 3] = [TreeType::Forest, TreeType::Jungle, TreeType::Snow];
 }
 
 #[derive(Debug, Clone, Copy, Eq)]
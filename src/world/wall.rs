@@ -3,7 +3,9 @@ use std::ops::Deref;
 use bevy_ecs_tilemap::helpers::square_grid::neighbors::Neighbors;
 use rand::{thread_rng, Rng};
 
-use crate::{common::{helpers::get_wall_start_index, TextureAtlasPos}, items::ItemWall};
+use crate::{common::{helpers::get_wall_start_index, TextureAtlasPos}, items::{Item, ItemWall}};
+
+use super::registry::{WallDefinition, wall_definitions};
 
 pub(crate) type WallId = u8;
 
@@ -11,25 +13,51 @@ pub(crate) type WallId = u8;
 pub enum WallType {
     Stone,
     Dirt,
+    Mud,
+    Snow,
 }
 
 impl WallType {
+    pub(crate) const ALL: [WallType; 4] = [
+        WallType::Stone,
+        WallType::Dirt,
+        WallType::Mud,
+        WallType::Snow,
+    ];
+
+    #[inline]
+    pub(crate) fn definition(&self) -> &'static WallDefinition {
+        &wall_definitions()[*self as usize]
+    }
+
+    #[inline]
     pub fn max_hp(&self) -> i32 {
-        70
+        self.definition().hardness
     }
 
+    #[inline]
     pub fn id(&self) -> WallId {
-        match self {
-            WallType::Stone => 1,
-            WallType::Dirt => 2,
-        }
+        self.definition().id
+    }
+
+    pub(crate) fn from_id(id: WallId) -> Option<WallType> {
+        Self::ALL.into_iter().find(|wall_type| wall_type.id() == id)
     }
 
+    #[inline]
     pub fn color(&self) -> [u8; 3] {
-        match self {
-            WallType::Stone => [52, 52, 52],
-            WallType::Dirt => [88, 61, 46],
-        }
+        self.definition().color
+    }
+
+    #[inline]
+    pub(crate) fn glow(&self) -> Option<[f32; 3]> {
+        self.definition().glow
+    }
+
+    /// The item which is dropped when the wall is broken
+    pub(crate) fn dropped_item(&self) -> Option<Item> {
+        // The dropped items are checked when the items are loaded
+        self.definition().drop.as_deref().map(|id| Item::from_id(id).unwrap())
     }
 }
 
@@ -51,9 +79,8 @@ pub struct Wall {
 }
 
 impl From<WallType> for Wall {
-    fn from(block_type: WallType) -> Self {
-        let mut rng = thread_rng();
-        Wall::new(block_type, rng.gen_range(0..3))
+    fn from(wall_type: WallType) -> Self {
+        Wall::with_random_variant(wall_type, &mut thread_rng())
     }
 }
 
@@ -75,6 +102,11 @@ impl Wall {
             cracks_index: None
         }
     }
+
+    #[inline]
+    pub(crate) fn with_random_variant(wall_type: WallType, rng: &mut impl Rng) -> Wall {
+        Wall::new(wall_type, rng.gen_range(0..3))
+    }
 }
 
 impl Wall {
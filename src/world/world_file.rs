@@ -0,0 +1,681 @@
+use std::{fs::File, io::{BufWriter, BufReader, Write, Read, Error, ErrorKind}, path::Path};
+
+use bevy::{math::URect, prelude::UVec2, utils::HashMap};
+use bevy_ecs_tilemap::tiles::TilePos;
+
+use crate::{common::math::Fnv1aHasher, items::{ChestContents, Item, ItemStack, SlotContainer, CHEST_SLOT_COUNT}};
+
+use super::{
+    WorldData, Layer, BlockArray, WallArray, LiquidArray,
+    block::{Block, BlockType, BlockShape},
+    biome::Biome,
+    wall::{Wall, WallType},
+    liquid::{Liquid, LiquidType},
+    tree::{Tree, TreeType, TreeFrameType},
+    furniture::{Furniture, FurnitureType}
+};
+
+pub const WORLD_FILE_EXTENSION: &str = "world";
+
+const MAGIC: [u8; 4] = *b"TCWF";
+const FORMAT_VERSION: u16 = 5;
+
+const BLOCK_RECORD_SIZE: usize = 14;
+const WALL_RECORD_SIZE: usize = 12;
+const LIQUID_RECORD_SIZE: usize = 3;
+
+type BlockRecord = [u8; BLOCK_RECORD_SIZE];
+type WallRecord = [u8; WALL_RECORD_SIZE];
+type LiquidRecord = [u8; LIQUID_RECORD_SIZE];
+
+/*
+ * File layout (all numbers are little endian):
+ *
+ * magic             4 bytes   "TCWF"
+ * version           u16
+ * area              4 x u32   min.x, min.y, max.x, max.y
+ * playable area     4 x u32   min.x, min.y, max.x, max.y
+ * layer             4 x u32   surface, underground, cavern, dirt height
+ * spawn point       2 x u32
+ * biomes            u8 per column, since version 2
+ * blocks            run-length encoded layer
+ * walls             run-length encoded layer
+ * liquids           run-length encoded layer, since version 3
+ * chests            u32 count followed by the chests, since version 5
+ *
+ * A layer is stored row by row as a sequence of runs. Each run is a u32 length
+ * followed by a single tile record that is repeated `length` times.
+ * A record starts with a presence byte, an empty tile is stored as that byte only.
+ *
+ * A chest is the position of its top left tile (2 x u32) followed by a u16 slot count and the slots.
+ * A slot starts with a presence byte, an item is stored as the length of its id (u8), the id and the stack (u16).
+*/
+
+impl WorldData {
+    pub fn save(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        let file = File::create(path)?;
+        let mut writer = BufWriter::new(file);
+
+        self.write_world(&mut writer)?;
+
+        writer.flush()
+    }
+
+    /// Hash of the world as it is written to a file.
+    /// Worlds generated from the same seed must have the same checksum.
+    pub fn checksum(&self) -> u64 {
+        let mut hasher = Fnv1aHasher::default();
+
+        // Writing into the hasher never fails
+        self.write_world(&mut hasher).unwrap();
+
+        hasher.finish()
+    }
+
+    fn write_world<W: Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        writer.write_all(&MAGIC)?;
+        writer.write_all(&FORMAT_VERSION.to_le_bytes())?;
+
+        write_urect(self.area, writer)?;
+        write_urect(self.playable_area, writer)?;
+
+        write_u32(self.layer.surface as u32, writer)?;
+        write_u32(self.layer.underground as u32, writer)?;
+        write_u32(self.layer.cavern as u32, writer)?;
+        write_u32(self.layer.dirt_height as u32, writer)?;
+
+        write_u32(self.spawn_point.x, writer)?;
+        write_u32(self.spawn_point.y, writer)?;
+
+        let biomes: Vec<u8> = self.biomes.iter().map(|biome| biome.id()).collect();
+        writer.write_all(&biomes)?;
+
+        write_layer(self.blocks.iter().map(|b| encode_block(b.as_ref())), writer)?;
+        write_layer(self.walls.iter().map(|w| encode_wall(w.as_ref())), writer)?;
+        write_layer(self.liquids.iter().map(|l| encode_liquid(l.as_ref())), writer)?;
+
+        write_chests(&self.chests, writer)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> std::io::Result<WorldData> {
+        let file = File::open(path)?;
+
+        Self::read_world(&mut BufReader::new(file))
+    }
+
+    fn read_world<R: Read>(reader: &mut R) -> std::io::Result<WorldData> {
+        let mut magic = [0u8; 4];
+        reader.read_exact(&mut magic)?;
+
+        if magic != MAGIC {
+            return Err(invalid_data("Not a world file"));
+        }
+
+        let version = read_u16(reader)?;
+
+        if version == 0 || version > FORMAT_VERSION {
+            return Err(invalid_data(format!("Unsupported world file version {}", version)));
+        }
+
+        let area = read_urect(reader)?;
+        let playable_area = read_urect(reader)?;
+
+        let layer = Layer {
+            surface: read_u32(reader)? as usize,
+            underground: read_u32(reader)? as usize,
+            cavern: read_u32(reader)? as usize,
+            dirt_height: read_u32(reader)? as usize,
+        };
+
+        let spawn_point = TilePos::new(read_u32(reader)?, read_u32(reader)?);
+
+        let shape = (area.height() as usize, area.width() as usize);
+
+        let biomes = if version >= 2 {
+            let mut ids = vec![0u8; shape.1];
+            reader.read_exact(&mut ids)?;
+
+            ids.into_iter()
+                .map(|id| Biome::from_id(id).ok_or_else(|| invalid_data(format!("Unknown biome id {}", id))))
+                .collect::<std::io::Result<Vec<Biome>>>()?
+        } else {
+            vec![Biome::Forest; shape.1]
+        };
+
+        let blocks = read_layer(reader, shape, read_block)?;
+        let walls = read_layer(reader, shape, read_wall)?;
+
+        let liquids = if version >= 3 {
+            LiquidArray::from_shape_vec(shape, read_layer(reader, shape, read_liquid)?).map_err(invalid_data)?
+        } else {
+            LiquidArray::default(shape)
+        };
+
+        let chests = if version >= 5 {
+            read_chests(reader)?
+        } else {
+            HashMap::default()
+        };
+
+        Ok(WorldData {
+            area,
+            layer,
+            playable_area,
+            spawn_point,
+            blocks: BlockArray::from_shape_vec(shape, blocks).map_err(invalid_data)?,
+            walls: WallArray::from_shape_vec(shape, walls).map_err(invalid_data)?,
+            liquids,
+            biomes,
+            chests,
+        })
+    }
+}
+
+impl WorldData {
+    /// Returns the positions of the tiles which are stored differently in the two worlds,
+    /// or `None` when the worlds have different sizes.
+    pub fn differing_tiles<'a>(&'a self, other: &'a WorldData) -> Option<impl Iterator<Item = TilePos> + 'a> {
+        if self.area != other.area {
+            return None;
+        }
+
+        let differing = self.blocks.indexed_iter()
+            .zip(other.blocks.iter())
+            .zip(self.walls.iter().zip(other.walls.iter()))
+            .zip(self.liquids.iter().zip(other.liquids.iter()))
+            .filter(|((((_, a), b), (wall_a, wall_b)), (liquid_a, liquid_b))| {
+                encode_block(a.as_ref()) != encode_block(b.as_ref())
+                    || encode_wall(wall_a.as_ref()) != encode_wall(wall_b.as_ref())
+                    || encode_liquid(liquid_a.as_ref()) != encode_liquid(liquid_b.as_ref())
+            })
+            .map(|(((((y, x), _), _), _), _)| TilePos::new(x as u32, y as u32));
+
+        Some(differing)
+    }
+}
+
+fn encode_block(block: Option<&Block>) -> BlockRecord {
+    let mut record = [0u8; BLOCK_RECORD_SIZE];
+
+    let Some(block) = block else { return record; };
+
+    // Trees store their type and frame, furniture stores the tile position inside of the object and its style
+    let (a, b) = match block.block_type {
+        BlockType::Tree(tree) => (tree.tree_type as u8, tree.frame_type as u8),
+        BlockType::Furniture(furniture) => (furniture.x | furniture.y << 4, furniture.style),
+        _ => (0, 0)
+    };
+
+    record[0] = 1;
+    record[1] = block.block_type.id();
+    record[2] = a;
+    record[3] = b;
+    // The shape takes the high bits of the variant byte, it's always zero in the files of the older versions
+    record[4] = block.variant as u8 | block.shape.id() << 4;
+    record[5..9].copy_from_slice(&block.hp.to_le_bytes());
+    encode_cracks(block.cracks_index, &mut record[9..14]);
+
+    record
+}
+
+fn encode_wall(wall: Option<&Wall>) -> WallRecord {
+    let mut record = [0u8; WALL_RECORD_SIZE];
+
+    let Some(wall) = wall else { return record; };
+
+    record[0] = 1;
+    record[1] = wall.wall_type.id();
+    record[2] = wall.variant as u8;
+    record[3..7].copy_from_slice(&wall.hp.to_le_bytes());
+    encode_cracks(wall.cracks_index, &mut record[7..12]);
+
+    record
+}
+
+fn encode_liquid(liquid: Option<&Liquid>) -> LiquidRecord {
+    let mut record = [0u8; LIQUID_RECORD_SIZE];
+
+    let Some(liquid) = liquid else { return record; };
+
+    record[0] = 1;
+    record[1] = liquid.liquid_type.id();
+    record[2] = liquid.amount;
+
+    record
+}
+
+#[inline]
+fn encode_cracks(cracks_index: Option<u32>, out: &mut [u8]) {
+    if let Some(index) = cracks_index {
+        out[0] = 1;
+        out[1..5].copy_from_slice(&index.to_le_bytes());
+    }
+}
+
+#[inline]
+fn decode_cracks(bytes: &[u8]) -> Option<u32> {
+    (bytes[0] != 0).then(|| u32::from_le_bytes([bytes[1], bytes[2], bytes[3], bytes[4]]))
+}
+
+fn read_block<R: Read>(reader: &mut R, present: bool) -> std::io::Result<Option<Block>> {
+    if !present { return Ok(None); }
+
+    let mut record = [0u8; BLOCK_RECORD_SIZE - 1];
+    reader.read_exact(&mut record)?;
+
+    let tree_id = BlockType::Tree(Tree::new(TreeType::Forest, TreeFrameType::TrunkPlain)).id();
+
+    let block_type = match record[0] {
+        id if id == tree_id => {
+            let tree_type = tree_type_from_u8(record[1])
+                .ok_or_else(|| invalid_data(format!("Unknown tree type {}", record[1])))?;
+            let frame_type = tree_frame_type_from_u8(record[2])
+                .ok_or_else(|| invalid_data(format!("Unknown tree frame type {}", record[2])))?;
+
+            BlockType::Tree(Tree::new(tree_type, frame_type))
+        },
+        id => match FurnitureType::from_id(id) {
+            Some(furniture_type) => {
+                let furniture = Furniture::new(furniture_type, record[1] & 0xF, record[1] >> 4, record[2]);
+                let size = furniture_type.size();
+
+                if furniture.x as u32 >= size.x || furniture.y as u32 >= size.y {
+                    return Err(invalid_data(format!("Invalid tile {}x{} of {:?}", furniture.x, furniture.y, furniture_type)));
+                }
+
+                BlockType::Furniture(furniture)
+            },
+            None => BlockType::from_id(id).ok_or_else(|| invalid_data(format!("Unknown block id {}", id)))?
+        }
+    };
+
+    let variant = (record[3] & 0xF) as u32;
+
+    if variant > 2 {
+        return Err(invalid_data(format!("Invalid block variant {}", variant)));
+    }
+
+    let shape = BlockShape::from_id(record[3] >> 4)
+        .ok_or_else(|| invalid_data(format!("Unknown block shape {}", record[3] >> 4)))?;
+
+    Ok(Some(Block {
+        block_type,
+        variant,
+        hp: i32::from_le_bytes([record[4], record[5], record[6], record[7]]),
+        cracks_index: decode_cracks(&record[8..13]),
+        shape,
+    }))
+}
+
+fn read_wall<R: Read>(reader: &mut R, present: bool) -> std::io::Result<Option<Wall>> {
+    if !present { return Ok(None); }
+
+    let mut record = [0u8; WALL_RECORD_SIZE - 1];
+    reader.read_exact(&mut record)?;
+
+    let wall_type = WallType::from_id(record[0])
+        .ok_or_else(|| invalid_data(format!("Unknown wall id {}", record[0])))?;
+
+    let variant = record[1] as u32;
+
+    if variant > 2 {
+        return Err(invalid_data(format!("Invalid wall variant {}", variant)));
+    }
+
+    Ok(Some(Wall {
+        wall_type,
+        variant,
+        hp: i32::from_le_bytes([record[2], record[3], record[4], record[5]]),
+        cracks_index: decode_cracks(&record[6..11]),
+    }))
+}
+
+fn read_liquid<R: Read>(reader: &mut R, present: bool) -> std::io::Result<Option<Liquid>> {
+    if !present { return Ok(None); }
+
+    let mut record = [0u8; LIQUID_RECORD_SIZE - 1];
+    reader.read_exact(&mut record)?;
+
+    let liquid_type = LiquidType::from_id(record[0])
+        .ok_or_else(|| invalid_data(format!("Unknown liquid id {}", record[0])))?;
+
+    if record[1] == 0 {
+        return Err(invalid_data("Empty liquid"));
+    }
+
+    Ok(Some(Liquid::new(liquid_type, record[1])))
+}
+
+fn write_chests<W: Write>(chests: &HashMap<TilePos, ChestContents>, writer: &mut W) -> std::io::Result<()> {
+    // Sorted, so the same world is always written the same way
+    let mut chests: Vec<_> = chests.iter().filter(|(_, chest)| !chest.is_empty()).collect();
+    chests.sort_unstable_by_key(|(pos, _)| (pos.y, pos.x));
+
+    write_u32(chests.len() as u32, writer)?;
+
+    for (pos, chest) in chests {
+        write_u32(pos.x, writer)?;
+        write_u32(pos.y, writer)?;
+        write_u16(chest.slot_count() as u16, writer)?;
+
+        for slot in chest.slots() {
+            let Some(item_stack) = slot else {
+                writer.write_all(&[0])?;
+                continue;
+            };
+
+            let id = item_stack.item.definition().id.as_bytes();
+
+            writer.write_all(&[1, id.len() as u8])?;
+            writer.write_all(id)?;
+            write_u16(item_stack.stack, writer)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn read_chests<R: Read>(reader: &mut R) -> std::io::Result<HashMap<TilePos, ChestContents>> {
+    let count = read_u32(reader)?;
+    let mut chests = HashMap::default();
+
+    for _ in 0..count {
+        let pos = TilePos::new(read_u32(reader)?, read_u32(reader)?);
+        let slot_count = read_u16(reader)? as usize;
+
+        if slot_count > CHEST_SLOT_COUNT {
+            return Err(invalid_data(format!("Too many chest slots {}", slot_count)));
+        }
+
+        let mut chest = ChestContents::default();
+
+        for index in 0..slot_count {
+            let mut header = [0u8; 1];
+            reader.read_exact(&mut header)?;
+
+            if header[0] == 0 { continue; }
+
+            reader.read_exact(&mut header)?;
+
+            let mut id = vec![0u8; header[0] as usize];
+            reader.read_exact(&mut id)?;
+
+            let id = String::from_utf8(id).map_err(invalid_data)?;
+            let item = Item::from_id(&id).ok_or_else(|| invalid_data(format!("Unknown item \"{}\"", id)))?;
+            let stack = read_u16(reader)?;
+
+            if stack == 0 || stack > item.max_stack() {
+                return Err(invalid_data(format!("Invalid stack {} of \"{}\"", stack, id)));
+            }
+
+            chest.set_slot(index, Some(ItemStack::new(item).with_stack(stack)));
+        }
+
+        chests.insert(pos, chest);
+    }
+
+    Ok(chests)
+}
+
+fn tree_type_from_u8(value: u8) -> Option<TreeType> {
+    TreeType::ALL
+        .into_iter()
+        .find(|tree_type| *tree_type as u8 == value)
+}
+
+fn tree_frame_type_from_u8(value: u8) -> Option<TreeFrameType> {
+    TreeFrameType::ALL
+        .into_iter()
+        .find(|frame_type| *frame_type as u8 == value)
+}
+
+fn write_layer<const N: usize, W: Write>(mut records: impl Iterator<Item = [u8; N]>, writer: &mut W) -> std::io::Result<()> {
+    let Some(mut current) = records.next() else { return Ok(()); };
+    let mut length: u32 = 1;
+
+    for record in records {
+        if record == current && length < u32::MAX {
+            length += 1;
+            continue;
+        }
+
+        write_run(length, &current, writer)?;
+
+        current = record;
+        length = 1;
+    }
+
+    write_run(length, &current, writer)
+}
+
+#[inline]
+fn write_run<W: Write>(length: u32, record: &[u8], writer: &mut W) -> std::io::Result<()> {
+    write_u32(length, writer)?;
+
+    // An empty tile is stored as the presence byte only
+    if record[0] == 0 {
+        writer.write_all(&record[..1])
+    } else {
+        writer.write_all(record)
+    }
+}
+
+fn read_layer<T: Clone, R: Read>(
+    reader: &mut R,
+    shape: (usize, usize),
+    read_tile: fn(&mut R, bool) -> std::io::Result<Option<T>>
+) -> std::io::Result<Vec<Option<T>>> {
+    let total = shape.0 * shape.1;
+    let mut tiles = Vec::with_capacity(total);
+
+    while tiles.len() < total {
+        let length = read_u32(reader)? as usize;
+
+        if length == 0 || tiles.len() + length > total {
+            return Err(invalid_data("Corrupted tile layer"));
+        }
+
+        let mut present = [0u8; 1];
+        reader.read_exact(&mut present)?;
+
+        let tile = read_tile(reader, present[0] != 0)?;
+
+        tiles.extend(std::iter::repeat(tile).take(length));
+    }
+
+    Ok(tiles)
+}
+
+fn invalid_data<E>(error: E) -> Error
+where
+    E: Into<Box<dyn std::error::Error + Send + Sync>>
+{
+    Error::new(ErrorKind::InvalidData, error)
+}
+
+fn write_urect<W: Write>(rect: URect, writer: &mut W) -> std::io::Result<()> {
+    write_u32(rect.min.x, writer)?;
+    write_u32(rect.min.y, writer)?;
+    write_u32(rect.max.x, writer)?;
+    write_u32(rect.max.y, writer)
+}
+
+fn read_urect<R: Read>(reader: &mut R) -> std::io::Result<URect> {
+    let min = UVec2::new(read_u32(reader)?, read_u32(reader)?);
+    let max = UVec2::new(read_u32(reader)?, read_u32(reader)?);
+
+    Ok(URect { min, max })
+}
+
+#[inline(always)]
+fn write_u32<W: Write>(value: u32, writer: &mut W) -> std::io::Result<()> {
+    writer.write_all(&value.to_le_bytes())
+}
+
+#[inline(always)]
+fn read_u32<R: Read>(reader: &mut R) -> std::io::Result<u32> {
+    let mut bytes = [0u8; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+#[inline(always)]
+fn write_u16<W: Write>(value: u16, writer: &mut W) -> std::io::Result<()> {
+    writer.write_all(&value.to_le_bytes())
+}
+
+#[inline(always)]
+fn read_u16<R: Read>(reader: &mut R) -> std::io::Result<u16> {
+    let mut bytes = [0u8; 2];
+    reader.read_exact(&mut bytes)?;
+    Ok(u16::from_le_bytes(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_ecs_tilemap::tiles::TilePos;
+
+    use crate::{test_utils::load_registries, items::{ChestContents, Item, ItemStack, SlotContainer}, world::{WorldData, WorldSize, generator::generate_world, biome::Biome}};
+
+    use super::{FORMAT_VERSION, write_layer, encode_liquid};
+
+    /// The size of the fields in front of the biomes
+    const HEADER_SIZE: usize = 4 + 2 + 3 * 4 * 4 + 2 * 4;
+
+    const SEED: u32 = 42;
+
+    fn write(world: &WorldData) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        world.write_world(&mut bytes).unwrap();
+        bytes
+    }
+
+    fn read(bytes: &[u8]) -> WorldData {
+        WorldData::read_world(&mut &bytes[..]).unwrap()
+    }
+
+    /// Writes the world in the layout of an older version
+    fn downgrade(world: &WorldData, version: u16) -> Vec<u8> {
+        let mut bytes = write(world);
+        bytes[4..6].copy_from_slice(&version.to_le_bytes());
+
+        // The chest section is the last one, an empty one is just the count
+        if version < 5 {
+            let count = u32::from_le_bytes(bytes[bytes.len() - 4..].try_into().unwrap());
+            assert_eq!(count, 0, "a world with chests can't be downgraded");
+            bytes.truncate(bytes.len() - 4);
+        }
+
+        // The liquids are the last layer
+        if version < 3 {
+            let mut liquids = Vec::new();
+            write_layer(world.liquids.iter().map(|l| encode_liquid(l.as_ref())), &mut liquids).unwrap();
+            bytes.truncate(bytes.len() - liquids.len());
+        }
+
+        if version < 2 {
+            bytes.drain(HEADER_SIZE..HEADER_SIZE + world.biomes.len());
+        }
+
+        bytes
+    }
+
+    fn assert_same_world(expected: &WorldData, actual: &WorldData) {
+        assert_eq!(expected.area, actual.area);
+        assert_eq!(expected.playable_area, actual.playable_area);
+        assert_eq!(expected.spawn_point, actual.spawn_point);
+        assert_eq!(expected.layer.surface, actual.layer.surface);
+        assert_eq!(expected.layer.underground, actual.layer.underground);
+        assert_eq!(expected.layer.cavern, actual.layer.cavern);
+        assert_eq!(expected.layer.dirt_height, actual.layer.dirt_height);
+        assert_eq!(expected.biomes, actual.biomes);
+        assert!(expected.blocks == actual.blocks, "the blocks differ");
+        assert!(expected.walls == actual.walls, "the walls differ");
+        assert!(expected.liquids == actual.liquids, "the liquids differ");
+    }
+
+    #[test]
+    fn generated_world_round_trip() {
+        load_registries();
+
+        let world = generate_world(SEED, WorldSize::Tiny);
+        let loaded = read(&write(&world));
+
+        assert_same_world(&world, &loaded);
+        assert!(loaded.chests.is_empty());
+    }
+
+    #[test]
+    fn chests_round_trip() {
+        load_registries();
+
+        let mut world = generate_world(SEED, WorldSize::Tiny);
+
+        let item = Item::all().next().unwrap();
+        let mut chest = ChestContents::default();
+        chest.set_slot(0, Some(ItemStack::new(item)));
+        chest.set_slot(7, Some(ItemStack::new(item).with_max_stack()));
+
+        let chest_pos = TilePos::new(10, 20);
+        world.chests.insert(chest_pos, chest);
+        // Empty chests are not stored
+        world.chests.insert(TilePos::new(30, 40), ChestContents::default());
+
+        let loaded = read(&write(&world));
+
+        assert_same_world(&world, &loaded);
+        assert_eq!(loaded.chests.len(), 1);
+        assert!(loaded.chests[&chest_pos].slots == chest.slots, "the chest slots differ");
+    }
+
+    #[test]
+    fn loads_older_versions() {
+        load_registries();
+
+        let world = generate_world(SEED, WorldSize::Tiny);
+
+        // Version 4 has no chests, version 3 has no block shapes either,
+        // version 2 has no liquids and version 1 has no biomes
+        for version in [4, 3, 2, 1] {
+            let loaded = read(&downgrade(&world, version));
+
+            let mut expected = read(&write(&world));
+            if version < 3 {
+                expected.liquids.fill(None);
+            }
+            if version < 2 {
+                expected.biomes.fill(Biome::Forest);
+            }
+
+            assert_same_world(&expected, &loaded);
+            assert!(loaded.chests.is_empty());
+        }
+    }
+
+    /// Pins the generated worlds, so a change of the generator output doesn't go unnoticed.
+    /// When the generator is changed on purpose, the checksums are updated.
+    #[test]
+    fn generator_checksums() {
+        load_registries();
+
+        let expected = [
+            (SEED, 0x6690_652C_9EB0_1EEC),
+            (1, 0xB779_7422_0C04_CE08),
+            (123_456_789, 0xDB78_9003_C02D_AE9B),
+        ];
+
+        for (seed, checksum) in expected {
+            assert_eq!(generate_world(seed, WorldSize::Tiny).checksum(), checksum, "the world of seed {seed} has changed");
+        }
+    }
+
+    #[test]
+    fn rejects_newer_versions() {
+        load_registries();
+
+        let world = generate_world(SEED, WorldSize::Tiny);
+        let bytes = downgrade(&world, FORMAT_VERSION + 1);
+
+        assert!(WorldData::read_world(&mut &bytes[..]).is_err());
+    }
+}